@@ -0,0 +1,523 @@
+//! Converts AsciiMath (<http://asciimath.org>) to MathML (see [`crate::interface::set_asciimath`]).
+//!
+//! AsciiMath is forgiving, so the conventions of asciimath.js are followed for its ambiguities:
+//! * `/` makes a fraction of the simple expressions (with their scripts) on either side: `ab/cd` is `a`, `b/c`, `d`
+//! * the outer brackets of an argument to `/`, `^`, `_`, and functions like `sqrt` are removed: `x^(2n)`
+//! * a bracketed list of bracketed rows with the same number of entries is a matrix: `[(1,2),(3,4)]`
+//! * brackets don't need to match (`(a,b]`) and `{:`/`:}` are invisible brackets
+//!
+//! As with LaTeX input, the MathML is meant to be passed to `set_mathml` so that it is cleaned up.
+#![allow(clippy::needless_return)]
+
+use crate::errors::*;
+use crate::latex::{escape_xml, mo, wrap_in_mrow};
+use phf::phf_map;
+
+/// Convert an AsciiMath string to MathML.
+pub fn asciimath_to_mathml(asciimath: &str) -> Result<String> {
+    let mut parser = AsciiMathParser::new(asciimath.trim());
+    let mut children = Vec::new();
+    loop {
+        children.append(&mut parser.parse_expression(false)?);
+        // unmatched right brackets are just operators
+        match parser.next_token() {
+            None => break,
+            Some(token) => children.push(Node::new(token.to_mathml())),
+        }
+    }
+    return Ok(format!("<math>{}</math>", wrap_in_mrow(children.into_iter().map(|node| node.mathml).collect())));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolKind {
+    Identifier,     // <mi>
+    Operator,       // <mo>
+    UnderOver,      // scripts go above and below (e.g., "sum" and "lim")
+    Text,           // <mtext> (e.g., "and")
+    Space,          // <mspace> (the output is the width)
+    LeftBracket,
+    RightBracket,
+    LeftRight,      // '|' and '||' which can be either a left or right bracket
+    Unary,          // takes one argument (e.g., "sqrt" and "hat")
+    Binary,         // takes two arguments (e.g., "frac" and "root")
+    TextArg,        // "text(...)"
+}
+
+/// The AsciiMath symbols (from asciimath.js) with their kind and output
+static SYMBOLS: phf::Map<&str, (SymbolKind, &str)> = phf_map! {
+    // Greek
+    "alpha" => (SymbolKind::Identifier, "α"), "beta" => (SymbolKind::Identifier, "β"), "gamma" => (SymbolKind::Identifier, "γ"),
+    "Gamma" => (SymbolKind::Identifier, "Γ"), "delta" => (SymbolKind::Identifier, "δ"), "Delta" => (SymbolKind::Identifier, "Δ"),
+    "epsilon" => (SymbolKind::Identifier, "ε"), "varepsilon" => (SymbolKind::Identifier, "ɛ"), "zeta" => (SymbolKind::Identifier, "ζ"),
+    "eta" => (SymbolKind::Identifier, "η"), "theta" => (SymbolKind::Identifier, "θ"), "Theta" => (SymbolKind::Identifier, "Θ"),
+    "vartheta" => (SymbolKind::Identifier, "ϑ"), "iota" => (SymbolKind::Identifier, "ι"), "kappa" => (SymbolKind::Identifier, "κ"),
+    "lambda" => (SymbolKind::Identifier, "λ"), "Lambda" => (SymbolKind::Identifier, "Λ"), "mu" => (SymbolKind::Identifier, "μ"),
+    "nu" => (SymbolKind::Identifier, "ν"), "xi" => (SymbolKind::Identifier, "ξ"), "Xi" => (SymbolKind::Identifier, "Ξ"),
+    "pi" => (SymbolKind::Identifier, "π"), "Pi" => (SymbolKind::Identifier, "Π"), "rho" => (SymbolKind::Identifier, "ρ"),
+    "sigma" => (SymbolKind::Identifier, "σ"), "Sigma" => (SymbolKind::Identifier, "Σ"), "tau" => (SymbolKind::Identifier, "τ"),
+    "upsilon" => (SymbolKind::Identifier, "υ"), "phi" => (SymbolKind::Identifier, "ϕ"), "Phi" => (SymbolKind::Identifier, "Φ"),
+    "varphi" => (SymbolKind::Identifier, "φ"), "chi" => (SymbolKind::Identifier, "χ"), "psi" => (SymbolKind::Identifier, "ψ"),
+    "Psi" => (SymbolKind::Identifier, "Ψ"), "omega" => (SymbolKind::Identifier, "ω"), "Omega" => (SymbolKind::Identifier, "Ω"),
+
+    // operators
+    "+" => (SymbolKind::Operator, "+"), "-" => (SymbolKind::Operator, "−"), "*" => (SymbolKind::Operator, "⋅"),
+    "**" => (SymbolKind::Operator, "∗"), "***" => (SymbolKind::Operator, "⋆"), "/" => (SymbolKind::Operator, "/"),
+    "//" => (SymbolKind::Operator, "/"), "\\\\" => (SymbolKind::Operator, "\\"), "setminus" => (SymbolKind::Operator, "∖"),
+    "xx" => (SymbolKind::Operator, "×"), "|><" => (SymbolKind::Operator, "⋉"), "><|" => (SymbolKind::Operator, "⋊"),
+    "|><|" => (SymbolKind::Operator, "⋈"), "-:" => (SymbolKind::Operator, "÷"), "divide" => (SymbolKind::Operator, "÷"),
+    "@" => (SymbolKind::Operator, "∘"), "o+" => (SymbolKind::Operator, "⊕"), "ox" => (SymbolKind::Operator, "⊗"),
+    "o." => (SymbolKind::Operator, "⊙"), "^^" => (SymbolKind::Operator, "∧"), "vv" => (SymbolKind::Operator, "∨"),
+    "nn" => (SymbolKind::Operator, "∩"), "uu" => (SymbolKind::Operator, "∪"), "+-" => (SymbolKind::Operator, "±"),
+    "-+" => (SymbolKind::Operator, "∓"), "," => (SymbolKind::Operator, ","), "'" => (SymbolKind::Operator, "′"),
+    "sum" => (SymbolKind::UnderOver, "∑"), "prod" => (SymbolKind::UnderOver, "∏"), "^^^" => (SymbolKind::UnderOver, "⋀"),
+    "vvv" => (SymbolKind::UnderOver, "⋁"), "nnn" => (SymbolKind::UnderOver, "⋂"), "uuu" => (SymbolKind::UnderOver, "⋃"),
+    "int" => (SymbolKind::Operator, "∫"), "oint" => (SymbolKind::Operator, "∮"),
+
+    // relations
+    "=" => (SymbolKind::Operator, "="), "!=" => (SymbolKind::Operator, "≠"), ":=" => (SymbolKind::Operator, "≔"),
+    "lt" => (SymbolKind::Operator, "<"), "<=" => (SymbolKind::Operator, "≤"), "lt=" => (SymbolKind::Operator, "≤"),
+    "gt" => (SymbolKind::Operator, ">"), ">=" => (SymbolKind::Operator, "≥"), "gt=" => (SymbolKind::Operator, "≥"),
+    "-<" => (SymbolKind::Operator, "≺"), ">-" => (SymbolKind::Operator, "≻"), "in" => (SymbolKind::Operator, "∈"),
+    "!in" => (SymbolKind::Operator, "∉"), "sub" => (SymbolKind::Operator, "⊂"), "sup" => (SymbolKind::Operator, "⊃"),
+    "sube" => (SymbolKind::Operator, "⊆"), "supe" => (SymbolKind::Operator, "⊇"), "-=" => (SymbolKind::Operator, "≡"),
+    "~=" => (SymbolKind::Operator, "≅"), "~~" => (SymbolKind::Operator, "≈"), "~" => (SymbolKind::Operator, "∼"),
+    "prop" => (SymbolKind::Operator, "∝"),
+
+    // logic
+    "and" => (SymbolKind::Text, "and"), "or" => (SymbolKind::Text, "or"), "if" => (SymbolKind::Text, "if"),
+    "not" => (SymbolKind::Operator, "¬"), "=>" => (SymbolKind::Operator, "⇒"), "iff" => (SymbolKind::Operator, "⇔"),
+    "AA" => (SymbolKind::Operator, "∀"), "EE" => (SymbolKind::Operator, "∃"), "_|_" => (SymbolKind::Operator, "⊥"),
+    "TT" => (SymbolKind::Operator, "⊤"), "|--" => (SymbolKind::Operator, "⊢"), "|==" => (SymbolKind::Operator, "⊨"),
+
+    // miscellaneous
+    "del" => (SymbolKind::Operator, "∂"), "grad" => (SymbolKind::Operator, "∇"), "O/" => (SymbolKind::Operator, "∅"),
+    "oo" => (SymbolKind::Identifier, "∞"), "aleph" => (SymbolKind::Identifier, "ℵ"), "..." => (SymbolKind::Operator, "…"),
+    ":." => (SymbolKind::Operator, "∴"), ":'" => (SymbolKind::Operator, "∵"), "/_" => (SymbolKind::Operator, "∠"),
+    "/_\\" => (SymbolKind::Operator, "△"), "cdots" => (SymbolKind::Operator, "⋯"), "vdots" => (SymbolKind::Operator, "⋮"),
+    "ddots" => (SymbolKind::Operator, "⋱"), "diamond" => (SymbolKind::Operator, "⋄"), "square" => (SymbolKind::Operator, "□"),
+    "|__" => (SymbolKind::Operator, "⌊"), "__|" => (SymbolKind::Operator, "⌋"), "|~" => (SymbolKind::Operator, "⌈"),
+    "~|" => (SymbolKind::Operator, "⌉"), "CC" => (SymbolKind::Identifier, "ℂ"), "NN" => (SymbolKind::Identifier, "ℕ"),
+    "QQ" => (SymbolKind::Identifier, "ℚ"), "RR" => (SymbolKind::Identifier, "ℝ"), "ZZ" => (SymbolKind::Identifier, "ℤ"),
+    "\\ " => (SymbolKind::Space, "0.333em"), "quad" => (SymbolKind::Space, "1em"), "qquad" => (SymbolKind::Space, "2em"),
+
+    // functions
+    "sin" => (SymbolKind::Identifier, "sin"), "cos" => (SymbolKind::Identifier, "cos"), "tan" => (SymbolKind::Identifier, "tan"),
+    "sec" => (SymbolKind::Identifier, "sec"), "csc" => (SymbolKind::Identifier, "csc"), "cot" => (SymbolKind::Identifier, "cot"),
+    "arcsin" => (SymbolKind::Identifier, "arcsin"), "arccos" => (SymbolKind::Identifier, "arccos"),
+    "arctan" => (SymbolKind::Identifier, "arctan"), "sinh" => (SymbolKind::Identifier, "sinh"),
+    "cosh" => (SymbolKind::Identifier, "cosh"), "tanh" => (SymbolKind::Identifier, "tanh"), "sech" => (SymbolKind::Identifier, "sech"),
+    "csch" => (SymbolKind::Identifier, "csch"), "coth" => (SymbolKind::Identifier, "coth"), "exp" => (SymbolKind::Identifier, "exp"),
+    "log" => (SymbolKind::Identifier, "log"), "ln" => (SymbolKind::Identifier, "ln"), "det" => (SymbolKind::Identifier, "det"),
+    "dim" => (SymbolKind::Identifier, "dim"), "mod" => (SymbolKind::Identifier, "mod"), "gcd" => (SymbolKind::Identifier, "gcd"),
+    "lcm" => (SymbolKind::Identifier, "lcm"), "lub" => (SymbolKind::Identifier, "lub"), "glb" => (SymbolKind::Identifier, "glb"),
+    "min" => (SymbolKind::UnderOver, "min"), "max" => (SymbolKind::UnderOver, "max"), "lim" => (SymbolKind::UnderOver, "lim"),
+    "Lim" => (SymbolKind::UnderOver, "Lim"),
+
+    // arrows
+    "uarr" => (SymbolKind::Operator, "↑"), "darr" => (SymbolKind::Operator, "↓"), "rarr" => (SymbolKind::Operator, "→"),
+    "->" => (SymbolKind::Operator, "→"), ">->" => (SymbolKind::Operator, "↣"), "->>" => (SymbolKind::Operator, "↠"),
+    ">->>" => (SymbolKind::Operator, "⤖"), "|->" => (SymbolKind::Operator, "↦"), "larr" => (SymbolKind::Operator, "←"),
+    "harr" => (SymbolKind::Operator, "↔"), "rArr" => (SymbolKind::Operator, "⇒"), "lArr" => (SymbolKind::Operator, "⇐"),
+    "hArr" => (SymbolKind::Operator, "⇔"),
+
+    // brackets
+    "(" => (SymbolKind::LeftBracket, "("), ")" => (SymbolKind::RightBracket, ")"),
+    "[" => (SymbolKind::LeftBracket, "["), "]" => (SymbolKind::RightBracket, "]"),
+    "{" => (SymbolKind::LeftBracket, "{"), "}" => (SymbolKind::RightBracket, "}"),
+    "(:" => (SymbolKind::LeftBracket, "⟨"), ":)" => (SymbolKind::RightBracket, "⟩"),
+    "<<" => (SymbolKind::LeftBracket, "⟨"), ">>" => (SymbolKind::RightBracket, "⟩"),
+    "langle" => (SymbolKind::LeftBracket, "⟨"), "rangle" => (SymbolKind::RightBracket, "⟩"),
+    "{:" => (SymbolKind::LeftBracket, ""), ":}" => (SymbolKind::RightBracket, ""),
+    "|" => (SymbolKind::LeftRight, "|"), "||" => (SymbolKind::LeftRight, "‖"),
+
+    // functions of one or two arguments (the output is not used)
+    "sqrt" => (SymbolKind::Unary, ""), "abs" => (SymbolKind::Unary, ""), "floor" => (SymbolKind::Unary, ""),
+    "ceil" => (SymbolKind::Unary, ""), "norm" => (SymbolKind::Unary, ""),
+    "hat" => (SymbolKind::Unary, ""), "bar" => (SymbolKind::Unary, ""), "overline" => (SymbolKind::Unary, ""),
+    "vec" => (SymbolKind::Unary, ""), "dot" => (SymbolKind::Unary, ""), "ddot" => (SymbolKind::Unary, ""),
+    "tilde" => (SymbolKind::Unary, ""), "ul" => (SymbolKind::Unary, ""), "underline" => (SymbolKind::Unary, ""),
+    "obrace" => (SymbolKind::Unary, ""), "ubrace" => (SymbolKind::Unary, ""), "cancel" => (SymbolKind::Unary, ""),
+    "bb" => (SymbolKind::Unary, ""), "bbb" => (SymbolKind::Unary, ""), "cc" => (SymbolKind::Unary, ""),
+    "tt" => (SymbolKind::Unary, ""), "fr" => (SymbolKind::Unary, ""), "sf" => (SymbolKind::Unary, ""),
+    "frac" => (SymbolKind::Binary, ""), "root" => (SymbolKind::Binary, ""), "stackrel" => (SymbolKind::Binary, ""),
+    "overset" => (SymbolKind::Binary, ""), "underset" => (SymbolKind::Binary, ""), "color" => (SymbolKind::Binary, ""),
+    "text" => (SymbolKind::TextArg, ""), "mbox" => (SymbolKind::TextArg, ""),
+};
+
+/// The length of the longest key in `SYMBOLS`
+const MAX_SYMBOL_LEN: usize = 10;
+
+/// Accents: the character and whether it goes over (true) or under (false) the base
+static ACCENTS: phf::Map<&str, (&str, bool)> = phf_map! {
+    "hat" => ("^", true), "bar" => ("¯", true), "overline" => ("¯", true), "vec" => ("→", true),
+    "dot" => ("˙", true), "ddot" => ("¨", true), "tilde" => ("~", true), "obrace" => ("⏞", true),
+    "ul" => ("_", false), "underline" => ("_", false), "ubrace" => ("⏟", false),
+};
+
+/// Font commands and the `mathvariant` they correspond to
+static MATH_VARIANTS: phf::Map<&str, &str> = phf_map! {
+    "bb" => "bold", "bbb" => "double-struck", "cc" => "script", "tt" => "monospace", "fr" => "fraktur", "sf" => "sans-serif",
+};
+
+/// Functions that put their argument in brackets
+static BRACKETING_FUNCTIONS: phf::Map<&str, (&str, &str)> = phf_map! {
+    "abs" => ("|", "|"), "floor" => ("⌊", "⌋"), "ceil" => ("⌈", "⌉"), "norm" => ("‖", "‖"),
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Symbol(&'static str, SymbolKind, &'static str),     // name, kind, output
+    Number(String),
+    Letter(char),
+    Text(String),       // "quoted text"
+    Other(char),
+}
+
+impl Token {
+    fn to_mathml(&self) -> String {
+        return match self {
+            Token::Symbol(_, kind, output) => match kind {
+                SymbolKind::Identifier => format!("<mi>{output}</mi>"),
+                SymbolKind::Text => format!("<mtext> {output} </mtext>"),
+                SymbolKind::Space => format!("<mspace width='{output}'/>"),
+                SymbolKind::UnderOver if output.chars().all(|ch| ch.is_alphabetic()) => format!("<mi>{output}</mi>"),
+                _ => mo(output),
+            },
+            Token::Number(number) => format!("<mn>{number}</mn>"),
+            Token::Letter(ch) => format!("<mi>{ch}</mi>"),
+            Token::Text(text) => format!("<mtext>{}</mtext>", escape_xml(text)),
+            Token::Other(ch) => mo(&ch.to_string()),
+        };
+    }
+}
+
+/// A bracketed group: the brackets (possibly empty) and the nodes inside of them
+#[derive(Debug)]
+struct Group {
+    open: &'static str,
+    close: &'static str,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+struct Node {
+    mathml: String,
+    group: Option<Group>,
+    is_comma: bool,
+    limits: bool,       // scripts go above/below
+}
+
+impl Node {
+    fn new(mathml: String) -> Node {
+        return Node { mathml, group: None, is_comma: false, limits: false };
+    }
+
+    /// Create a bracketed group, turning it into a matrix if it looks like one
+    fn new_group(open: &'static str, close: &'static str, children: Vec<Node>) -> Node {
+        let open_mo = if open.is_empty() {"".to_string()} else {mo(open)};
+        let close_mo = if close.is_empty() {"".to_string()} else {mo(close)};
+        let mathml = match as_matrix(&children) {
+            Some(table) => format!("<mrow>{open_mo}{table}{close_mo}</mrow>"),
+            None => format!("<mrow>{}{}{}</mrow>",
+                            open_mo, children.iter().map(|child| child.mathml.as_str()).collect::<String>(), close_mo),
+        };
+        return Node { mathml, group: Some(Group { open, close, children }), is_comma: false, limits: false };
+    }
+
+    /// The MathML with the outer (), [], or {} removed (used for the arguments of '/', '^', 'sqrt', etc)
+    fn without_brackets(&self) -> String {
+        return match &self.group {
+            Some(group) if matches!(group.open, "(" | "[" | "{" | "") =>
+                wrap_in_mrow(group.children.iter().map(|child| child.mathml.clone()).collect()),
+            _ => self.mathml.clone(),
+        };
+    }
+}
+
+struct AsciiMathParser {
+    chars: Vec<char>,
+    i: usize,
+}
+
+impl AsciiMathParser {
+    fn new(asciimath: &str) -> AsciiMathParser {
+        return AsciiMathParser { chars: asciimath.chars().collect(), i: 0 };
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.i).is_some_and(|ch| ch.is_whitespace()) {
+            self.i += 1;
+        }
+    }
+
+    /// Returns the next token and the number of chars in it
+    fn peek_token(&mut self) -> Option<(Token, usize)> {
+        self.skip_whitespace();
+        let ch = *self.chars.get(self.i)?;
+        if ch.is_ascii_digit() || (ch == '.' && self.chars.get(self.i + 1).is_some_and(|ch| ch.is_ascii_digit())) {
+            let mut len = 0;
+            let mut seen_decimal_point = false;
+            while let Some(&ch) = self.chars.get(self.i + len) {
+                if ch == '.' && !seen_decimal_point && self.chars.get(self.i + len + 1).is_some_and(|ch| ch.is_ascii_digit()) {
+                    seen_decimal_point = true;
+                } else if !ch.is_ascii_digit() {
+                    break;
+                }
+                len += 1;
+            }
+            return Some( (Token::Number(self.chars[self.i..self.i + len].iter().collect()), len) );
+        }
+        if ch == '"' {
+            let len = self.chars[self.i + 1..].iter().position(|&ch| ch == '"').unwrap_or(self.chars.len() - self.i - 1);
+            let text = self.chars[self.i + 1..self.i + 1 + len].iter().collect();
+            return Some( (Token::Text(text), len + 2) );
+        }
+        // longest match
+        let max_len = MAX_SYMBOL_LEN.min(self.chars.len() - self.i);
+        for len in (1..=max_len).rev() {
+            let name: String = self.chars[self.i..self.i + len].iter().collect();
+            if let Some((&key, &(kind, output))) = SYMBOLS.get_entry(name.as_str()) {
+                return Some( (Token::Symbol(key, kind, output), len) );
+            }
+        }
+        if ch.is_alphabetic() {
+            return Some( (Token::Letter(ch), 1) );
+        }
+        return Some( (Token::Other(ch), 1) );
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let (token, len) = self.peek_token()?;
+        self.i += len;
+        return Some(token);
+    }
+
+    fn peek_is(&mut self, name: &str) -> bool {
+        return matches!(self.peek_token(), Some((Token::Symbol(symbol, _, _), _)) if symbol == name) ||
+               matches!(self.peek_token(), Some((Token::Other(ch), _)) if name.len() == 1 && name.starts_with(ch));
+    }
+
+    /// Parse until the end, a right bracket, or (if `in_abs`) a '|'
+    fn parse_expression(&mut self, in_abs: bool) -> Result<Vec<Node>> {
+        let mut children = Vec::new();
+        loop {
+            match self.peek_token() {
+                None | Some((Token::Symbol(_, SymbolKind::RightBracket, _), _)) => return Ok(children),
+                Some((Token::Symbol(_, SymbolKind::LeftRight, _), _)) if in_abs => return Ok(children),
+                _ => (),
+            }
+            let node = self.parse_intermediate()?;
+            if self.peek_is("/") {
+                self.next_token();
+                let denominator = self.parse_intermediate()?;
+                children.push(Node::new(format!("<mfrac>{}{}</mfrac>", node.without_brackets(), denominator.without_brackets())));
+            } else {
+                children.push(node);
+            }
+        }
+    }
+
+    /// Parse a simple expression along with any scripts
+    fn parse_intermediate(&mut self) -> Result<Node> {
+        let base = self.parse_simple()?;
+        let mut sub = None;
+        let mut sup = None;
+        if self.peek_is("_") {
+            self.next_token();
+            sub = Some(self.parse_simple()?.without_brackets());
+        }
+        if self.peek_is("^") {
+            self.next_token();
+            sup = Some(self.parse_simple()?.without_brackets());
+        }
+        let (under, over, both) = if base.limits { ("munder", "mover", "munderover") } else { ("msub", "msup", "msubsup") };
+        return Ok( match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => Node::new(format!("<{under}>{}{sub}</{under}>", base.mathml)),
+            (None, Some(sup)) => Node::new(format!("<{over}>{}{sup}</{over}>", base.mathml)),
+            (Some(sub), Some(sup)) => Node::new(format!("<{both}>{}{sub}{sup}</{both}>", base.mathml)),
+        });
+    }
+
+    fn parse_simple(&mut self) -> Result<Node> {
+        let token = match self.next_token() {
+            None => bail!("AsciiMath error: missing argument at end of input"),
+            Some(token) => token,
+        };
+        let (name, kind, output) = match token {
+            Token::Symbol(name, kind, output) => (name, kind, output),
+            _ => return Ok(Node::new(token.to_mathml())),
+        };
+        return Ok( match kind {
+            SymbolKind::LeftBracket => {
+                let children = self.parse_expression(false)?;
+                let close = match self.peek_token() {
+                    Some((Token::Symbol(_, SymbolKind::RightBracket, close), _)) => {
+                        self.next_token();
+                        close
+                    },
+                    _ => "",
+                };
+                Node::new_group(output, close, children)
+            },
+            SymbolKind::LeftRight => {
+                let start = self.i;
+                let children = self.parse_expression(true)?;
+                if self.peek_is(name) {
+                    self.next_token();
+                    Node::new_group(output, output, children)
+                } else {
+                    // not matched, so it is just an operator
+                    self.i = start;
+                    Node::new(mo(output))
+                }
+            },
+            SymbolKind::Unary => {
+                let arg = self.parse_simple()?;
+                let arg = arg.without_brackets();
+                if name == "sqrt" {
+                    Node::new(format!("<msqrt>{arg}</msqrt>"))
+                } else if name == "cancel" {
+                    Node::new(format!("<menclose notation='updiagonalstrike'>{arg}</menclose>"))
+                } else if let Some(&(open, close)) = BRACKETING_FUNCTIONS.get(name) {
+                    Node::new(format!("<mrow>{}{}{}</mrow>", mo(open), arg, mo(close)))
+                } else if let Some(&variant) = MATH_VARIANTS.get(name) {
+                    Node::new(arg.replace("<mi>", &format!("<mi mathvariant='{variant}'>"))
+                                 .replace("<mn>", &format!("<mn mathvariant='{variant}'>")))
+                } else {
+                    let &(accent, is_over) = ACCENTS.get(name).unwrap();
+                    let is_brace = name.ends_with("brace");
+                    let mathml = if is_over {
+                        format!("<mover accent='true'>{}{}</mover>", arg, mo(accent))
+                    } else {
+                        format!("<munder accentunder='true'>{}{}</munder>", arg, mo(accent))
+                    };
+                    Node { mathml, group: None, is_comma: false, limits: is_brace }
+                }
+            },
+            SymbolKind::Binary => {
+                let first = self.parse_simple()?.without_brackets();
+                let second = self.parse_simple()?.without_brackets();
+                Node::new( match name {
+                    "frac" => format!("<mfrac>{first}{second}</mfrac>"),
+                    "root" => format!("<mroot>{second}{first}</mroot>"),
+                    "underset" => format!("<munder>{second}{first}</munder>"),
+                    "color" => second,
+                    _ => format!("<mover>{second}{first}</mover>"),     // stackrel, overset
+                })
+            },
+            SymbolKind::TextArg => {
+                self.skip_whitespace();
+                if self.chars.get(self.i) != Some(&'(') {
+                    bail!("AsciiMath error: '{}' must be followed by '('", name);
+                }
+                let len = self.chars[self.i..].iter().position(|&ch| ch == ')')
+                    .ok_or_else(|| anyhow!("AsciiMath error: missing ')' after '{}('", name))?;
+                let text: String = self.chars[self.i + 1..self.i + len].iter().collect();
+                self.i += len + 1;
+                Node::new(format!("<mtext>{}</mtext>", escape_xml(&text)))
+            },
+            _ => {
+                let mut node = Node::new(token.to_mathml());
+                node.is_comma = name == ",";
+                node.limits = kind == SymbolKind::UnderOver;
+                node
+            },
+        });
+    }
+}
+
+/// If the `children` are comma-separated bracketed rows with the same brackets and number of comma-separated entries,
+///   return the `mtable` for them.
+fn as_matrix(children: &[Node]) -> Option<String> {
+    let rows: Vec<&[Node]> = children.split(|child| child.is_comma).collect();
+    if rows.len() < 2 {
+        return None;
+    }
+    let mut brackets = None;
+    let mut n_columns = None;
+    let mut table = String::new();
+    for row in rows {
+        let group = match row {
+            [Node { group: Some(group), .. }] if matches!(group.open, "(" | "[") => group,
+            _ => return None,
+        };
+        if *brackets.get_or_insert((group.open, group.close)) != (group.open, group.close) {
+            return None;
+        }
+        let cells: Vec<&[Node]> = group.children.split(|child| child.is_comma).collect();
+        if *n_columns.get_or_insert(cells.len()) != cells.len() {
+            return None;
+        }
+        table.push_str("<mtr>");
+        for cell in cells {
+            table.push_str(&format!("<mtd>{}</mtd>", wrap_in_mrow(cell.iter().map(|node| node.mathml.clone()).collect())));
+        }
+        table.push_str("</mtr>");
+    }
+    return Some(format!("<mtable>{table}</mtable>"));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(asciimath: &str, mathml: &str) {
+        match asciimath_to_mathml(asciimath) {
+            Ok(result) => assert_eq!(result, format!("<math>{mathml}</math>"), "\nAsciiMath: {asciimath}"),
+            Err(e) => panic!("{}", crate::interface::errors_to_string(&e)),
+        }
+    }
+
+    #[test]
+    fn simple() {
+        test("x+2.5", "<mrow><mi>x</mi><mo>+</mo><mn>2.5</mn></mrow>");
+        test("alpha <= oo", "<mrow><mi>α</mi><mo>≤</mo><mi>∞</mi></mrow>");
+        test("sin x", "<mrow><mi>sin</mi><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn fractions_remove_brackets() {
+        test("x^2/(a+b)", "<mfrac><msup><mi>x</mi><mn>2</mn></msup><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow></mfrac>");
+        test("ab/cd", "<mrow><mi>a</mi><mfrac><mi>b</mi><mi>c</mi></mfrac><mi>d</mi></mrow>");
+        test("frac(1)(2)", "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+    }
+
+    #[test]
+    fn scripts() {
+        test("x_(i+1)^2", "<msubsup><mi>x</mi><mrow><mi>i</mi><mo>+</mo><mn>1</mn></mrow><mn>2</mn></msubsup>");
+        test("sum_(i=1)^n i",
+            "<mrow><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi></mrow>");
+        test("lim_(x->0)", "<munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>");
+    }
+
+    #[test]
+    fn functions() {
+        test("sqrt(x+1)", "<msqrt><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></msqrt>");
+        test("root(3)(x)", "<mroot><mi>x</mi><mn>3</mn></mroot>");
+        test("abs(x)", "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>");
+        test("bbb R", "<mi mathvariant='double-struck'>R</mi>");
+        test("hat x", "<mover accent='true'><mi>x</mi><mo>^</mo></mover>");
+        test("text(if ) x", "<mrow><mtext>if </mtext><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn brackets() {
+        test("(a,b]", "<mrow><mo>(</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>]</mo></mrow>");
+        test("|x|", "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>");
+        test("a | b", "<mrow><mi>a</mi><mo>|</mo><mi>b</mi></mrow>");
+        test("x)", "<mrow><mi>x</mi><mo>)</mo></mrow>");
+    }
+
+    #[test]
+    fn matrix() {
+        test("[(1,2),(3,4)]",
+            "<mrow><mo>[</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>\
+             <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr></mtable><mo>]</mo></mrow>");
+        // not the same number of columns
+        test("((1,2),(3))",
+            "<mrow><mo>(</mo><mrow><mo>(</mo><mn>1</mn><mo>,</mo><mn>2</mn><mo>)</mo></mrow><mo>,</mo>\
+             <mrow><mo>(</mo><mn>3</mn><mo>)</mo></mrow><mo>)</mo></mrow>");
+    }
+}
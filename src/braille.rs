@@ -13,8 +13,9 @@ use crate::speech::{BRAILLE_RULES, SpeechRulesWithContext, braille_replace_chars
 use crate::canonicalize::get_parent;
 use std::borrow::Cow;
 use std::ops::Range;
+use std::collections::HashMap;
 use std::sync::LazyLock;
-use log::error;
+use log::{error, warn};
 
 static UEB_PREFIXES: phf::Set<char> = phf_set! {
     '⠼', '⠈', '⠘', '⠸', '⠐', '⠨', '⠰', '⠠',
@@ -31,18 +32,48 @@ fn braille_at(braille: &str, index: usize) -> char {
 /// If 'nav_node_id' is not an empty string, then the element with that id will have dots 7 & 8 turned on as per the pref
 /// Returns the braille string (highlighted) along with the *character* start/end of the highlight (whole string if no highlight)
 pub fn braille_mathml(mathml: Element, nav_node_id: &str) -> Result<(String, usize, usize)> {
+    return braille_mathml_with_sources(mathml, nav_node_id, None);
+}
+
+/// A node whose braille was marked while brailling
+struct BrailleSource {
+    id: String,
+    is_leaf: bool,
+    is_operator: bool,
+}
+
+/// The nodes whose braille was marked while brailling along with the index (into 'nodes') of the source of each braille cell
+/// (None means the cell isn't part of any node's braille -- it belongs to the 'math' element)
+#[derive(Default)]
+struct BrailleSources {
+    nodes: Vec<BrailleSource>,
+    cells: Vec<Option<usize>>,
+}
+
+/// Does the work for [`braille_mathml`]. If 'sources' is given, the source of each cell is recorded in it
+fn braille_mathml_with_sources(mathml: Element, nav_node_id: &str, mut sources: Option<&mut BrailleSources>) -> Result<(String, usize, usize)> {
     return BRAILLE_RULES.with(|rules| {
         rules.borrow_mut().read_files()?;
         let rules = rules.borrow();
         let new_package = Package::new();
         let mut rules_with_context = SpeechRulesWithContext::new(&rules, new_package.as_document(), nav_node_id, 0);
+        if sources.is_some() {
+            rules_with_context.braille_sources = Some(vec![]);
+        }
         let braille_string = rules_with_context.match_pattern::<String>(mathml)
                         .context("Pattern match/replacement failure!")?;
         // debug!("braille_mathml: braille string: {}", &braille_string);
         let braille_string = braille_string.replace(' ', "");
+        let source_nodes = rules_with_context.braille_sources.take();
+        let (braille_string, raw_sources) = match source_nodes {
+            None => (braille_string, vec![]),
+            Some(_) => remove_braille_source_markers(&braille_string),
+        };
+        let raw_braille = if sources.is_some() {braille_string.clone()} else {String::default()};
         let pref_manager = rules_with_context.get_rules().pref_manager.borrow();
         let highlight_style = pref_manager.pref_to_string("BrailleNavHighlight");
         let braille_code = pref_manager.pref_to_string("BrailleCode");
+        let eight_dot_table = eight_dot_table_name(&pref_manager);
         let braille = match braille_code.as_str() {
             "Nemeth" => nemeth_cleanup(pref_manager, braille_string),
             "UEB" => ueb_cleanup(pref_manager, braille_string),
@@ -50,10 +81,26 @@ pub fn braille_mathml(mathml: Element, nav_node_id: &str) -> Result<(String, usi
             "CMU" => cmu_cleanup(pref_manager, braille_string), 
             "Finnish" => finnish_cleanup(pref_manager, braille_string),
             "Swedish" => swedish_cleanup(pref_manager, braille_string),
+            "French" => french_cleanup(pref_manager, braille_string),
             "LaTeX" => LaTeX_cleanup(pref_manager, braille_string),
             "ASCIIMath" => ASCIIMath_cleanup(pref_manager, braille_string),
             _ => braille_string.trim_matches('⠀').to_string(),    // probably needs cleanup if someone has another code, but this will have to get added by hand
         };
+        if let Some(sources) = sources.as_deref_mut() {
+            sources.nodes = source_nodes.unwrap_or_default().into_iter()
+                .map(|node| BrailleSource {
+                    id: node.attribute_value("id").unwrap_or_default().to_string(),
+                    is_leaf: is_leaf(node),
+                    is_operator: name(node) == "mo",
+                })
+                .collect();
+            sources.cells = align_braille_sources(&raw_braille, &raw_sources, &braille, &braille_code, &sources.nodes);
+        }
+
+        if eight_dot_table != "None" {
+            let cell_sources = sources.map(|sources| &mut sources.cells);
+            return Ok( eight_dot_braille(braille, &braille_code, &eight_dot_table, &highlight_style, cell_sources) );
+        }
 
         return Ok(
             if highlight_style != "Off" {
@@ -65,6 +112,65 @@ pub fn braille_mathml(mathml: Element, nav_node_id: &str) -> Result<(String, usi
         );
     });
 
+    /// 8-dot braille uses dots 7 & 8 for its characters, so the highlight is only reported as the [start, end) range
+    /// Text-based codes are converted one char per cell using the 8-dot table; for the other codes, the capital and number indicators
+    ///   are folded into the table's cells (see [`fold_into_eight_dot`]) and 'cell_sources' is updated to match
+    fn eight_dot_braille(braille: String, braille_code: &str, table: &str, highlight_style: &str, cell_sources: Option<&mut Vec<Option<usize>>>) -> (String, usize, usize) {
+        use crate::definitions::BRAILLE_DEFINITIONS;
+        let table_name = format!("EightDot{}", table);
+        return BRAILLE_DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            let table = definitions.get_hashmap(&table_name);
+            if table.is_none() {
+                error!("8-dot braille table '{}' is not defined (EightDot_Table='{}')", table_name, table_name.trim_start_matches("EightDot"));
+            }
+            if !is_text_braille_code(braille_code) {
+                let (braille, start, end) = if highlight_style != "Off" {
+                    highlight_braille_chars(braille, braille_code, highlight_style == "All")
+                } else {
+                    let end = braille.len()/3;
+                    (braille, 0, end)
+                };
+                let braille = braille.chars().map(unhighlight).collect::<String>();
+                let Some(table) = table else {
+                    return (braille, start, end);
+                };
+                let (braille, from_cells) = fold_into_eight_dot(&braille, braille_code, &table);
+                if let Some(cell_sources) = cell_sources {
+                    let new_sources = from_cells.iter().map(|&i| cell_sources.get(i).copied().flatten()).collect();
+                    *cell_sources = new_sources;
+                }
+                // the highlight is in terms of the 6-dot cells
+                let start = from_cells.iter().position(|&i| i >= start).unwrap_or(from_cells.len());
+                let end = from_cells.iter().position(|&i| i >= end).unwrap_or(from_cells.len());
+                return (braille, start, end);
+            }
+            let mut result = String::with_capacity(3*braille.len());
+            let mut n_cells = 0;
+            let mut start = None;
+            let mut end = None;
+            for ch in braille.chars() {
+                match ch {
+                    HIGHLIGHT_START => start = start.or(Some(n_cells)),
+                    HIGHLIGHT_END => end = Some(n_cells),
+                    _ => {
+                        // chars not in the table are left as is -- there isn't a good alternative
+                        match table.as_ref().and_then(|table| table.get(ch.to_string().as_str())) {
+                            Some(cell) => result.push_str(cell),
+                            None => result.push(ch),
+                        }
+                        n_cells += 1;
+                    }
+                }
+            }
+            return match (start, end) {
+                (Some(start), Some(end)) if highlight_style == "FirstChar" => (result, start, end.min(start + 1)),
+                (Some(start), Some(end)) => (result, start, end),
+                _ => (result, 0, n_cells),
+            };
+        });
+    }
+
     /// highlight with dots 7 & 8 based on the highlight style
     /// both the start and stop points will be extended to deal with indicators such as capitalization
     /// if 'fill_range' is true, the interior will be highlighted
@@ -274,6 +380,323 @@ thread_local!{
 }
 
 
+/// Marks the start of the navigation node for text-based codes when they are turned into 8-dot braille (dots 7 & 8 can't be used)
+pub const HIGHLIGHT_START: char = '\u{E000}';
+/// Marks the end of the navigation node for text-based codes when they are turned into 8-dot braille
+pub const HIGHLIGHT_END: char = '\u{E001}';
+
+/// True if the braille code produces text (e.g., ASCIIMath) rather than braille cells
+fn is_text_braille_code(braille_code: &str) -> bool {
+    return matches!(braille_code, "ASCIIMath" | "ASCIIMath-fi" | "LaTeX");
+}
+
+/// True if the nav node should be marked with [`HIGHLIGHT_START`]/[`HIGHLIGHT_END`] instead of dots 7 & 8
+pub fn uses_highlight_markers() -> bool {
+    let pref_manager = PreferenceManager::get();
+    let pref_manager = pref_manager.borrow();
+    return pref_manager.pref_to_string("EightDot_Table") != "None" &&
+           is_text_braille_code(&pref_manager.pref_to_string("BrailleCode"));
+}
+
+/// The name of the 8-dot table to use ("None" if 8-dot braille isn't used).
+/// "Auto" uses the national table for the braille code or language (Swedish for the Swedish code or 'sv', Norwegian for 'nb', 'nn', or 'no')
+///   and NABCC otherwise.
+fn eight_dot_table_name(pref_manager: &PreferenceManager) -> String {
+    let table = pref_manager.pref_to_string("EightDot_Table");
+    if table != "Auto" {
+        return table;
+    }
+    let language = pref_manager.pref_to_string("Language");
+    let language = language.split('-').next().unwrap_or_default();
+    if pref_manager.pref_to_string("BrailleCode") == "Swedish" || language == "sv" {
+        return "Swedish".to_string();
+    }
+    return (if matches!(language, "nb" | "nn" | "no") {"Norwegian"} else {"NABCC"}).to_string();
+}
+
+/// The cells a 6-dot math code uses for capitals and numbers -- 8-dot braille has cells for capitals and digits, so these get folded into them
+struct SixDotIndicators {
+    capital: char,
+    /// None if digits aren't preceded by an indicator
+    number: Option<char>,
+    /// the cells for 0-9
+    digits: [char; 10],
+    /// the cells that can separate the digits of a number (e.g., a decimal point) along with the char they stand for
+    number_separators: &'static [(char, char)],
+}
+
+/// The cells for a-z
+const LETTER_CELLS: [char; 26] = [
+    '⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊', '⠚', '⠅', '⠇', '⠍',
+    '⠝', '⠕', '⠏', '⠟', '⠗', '⠎', '⠞', '⠥', '⠧', '⠺', '⠭', '⠽', '⠵',
+];
+/// The cells for 0-9 in codes that use a-j after the number indicator
+const LETTER_DIGITS: [char; 10] = ['⠚', '⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊'];
+
+/// Returns the indicators for 'braille_code' (None if the code isn't known to use 6-dot capital/number indicators)
+fn six_dot_indicators(braille_code: &str) -> Option<SixDotIndicators> {
+    return match braille_code {
+        "Nemeth" => Some( SixDotIndicators{ capital: '⠠', number: Some('⠼'), digits: ['⠴', '⠂', '⠆', '⠒', '⠲', '⠢', '⠖', '⠶', '⠦', '⠔'],
+                                             number_separators: &[('⠨', '.'), ('⠠', ',')] } ),
+        "UEB" => Some( SixDotIndicators{ capital: '⠠', number: Some('⠼'), digits: LETTER_DIGITS, number_separators: &[('⠲', '.'), ('⠂', ',')] } ),
+        "Swedish" | "Finnish" => Some( SixDotIndicators{ capital: '⠠', number: Some('⠼'), digits: LETTER_DIGITS, number_separators: &[('⠂', ',')] } ),
+        "CMU" => Some( SixDotIndicators{ capital: '⠨', number: Some('⠼'), digits: LETTER_DIGITS, number_separators: &[('⠂', ','), ('⠄', '.')] } ),
+        "Vietnam" => Some( SixDotIndicators{ capital: '⠨', number: Some('⠼'), digits: LETTER_DIGITS, number_separators: &[] } ),
+        "French" => Some( SixDotIndicators{ capital: '⠨', number: None, digits: LETTER_DIGITS, number_separators: &[] } ),
+        _ => None,
+    };
+}
+
+/// Fold the capital and number indicators of a 6-dot code into the capitals and digits of the 8-dot 'table' (the other cells are unchanged).
+/// E.g., for Swedish, "⠠⠁⠀⠼⠁⠃" becomes "⡁⠀⠡⠣" with the Swedish table.
+/// Returns the 8-dot braille along with the index of the 6-dot cell that each 8-dot cell came from.
+fn fold_into_eight_dot(braille: &str, braille_code: &str, table: &HashMap<String, String>) -> (String, Vec<usize>) {
+    let cells = braille.chars().collect::<Vec<char>>();
+    let indicators = match six_dot_indicators(braille_code) {
+        None => return (braille.to_string(), (0..cells.len()).collect()),
+        Some(indicators) => indicators,
+    };
+    let lookup = |ch: char| table.get(ch.to_string().as_str()).and_then(|cell| cell.chars().next());
+    let capital = |cell: char| LETTER_CELLS.iter().position(|&letter| letter == cell)
+                            .and_then(|i| lookup((b'A' + i as u8) as char));
+    let digit = |cell: char| indicators.digits.iter().position(|&digit| digit == cell).map(|i| (b'0' + i as u8) as char);
+
+    let mut result = String::with_capacity(braille.len());
+    let mut from_cells = Vec::with_capacity(cells.len());
+    let mut i = 0;
+    while i < cells.len() {
+        // the 8-dot cells (and where they came from) that replace the indicator and what it applies to
+        let mut folded: Vec<(char, usize)> = vec![];
+        let mut j = i + 1;
+        if cells[i] == indicators.capital {
+            // a double capital indicator capitalizes the letters that follow it
+            let n_indicators = cells[i..].iter().take_while(|&&cell| cell == indicators.capital).count();
+            j = i + n_indicators;
+            while j < cells.len() && (folded.is_empty() || n_indicators > 1) {
+                match capital(cells[j]) {
+                    Some(eight_dot_cell) => folded.push( (eight_dot_cell, j) ),
+                    None => break,
+                }
+                j += 1;
+            }
+        } else if Some(cells[i]) == indicators.number {
+            while j < cells.len() {
+                let ch = match digit(cells[j]) {
+                    Some(ch) => ch,
+                    None => match indicators.number_separators.iter().find(|(separator, _)| *separator == cells[j]) {
+                        Some(&(_, ch)) if !folded.is_empty() && j + 1 < cells.len() && digit(cells[j+1]).is_some() => ch,
+                        _ => break,
+                    },
+                };
+                match lookup(ch) {
+                    Some(eight_dot_cell) => folded.push( (eight_dot_cell, j) ),
+                    None => break,
+                }
+                j += 1;
+            }
+        }
+        if folded.is_empty() {
+            result.push(cells[i]);
+            from_cells.push(i);
+            i += 1;
+        } else {
+            for (eight_dot_cell, from_cell) in folded {
+                result.push(eight_dot_cell);
+                from_cells.push(from_cell);
+            }
+            i = j;
+        }
+    }
+    return (result, from_cells);
+}
+
+/// Marks the start of the braille for a node while brailling (the index of the node is added to it)
+const BRAILLE_SOURCE_START: u32 = 0xF0000;     // Supplementary Private Use Area-A
+/// The number of nodes that can be marked -- the braille for nodes after that is part of their marked ancestor
+const MAX_BRAILLE_SOURCES: usize = 0xFFFE;
+/// Marks the end of the braille for a node while brailling
+const BRAILLE_SOURCE_END: char = '\u{100000}';   // Supplementary Private Use Area-B
+
+/// Wrap the braille for a node with markers so that the braille cells can be traced back to the node after the cleanup.
+/// 'source_index' is the index of the node in the list of nodes whose braille is being marked.
+pub fn mark_braille_source(braille: String, source_index: usize) -> String {
+    if source_index >= MAX_BRAILLE_SOURCES {
+        return braille;
+    }
+    let start = char::from_u32(BRAILLE_SOURCE_START + source_index as u32).unwrap();
+    return format!("{start}{braille}{BRAILLE_SOURCE_END}");
+}
+
+/// Remove the markers added by [`mark_braille_source`] and return the braille along with the source of each char
+/// (the innermost marked node enclosing it)
+fn remove_braille_source_markers(braille: &str) -> (String, Vec<Option<usize>>) {
+    let mut result = String::with_capacity(braille.len());
+    let mut sources = Vec::with_capacity(braille.len()/3);
+    let mut open_sources: Vec<usize> = vec![];
+    for ch in braille.chars() {
+        let ch_as_u32 = ch as u32;
+        if ch == BRAILLE_SOURCE_END {
+            open_sources.pop();
+        } else if (BRAILLE_SOURCE_START..BRAILLE_SOURCE_START + MAX_BRAILLE_SOURCES as u32).contains(&ch_as_u32) {
+            open_sources.push((ch_as_u32 - BRAILLE_SOURCE_START) as usize);
+        } else {
+            result.push(ch);
+            sources.push(open_sources.last().copied());
+        }
+    }
+    return (result, sources);
+}
+
+/// Find the source of each cell in the cleaned up braille given the source of each char in the raw braille.
+/// The cleanup adds, removes, and changes indicators but otherwise keeps the raw braille, so the cells are aligned with the raw chars
+///   (a longest common subsequence) after the indicators in the raw braille (e.g., "N" or "C") are replaced by their cells.
+/// A cell that is aligned with a raw cell gets its source. The other cells were added by the cleanup: they get the source of the next raw cell,
+///   except at the start or end, where they are for the whole expression (e.g., grade 1 passage indicators).
+/// The alignment takes time and memory proportional to the number of raw cells times the number of cells. If that is too big,
+///   a single pass that matches each cell with a nearby raw cell is used instead (see [`align_braille_sources_linearly`]).
+/// As with the search in [`get_navigation_node_from_braille_position`], whitespace next to an operator belongs to the operator.
+fn align_braille_sources(raw: &str, raw_sources: &[Option<usize>], braille: &str, braille_code: &str, nodes: &[BrailleSource]) -> Vec<Option<usize>> {
+    const MAX_TABLE_SIZE: usize = 16_000_000;     // about 4000 cells -- far bigger than any braille display
+    // (cell, source, weight) -- aligning the cells for the content is more important than aligning the cells for indicators
+    let mut raw_cells: Vec<(char, Option<usize>, u16)> = Vec::with_capacity(raw_sources.len());
+    for (ch, source) in raw.chars().zip(raw_sources) {
+        match indicator_cells(braille_code, ch) {
+            Some(cells) => raw_cells.extend(cells.chars().map(|cell| (unhighlight(cell), *source, 1))),
+            None => raw_cells.push( (unhighlight(ch), *source, 2) ),
+        }
+    }
+    let cells = braille.chars().map(unhighlight).collect::<Vec<char>>();
+    let (n_raw, n_cells) = (raw_cells.len(), cells.len());
+    let mut cell_sources: Vec<Option<usize>> = vec![None; n_cells];
+    if (n_raw + 1) * (n_cells + 1) > MAX_TABLE_SIZE {
+        warn!("The braille is too long ({n_cells} cells) to align exactly with the MathML; the source of some cells may be off");
+        align_braille_sources_linearly(&raw_cells, &cells, &mut cell_sources);
+    } else {
+        // common[i][j] is the (weighted) length of the longest common subsequence of raw_cells[i..] and cells[j..]
+        let width = n_cells + 1;
+        let mut common = vec![0u16; (n_raw + 1) * width];
+        for i in (0..n_raw).rev() {
+            for j in (0..n_cells).rev() {
+                common[i*width + j] = if raw_cells[i].0 == cells[j] {
+                    raw_cells[i].2 + common[(i+1)*width + j+1]
+                } else {
+                    common[(i+1)*width + j].max(common[i*width + j+1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while j < n_cells {
+            if i < n_raw && raw_cells[i].0 == cells[j] && common[i*width + j] == raw_cells[i].2 + common[(i+1)*width + j+1] {
+                cell_sources[j] = raw_cells[i].1;
+                i += 1;
+                j += 1;
+            } else if i < n_raw && common[(i+1)*width + j] >= common[i*width + j+1] {
+                i += 1;     // the cleanup removed the raw cell
+            } else {
+                // the cleanup added the cell -- if it is before/after all the aligned cells, it is for the whole expression
+                let is_at_start_or_end = i == 0 || common[i*width + j] == 0;
+                cell_sources[j] = if is_at_start_or_end {None} else {raw_cells[i].1};
+                j += 1;
+            }
+        }
+    }
+
+    // the whitespace is often generated by the rule for a neighbor, so fix it up after all the sources are known
+    let is_operator = |source: Option<usize>| source.is_some_and(|i| nodes[i].is_operator);
+    for (i, cell) in braille.chars().enumerate() {
+        if unhighlight(cell) == '⠀' {
+            let previous_source = if i == 0 {None} else {cell_sources[i-1]};
+            let next_source = cell_sources.get(i+1).copied().flatten();
+            if is_operator(previous_source) {
+                cell_sources[i] = previous_source;
+            } else if is_operator(next_source) {
+                cell_sources[i] = next_source;
+            }
+        }
+    }
+    return cell_sources;
+
+    /// The cells an indicator in the raw braille might become (None if 'ch' isn't an indicator)
+    fn indicator_cells(braille_code: &str, ch: char) -> Option<&'static str> {
+        let mut buffer = [0; 4];
+        let ch = &*ch.encode_utf8(&mut buffer);
+        return match braille_code {
+            // number and multipurpose indicators are only potential indicators -- if they are removed, the cell won't match
+            "Nemeth" if ch == "N" => Some("⠼"),
+            "Nemeth" if ch == "M" => Some("⠐"),
+            "Nemeth" => NEMETH_INDICATOR_REPLACEMENTS.get(ch).copied(),
+            "UEB" => UEB_INDICATOR_REPLACEMENTS.get(ch).copied(),
+            "Vietnam" => VIETNAM_INDICATOR_REPLACEMENTS.get(ch).copied(),
+            _ => None,
+        };
+    }
+}
+
+/// Align 'cells' with the (cell, source, weight) 'raw_cells' in a single pass (used when [`align_braille_sources`] can't use a table).
+/// A cell gets the source of the first matching raw cell among the next few raw cells (the cleanup only removes a few indicators in a row);
+///   if there isn't one, the cleanup added the cell and it gets the source of the next raw cell (None at the start or end).
+fn align_braille_sources_linearly(raw_cells: &[(char, Option<usize>, u16)], cells: &[char], cell_sources: &mut [Option<usize>]) {
+    const MAX_LOOK_AHEAD: usize = 8;
+    let mut i = 0;
+    for (j, cell) in cells.iter().enumerate() {
+        let look_ahead = &raw_cells[i..raw_cells.len().min(i + MAX_LOOK_AHEAD)];
+        match look_ahead.iter().position(|raw_cell| raw_cell.0 == *cell) {
+            Some(k) => {
+                cell_sources[j] = raw_cells[i + k].1;
+                i += k + 1;
+            },
+            None => cell_sources[j] = if i == 0 {None} else {raw_cells.get(i).and_then(|raw_cell| raw_cell.1)},
+        }
+    }
+}
+
+/// Braille 'mathml' (see [`braille_mathml`]) and return the braille along with a vector that has an entry for each braille cell.
+/// Each entry is the id of the smallest MathML node enclosing the cell and the offset of the cell within that node if it is a leaf (0 otherwise).
+/// The sources are recorded while brailling, so this costs about the same as [`braille_mathml`] (no search is done for each cell).
+pub fn braille_mathml_with_source_map(mathml: Element, nav_node_id: &str) -> Result<(String, Vec<(String, usize)>)> {
+    let math_id = match mathml.attribute_value("id") {
+        None => bail!("'id' is not present on mathml: {}", mml_to_string(mathml)),
+        Some(id) => id,
+    };
+    let mut sources = BrailleSources::default();
+    let braille = braille_mathml_with_sources(mathml, nav_node_id, Some(&mut sources))?.0;
+    let n_cells = braille.chars().count();
+    // the conversion to 8-dot braille is one cell per char, so this shouldn't happen
+    sources.cells.resize(n_cells, None);
+
+    // offsets are relative to the first (non-whitespace) cell of a leaf
+    let mut first_cell: Vec<Option<usize>> = vec![None; sources.nodes.len()];
+    let mut source_map = Vec::with_capacity(n_cells);
+    for (i, (cell, source)) in braille.chars().zip(&sources.cells).enumerate() {
+        match source {
+            None => source_map.push( (math_id.to_string(), 0) ),
+            Some(source) => {
+                let node = &sources.nodes[*source];
+                let mut offset = 0;
+                if node.is_leaf && unhighlight(cell) != '⠀' {
+                    offset = i - *first_cell[*source].get_or_insert(i);
+                }
+                source_map.push( (node.id.clone(), offset) );
+            },
+        }
+    }
+    return Ok( (braille, source_map) );
+}
+
+fn is_operator_that_adds_whitespace(node: Element) -> bool {
+    use crate::definitions::BRAILLE_DEFINITIONS;
+    if PreferenceManager::get().borrow().pref_to_string("UseSpacesAroundAllOperators") == "true" {
+        return true;
+    } 
+
+    return BRAILLE_DEFINITIONS.with(|definitions| {
+        let definitions = definitions.borrow();
+        let comparison_operators = definitions.get_hashset("ComparisonOperators").unwrap();
+        return comparison_operators.contains(as_text(node));
+    });        
+}
+
 /// Given a 0-based braille position, return the id of the smallest MathML node enclosing it.
 /// This node might be a leaf with an offset.
 pub fn get_navigation_node_from_braille_position(mathml: Element, position: usize) -> Result<(String, usize)> {
@@ -296,7 +719,7 @@ pub fn get_navigation_node_from_braille_position(mathml: Element, position: usiz
     }
 
     // save the current highlight state, set the state to be the end points so we can find the braille, then restore the state
-    // FIX: this can fail if there is 8-dot braille
+    // Only the [start, end) range returned by braille_mathml() is used, so this works for 8-dot braille (which never highlights with dots 7 & 8)
     use crate::interface::{get_preference, set_preference};
     let saved_highlight_style = get_preference("BrailleNavHighlight").unwrap();
     set_preference("BrailleNavHighlight", "EndPoints").unwrap();
@@ -330,10 +753,12 @@ pub fn get_navigation_node_from_braille_position(mathml: Element, position: usiz
         };
         N_PROBES.with(|n| {*n.borrow_mut() += 1});
         let (braille, char_start, char_end) = braille_mathml(mathml, node_id)?;
+        // 8-dot tables might leave chars that aren't in the table as is, so don't assume 3 bytes/char
+        let braille: Vec<char> = braille.chars().collect();
         let mut status = None;
         // debug!("find_navigation_node ({}, id={}): highlight=[{}, {});  target={}", name(node), node_id, char_start, char_end, target_position);
         if is_leaf(node) {
-            if char_start == 0 && char_end == braille.len()/3 {
+            if char_start == 0 && char_end == braille.len() {
                 // nothing highlighted -- probably invisible char not represented in braille -- continue looking to the right
                 // debug!("  return due invisible char (?)' ");
                 status = Some(SearchStatus::LookRight);
@@ -349,9 +774,9 @@ pub fn get_navigation_node_from_braille_position(mathml: Element, position: usiz
             } else if name(node) == "mo" {
                 // if there is whitespace before or after the operator, consider the operator to be a match
                 if (char_start > 0 && target_position == char_start - 1 && 
-                    braille_at(&braille, 3*(char_start - 1)) == '⠀' && is_operator_that_adds_whitespace(node)) ||
-                   (3*char_end < braille.len() && target_position == char_end &&
-                    braille_at(&braille, 3*char_end) == '⠀' && is_operator_that_adds_whitespace(node)) {
+                    braille[char_start - 1] == '⠀' && is_operator_that_adds_whitespace(node)) ||
+                   (char_end < braille.len() && target_position == char_end &&
+                    braille[char_end] == '⠀' && is_operator_that_adds_whitespace(node)) {
                     return Ok( SearchState {
                         status: SearchStatus::Found,
                         node,
@@ -433,19 +858,6 @@ pub fn get_navigation_node_from_braille_position(mathml: Element, position: usiz
         } );
     }
 
-    fn is_operator_that_adds_whitespace(node: Element) -> bool {
-        use crate::definitions::BRAILLE_DEFINITIONS;
-        if PreferenceManager::get().borrow().pref_to_string("UseSpacesAroundAllOperators") == "true" {
-            return true;
-        } 
-
-        return BRAILLE_DEFINITIONS.with(|definitions| {
-            let definitions = definitions.borrow();
-            let comparison_operators = definitions.get_hashset("ComparisonOperators").unwrap();
-            return comparison_operators.contains(as_text(node));
-        });        
-    }
-
     /// look in children[i_left..i_right] for a count that exceeds target
     fn guess_child_node_ltr(children: &[ChildOfElement], i_left: usize, i_right: usize, start: usize, target: usize) -> usize {
         let mut estimated_position = start;
@@ -503,6 +915,38 @@ pub fn get_navigation_node_from_braille_position(mathml: Element, position: usiz
     }
 }
 
+static NEMETH_INDICATOR_REPLACEMENTS: phf::Map<&str, &str> = phf_map! {
+    "S" => "⠠⠨",    // sans-serif
+    "B" => "⠸",     // bold
+    "𝔹" => "⠨",     // blackboard
+    "T" => "⠈",     // script
+    "I" => "⠨",     // italic (mapped to be the same a blackboard)
+    "R" => "",      // roman
+    "E" => "⠰",     // English
+    "D" => "⠸",     // German (Deutsche)
+    "G" => "⠨",     // Greek
+    "V" => "⠨⠈",    // Greek Variants
+    "H" => "⠠⠠",    // Hebrew
+    "U" => "⠈⠈",    // Russian
+    "C" => "⠠",     // capital
+    "P" => "⠸",     // punctuation
+    "𝐏" => "⠸",     // hack for punctuation after a roman numeral -- never removed
+    "L" => "",      // letter
+    "l" => "",      // letter inside enclosed list
+    "M" => "",      // multipurpose indicator
+    "m" => "⠐",     // required multipurpose indicator
+    "N" => "",      // potential number indicator before digit
+    "n" => "⠼",     // required number indicator before digit
+    "𝑁" => "",      // hack for special case of a lone decimal pt -- not considered a number but follows rules mostly
+    "W" => "⠀",     // whitespace
+    "w" => "⠀",     // whitespace from comparison operator
+    "," => "⠠⠀",    // comma
+    "b" => "⠐",     // baseline
+    "𝑏" => "⣐",     // highlight baseline (it's a hack)
+    "↑" => "⠘",     // superscript
+    "↓" => "⠰",     // subscript
+};
+
 fn nemeth_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) -> String {
     // Typeface: S: sans-serif, B: bold, T: script/blackboard, I: italic, R: Roman
     // Language: E: English, D: German, G: Greek, V: Greek variants, H: Hebrew, U: Russian
@@ -512,37 +956,6 @@ fn nemeth_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) ->
     //      𝑁 -- hack for special case of a lone decimal pt -- not considered a number but follows rules mostly 
     // SRE doesn't have H: Hebrew or U: Russian, so not encoded (yet)
     // Note: some "positive" patterns find cases to keep the char and transform them to the lower case version
-    static NEMETH_INDICATOR_REPLACEMENTS: phf::Map<&str, &str> = phf_map! {
-        "S" => "⠠⠨",    // sans-serif
-        "B" => "⠸",     // bold
-        "𝔹" => "⠨",     // blackboard
-        "T" => "⠈",     // script
-        "I" => "⠨",     // italic (mapped to be the same a blackboard)
-        "R" => "",      // roman
-        "E" => "⠰",     // English
-        "D" => "⠸",     // German (Deutsche)
-        "G" => "⠨",     // Greek
-        "V" => "⠨⠈",    // Greek Variants
-        "H" => "⠠⠠",    // Hebrew
-        "U" => "⠈⠈",    // Russian
-        "C" => "⠠",     // capital
-        "P" => "⠸",     // punctuation
-        "𝐏" => "⠸",     // hack for punctuation after a roman numeral -- never removed
-        "L" => "",      // letter
-        "l" => "",      // letter inside enclosed list
-        "M" => "",      // multipurpose indicator
-        "m" => "⠐",     // required multipurpose indicator
-        "N" => "",      // potential number indicator before digit
-        "n" => "⠼",     // required number indicator before digit
-        "𝑁" => "",      // hack for special case of a lone decimal pt -- not considered a number but follows rules mostly
-        "W" => "⠀",     // whitespace
-        "w" => "⠀",     // whitespace from comparison operator
-        "," => "⠠⠀",    // comma
-        "b" => "⠐",     // baseline
-        "𝑏" => "⣐",     // highlight baseline (it's a hack)
-        "↑" => "⠘",     // superscript
-        "↓" => "⠰",     // subscript
-    };
 
     // Add an English Letter indicator. This involves finding "single letters".
     // The green book has a complicated set of cases, but the Nemeth UEB Rule book (May 2020), 4.10 has a much shorter explanation:
@@ -590,7 +1003,8 @@ fn nemeth_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) ->
     // 2. optional minus sign (⠤)
     // 3. optional typeface indicator
     // 4. number (N)
-    static NUM_IND_9A: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?P<start>^|[,Ww])(?P<minus>⠤?)N").unwrap());
+    // Note: '›' ends UEB text (Nemeth in UEB contexts) -- the math that follows starts after a switch indicator and space
+    static NUM_IND_9A: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?P<start>^|[,Ww›])(?P<minus>⠤?)N").unwrap());
 
     // Needed after section mark(§), paragraph mark(¶), #, or *
     static NUM_IND_9C: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(⠤?)(⠠⠷|⠠⠳|⠠⠈⠷)N").unwrap());
@@ -728,12 +1142,79 @@ fn nemeth_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) ->
         }
     });
 
+    let result = if pref_manager.pref_to_string("Nemeth_UEBContext") == "true" {
+        Cow::Owned(add_ueb_context_indicators(&result))
+    } else {
+        result
+    };
+
     // Remove unicode blanks at start and end -- do this after the substitutions because ',' introduces spaces
     let result = result.trim_start_matches('⠀').trim_end_matches('⠀');
     let result = COLLAPSE_SPACES.replace_all(result, "⠀");
    
     return result.to_string();
 
+    /// Nemeth embedded in UEB text (BANA "Guidance for Transcription Using the Nemeth Code within UEB Contexts"):
+    /// the math is wrapped with the opening (⠸⠩) and termination (⠸⠱) indicators, each separated from the math by a space.
+    /// Text (marked by '‹' and '›' in the rules) at the start or end is left outside of the indicators.
+    /// A single word of text inside the math uses the one-word switch indicator (⠸⠰); longer text terminates and reopens Nemeth.
+    fn add_ueb_context_indicators(braille: &str) -> String {
+        static UEB_TEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"‹([^›]*)›").unwrap());
+
+        // split into alternating (is_text, braille) segments, merging adjacent text
+        let mut segments: Vec<(bool, String)> = vec![];
+        let mut start = 0;
+        for text in UEB_TEXT.captures_iter(braille) {
+            let matched = text.get(0).unwrap();
+            segments.push( (false, braille[start..matched.start()].to_string()) );
+            segments.push( (true, text[1].to_string()) );
+            start = matched.end();
+        }
+        segments.push( (false, braille[start..].to_string()) );
+        let mut merged: Vec<(bool, String)> = Vec::with_capacity(segments.len());
+        for (is_text, segment) in segments {
+            if !is_text && segment.trim_matches('⠀').is_empty() {
+                if let Some(last) = merged.last_mut() {
+                    last.1.push_str(&segment);
+                }
+                continue;
+            }
+            match merged.last_mut() {
+                Some(last) if last.0 == is_text => last.1.push_str(&segment),
+                _ => merged.push( (is_text, segment) ),
+            }
+        }
+
+        let first_math = merged.iter().position(|(is_text, _)| !is_text);
+        let last_math = merged.iter().rposition(|(is_text, _)| !is_text);
+        if first_math.is_none() {
+            // no math, just text
+            return merged.into_iter().map(|(_, segment)| segment).collect::<Vec<String>>().join("⠀");
+        }
+        let (first_math, last_math) = (first_math.unwrap(), last_math.unwrap());
+        let mut result = String::with_capacity(braille.len() + 24);
+        for (i, (is_text, segment)) in merged.iter().enumerate() {
+            if i == first_math {
+                result.push_str("⠀⠸⠩⠀");
+            }
+            if !is_text || i < first_math || i > last_math {
+                result.push_str(segment);
+            } else if segment.trim_matches('⠀').contains('⠀') {
+                result.push_str("⠀⠸⠱⠀");
+                result.push_str(segment);
+                result.push_str("⠀⠸⠩⠀");
+            } else {
+                result.push_str("⠀⠸⠰⠀");
+                result.push_str(segment);
+                result.push('⠀');
+            }
+            if i == last_math {
+                result.push_str("⠀⠸⠱⠀");
+            }
+        }
+        return result;
+    }
+
     fn remove_baseline_before_space_or_punctuation<'a>(braille: &'a mut Cow<'a, str>) -> Cow<'a, str> {
         // If the baseline highlight is at the end of the string and it is going to be deleted by the regex,
         //   then we need to shift the highlight to the left if what is to it's left is not whitespace (which should never be a highlight end)
@@ -2168,6 +2649,68 @@ fn swedish_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) ->
     return result.to_string();
 }
 
+static FRENCH_INDICATOR_REPLACEMENTS: phf::Map<&str, &str> = phf_map! {
+    "S" => "XXX",    // sans-serif -- from prefs
+    "B" => "XXX",    // bold -- from prefs
+    "𝔹" => "XXX",    // blackboard -- from prefs
+    "T" => "XXX",    // script -- from prefs
+    "D" => "XXX",    // German (Fraktur) -- from prefs
+    "I" => "",      // italic -- the normal style for math letters, so not marked
+    "R" => "",      // roman
+    "L" => "",      // Letter left in to assist in locating letters
+    "G" => "⠘",     // Greek
+    "C" => "⠨",     // capital
+    "𝐶" => "⠨",     // capital that never should get word indicator (from chemical element)
+    "N" => "",      // number indicator -- Antoine digits don't need one
+    "W" => "⠀",     // whitespace
+    "𝐖"=> "⠀",     // whitespace
+    "w" => "⠀",     // whitespace after function name
+    "s" => "",      // typeface single char indicator
+};
+
+fn french_cleanup(pref_manager: Ref<PreferenceManager>, raw_braille: String) -> String {
+    static REPLACE_INDICATORS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([SB𝔹TDIRLGC𝐶NW𝐖ws])").unwrap());
+    // a run of two or more capital letters uses the double capital indicator
+    static CAPITAL_RUN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:CL[\u2800-\u28FF]){2,}").unwrap());
+    // function names are followed by a space, but not if a fence follows (the fence already separates them)
+    static FUNCTION_SPACE_BEFORE_FENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"w(⠠⠦|⠠⠠⠦|⠨⠦)").unwrap());
+
+    // debug!("french_cleanup: start={}", raw_braille);
+    let result = CAPITAL_RUN.replace_all(&raw_braille, |cap: &Captures| {
+        return "⠨⠨".to_string() + &cap[0].replace("CL", "");
+    });
+    let result = result.replace("CG", "G⠨");    // Greek indicator comes before the capital indicator
+    let result = FUNCTION_SPACE_BEFORE_FENCE.replace_all(&result, "$1");
+
+    // these typeforms need to get pulled from user-prefs as they are transcriber-defined
+    let bold = pref_manager.pref_to_string("French_Bold");
+    let double_struck = pref_manager.pref_to_string("French_DoubleStruck");
+    let sans_serif = pref_manager.pref_to_string("French_SansSerif");
+    let script = pref_manager.pref_to_string("French_Script");
+    let fraktur = pref_manager.pref_to_string("French_Fraktur");
+
+    let result = REPLACE_INDICATORS.replace_all(&result, |cap: &Captures| {
+        let matched_char = &cap[0];
+        match matched_char {
+            "B" => &bold,
+            "𝔹" => &double_struck,
+            "S" => &sans_serif,
+            "T" => &script,
+            "D" => &fraktur,
+            _ => match FRENCH_INDICATOR_REPLACEMENTS.get(matched_char) {
+                None => {error!("REPLACE_INDICATORS and FRENCH_INDICATOR_REPLACEMENTS are not in sync: missing '{matched_char}'"); ""},
+                Some(&ch) => ch,
+            },
+        }
+    });
+
+    // Remove unicode blanks at start and end -- do this after the substitutions because 'w' introduces spaces
+    let result = result.trim_start_matches('⠀').trim_end_matches('⠀');
+    let result = COLLAPSE_SPACES.replace_all(result, "⠀");
+
+    return result.to_string();
+}
+
 #[allow(non_snake_case)]
 fn LaTeX_cleanup(_pref_manager: Ref<PreferenceManager>, raw_braille: String) -> String {
     static REMOVE_SPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r" ([\^_,;)\]}])").unwrap()); // '^', '_', ',', ';', ')', ']', '}'
@@ -2331,6 +2874,8 @@ impl BrailleChars {
             "Vietnam" => BrailleChars:: get_braille_vietnam_chars(node, text_range),
             "Swedish" => BrailleChars:: get_braille_ueb_chars(node, text_range),    // FIX: need to figure out what to implement
             "Finnish" => BrailleChars:: get_braille_ueb_chars(node, text_range),    // FIX: need to figure out what to implement
+            "French" => BrailleChars:: get_braille_ueb_chars(node, text_range),     // typeforms/caps are handled in french_cleanup
+            "UEBText" => BrailleChars:: get_braille_ueb_text_chars(node, text_range),   // text in Nemeth within UEB contexts
             _ => return Err(sxd_xpath::function::Error::Other(format!("get_braille_chars: unknown braille code '{code}'")))
         };
         return match result {
@@ -2467,6 +3012,62 @@ impl BrailleChars {
         return Ok(result.to_string())
     }
 
+    /// Returns (uncontracted) UEB braille cells for text in the Nemeth within UEB contexts mode.
+    /// The Nemeth rules are the ones that are loaded, so the cells can't come from the UEB unicode files.
+    /// Chars other than letters, digits, and common punctuation are brailled with the Nemeth rules.
+    fn get_braille_ueb_text_chars(node: Element, text_range: Option<Range<usize>>) -> Result<String> {
+        static UEB_LETTERS: [char; 26] = ['⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊', '⠚', '⠅', '⠇', '⠍',
+                                         '⠝', '⠕', '⠏', '⠟', '⠗', '⠎', '⠞', '⠥', '⠧', '⠺', '⠭', '⠽', '⠵'];
+        static UEB_PUNCTUATION: phf::Map<char, &str> = phf_map! {
+            ',' => "⠂", '.' => "⠲", ';' => "⠆", ':' => "⠒", '?' => "⠦", '!' => "⠖", '\'' => "⠄", '’' => "⠄",
+            '-' => "⠤", '(' => "⠐⠣", ')' => "⠐⠜", '"' => "⠠⠶", '“' => "⠘⠦", '”' => "⠘⠴", '/' => "⠸⠌",
+        };
+
+        let text = BrailleChars::substring(as_text(node), &text_range);
+        let mut result = String::with_capacity(3 * text.len());
+        for (i_word, word) in text.split(' ').enumerate() {
+            if i_word > 0 {
+                result.push('⠀');
+            }
+            // a word with more than one letter that are all capitals uses the capitalized word indicator
+            let is_cap_word = word.chars().filter(|ch| ch.is_ascii_alphabetic()).count() > 1 &&
+                              word.chars().all(|ch| !ch.is_ascii_lowercase());
+            if is_cap_word {
+                result.push_str("⠠⠠");
+            }
+            let mut in_number = false;
+            let mut chars = word.chars().peekable();
+            while let Some(ch) = chars.next() {
+                if ch.is_ascii_digit() {
+                    if !in_number {
+                        result.push('⠼');
+                        in_number = true;
+                    }
+                    result.push(if ch == '0' {'⠚'} else {UEB_LETTERS[(ch as u8 - b'1') as usize]});
+                } else if in_number && (ch == '.' || ch == ',') && chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                    result.push(if ch == '.' {'⠲'} else {'⠂'});     // decimal point/digit separator stay in numeric mode
+                } else if ch.is_ascii_alphabetic() {
+                    let lower = ch.to_ascii_lowercase();
+                    if in_number && ('a'..='j').contains(&lower) {
+                        result.push('⠰');     // grade 1 indicator: otherwise it would be read as a digit
+                    }
+                    in_number = false;
+                    if ch.is_ascii_uppercase() && !is_cap_word {
+                        result.push('⠠');
+                    }
+                    result.push(UEB_LETTERS[(lower as u8 - b'a') as usize]);
+                } else {
+                    in_number = false;
+                    match UEB_PUNCTUATION.get(&ch) {
+                        Some(&braille) => result.push_str(braille),
+                        None => result.push_str(&braille_replace_chars(&ch.to_string(), node)?),
+                    }
+                }
+            }
+        }
+        return Ok(result);
+    }
+
     fn get_braille_cmu_chars(node: Element, text_range: Option<Range<usize>>) -> Result<String> {
         // In CMU, we need to replace spaces used for number blocks with "."
         // For other numbers, we need to add "." to create digit blocks
@@ -2749,8 +3350,7 @@ impl NeedsToBeGrouped {
         // if the fraction starts with a "-", it is still a numeric fraction that doesn't need parens
         let mut numerator = as_element(children[0]);
         let denominator = as_element(children[children.len()-1]);
-        let decimal_separator = crate::interface::get_preference("DecimalSeparators").unwrap()
-                                                        .chars().next().unwrap_or('.');
+        let decimal_separator = crate::canonicalize::get_separators().0.chars().next().unwrap_or('.');
         if is_integer(denominator, decimal_separator) {
             // check numerator being either an integer "- integer"
             if name(numerator) == "mrow" {
@@ -2917,6 +3517,33 @@ impl NeedsToBeGrouped {
         }
     }
 
+    /// Returns true if the element needs to be put in a block ("⠰...⠆") in the French code
+    /// 'is_base' is true for the base of a script or root (vs. the script or fraction part)
+    fn needs_grouping_for_french(mathml: Element, is_base: bool) -> bool {
+        match name(mathml) {
+            "mn" | "mo" => return false,
+            "mi" | "mtext" => {
+                let text = as_text(mathml);
+                // single chars and function names ("sin", "log", ...) are a single unit
+                return text.chars().nth(1).is_some() &&
+                       !crate::xpath_functions::IsInDefinition::is_defined_in(text, &SPEECH_DEFINITIONS, "FunctionNames").unwrap();
+            },
+            "mrow" => {
+                // bracketed exprs are already a unit
+                if IsBracketed::is_bracketed(mathml, "", "", false, true) {
+                    return false;
+                }
+                // a prefix operator applied to a leaf (e.g, "-2") stays together
+                let children = mathml.children();
+                return !(children.len() == 2 &&
+                         name(as_element(children[0])) == "mo" && is_leaf(as_element(children[1])));
+            },
+            "mfrac" | "mtable" => return true,
+            "msub" | "msup" | "msubsup" | "munder" | "mover" | "munderover" | "mmultiscripts" => return !is_base,
+            _ => return false,
+        }
+    }
+
     /// Returns true if the element needs grouping symbols
     /// Bases need extra attention because if they are a number and the item to the left is one, that needs distinguishing
     fn needs_grouping_for_ueb(mathml: Element, is_base: bool) -> bool {
@@ -3017,7 +3644,8 @@ impl Function for NeedsToBeGrouped {
                 "UEB" => NeedsToBeGrouped::needs_grouping_for_ueb(e, is_base),
                 "Finnish" => NeedsToBeGrouped::needs_grouping_for_finnish(e, is_base),
                 "Swedish" => NeedsToBeGrouped::needs_grouping_for_swedish(e, is_base),
-                _ => return Err(XPathError::Other(format!("NeedsToBeGrouped: braille code arg '{braille_code:?}' is not a known code ('UEB', 'CMU', or 'Swedish')"))),
+                "French" => NeedsToBeGrouped::needs_grouping_for_french(e, is_base),
+                _ => return Err(XPathError::Other(format!("NeedsToBeGrouped: braille code arg '{braille_code:?}' is not a known code ('UEB', 'CMU', 'Swedish', or 'French')"))),
             };
             return Ok( Value::Boolean( answer ) );
         }
@@ -3025,6 +3653,116 @@ impl Function for NeedsToBeGrouped {
         return Err(XPathError::Other(format!("NeedsToBeGrouped: first arg '{node:?}' is not a node")));
     }
 }
+
+/// Braille for the visual emphasis (color, background, enclosure) of a node that the user wants conveyed (see the `BrailleEmphasis` pref).
+/// Usage: BrailleEmphasis(node, 'start' | 'end') -- returns the braille to place before/after the node ("" if nothing needs to be conveyed)
+pub struct BrailleEmphasis;
+impl BrailleEmphasis {
+    // transcriber's note indicators (UEB 3.27) -- also used for Nemeth as transcriptions are in UEB contexts
+    const TN_OPEN: &'static str = "⠈⠨⠣";
+    const TN_CLOSE: &'static str = "⠈⠨⠜";
+
+    fn emphasis(node: Element, is_start: bool) -> String {
+        let pref_manager = PreferenceManager::get();
+        let pref_manager = pref_manager.borrow();
+        let convey = pref_manager.pref_to_string("BrailleEmphasis");
+        if convey == "None" {
+            return "".to_string();
+        }
+        let braille_code = pref_manager.pref_to_string("BrailleCode");
+        if braille_code != "Nemeth" && braille_code != "UEB" {
+            return "".to_string();
+        }
+        let conveys = |what: &str| convey == "All" || convey.split_whitespace().any(|word| word == what);
+
+        let mut descriptions = vec![];      // words for a transcriber's note
+        if conveys("Color") && let Some(color) = node.attribute_value("mathcolor") && !BrailleEmphasis::is_default_color(color, "black") {
+            descriptions.push(BrailleEmphasis::color_name(color));
+        }
+        if conveys("Background") && let Some(color) = node.attribute_value("mathbackground") && !BrailleEmphasis::is_default_color(color, "white") {
+            descriptions.push(format!("{} background", BrailleEmphasis::color_name(color)));
+        }
+        // typeforms can't describe a shape, so enclosures always get a transcriber's note
+        let is_enclosure = conveys("Enclosure") && name(node) == "menclose";
+        if is_enclosure {
+            let notation = node.attribute_value("notation").unwrap_or("longdiv");
+            descriptions.push(notation.split_whitespace().collect::<Vec<&str>>().join(" "));
+        }
+        if descriptions.is_empty() {
+            return "".to_string();
+        }
+
+        let is_single_symbol = is_leaf(node) && as_text(node).chars().count() == 1;
+        if !is_enclosure && pref_manager.pref_to_string("BrailleEmphasisStyle") == "Typeform" {
+            // use a transcriber-defined typeform (symbol indicator or word indicator + terminator)
+            let typeform = pref_manager.pref_to_string(&format!("{}_Emphasis", braille_code));
+            return match (is_start, is_single_symbol) {
+                (true, true) => typeform + "⠆",
+                (true, false) => typeform + "⠂",
+                (false, true) => "".to_string(),
+                (false, false) => typeform + "⠄",
+            };
+        }
+        if !is_start {
+            return if is_single_symbol {"".to_string()} else {BrailleEmphasis::transcribers_note("end")};
+        }
+        return BrailleEmphasis::transcribers_note(&descriptions.join(", "));
+    }
+
+    fn is_default_color(color: &str, default: &str) -> bool {
+        let color = color.trim().to_ascii_lowercase();
+        return color == default || color == "transparent" ||
+               (default == "black" && (color == "#000" || color == "#000000")) ||
+               (default == "white" && (color == "#fff" || color == "#ffffff"));
+    }
+
+    fn color_name(color: &str) -> String {
+        let color = color.trim().to_ascii_lowercase();
+        return if color.chars().all(|ch| ch.is_ascii_alphabetic()) {color} else {"colored".to_string()};
+    }
+
+    /// Transcriber's note with 'text' in uncontracted braille (text is ASCII letters, spaces, and commas)
+    fn transcribers_note(text: &str) -> String {
+        static LETTERS: &[char] = &['⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊', '⠚', '⠅', '⠇', '⠍',
+                                    '⠝', '⠕', '⠏', '⠟', '⠗', '⠎', '⠞', '⠥', '⠧', '⠺', '⠭', '⠽', '⠵'];
+        let mut result = String::with_capacity(3*(text.len() + 8));
+        result.push_str(BrailleEmphasis::TN_OPEN);
+        for ch in text.chars() {
+            match ch {
+                'a'..='z' => result.push(LETTERS[(ch as usize) - ('a' as usize)]),
+                ',' => result.push('⠂'),
+                _ => result.push('W'),
+            }
+        }
+        result.push_str(BrailleEmphasis::TN_CLOSE);
+        return result;
+    }
+}
+
+impl Function for BrailleEmphasis {
+    fn evaluate<'d>(&self,
+                        _context: &context::Evaluation<'_, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> StdResult<Value<'d>, XPathError>
+    {
+        let mut args = Args(args);
+        args.exactly(2)?;
+        let which = args.pop_string()?;
+        let node = validate_one_node(args.pop_nodeset()?, "BrailleEmphasis")?;
+        if let Node::Element(e) = node {
+            let is_start = match which.as_str() {
+                "start" => true,
+                "end" => false,
+                _ => return Err(XPathError::Other(format!("BrailleEmphasis: second arg '{which}' must be 'start' or 'end'"))),
+            };
+            let braille = BrailleEmphasis::emphasis(e, is_start);
+            // the braille is already translated -- quote it so it isn't translated again (but keep "" so the rules can test for it)
+            return Ok( Value::String( if braille.is_empty() {braille} else {make_quoted_string(braille)} ) );
+        }
+
+        return Err(XPathError::Other(format!("BrailleEmphasis: first arg '{node:?}' is not a node")));
+    }
+}
     
     
     
@@ -3063,6 +3801,72 @@ mod tests {
         return Ok( () );
     }
     
+    #[test]
+    fn source_map_leaf_offset() -> Result<()> {
+        let mathml_str = "<math id='id-0'><mrow id='id-1'>
+                <mi id='id-2'>x</mi><mo id='id-3'>=</mo><mn id='id-4'>123</mn>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("BrailleCode", "Nemeth").unwrap();
+        set_mathml(mathml_str).unwrap();
+        let (braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, "⠭⠀⠨⠅⠀⠼⠂⠆⠒");
+        // the number indicator is part of the number
+        assert_eq!(&source_map[5..], [("id-4".to_string(), 0), ("id-4".to_string(), 1), ("id-4".to_string(), 2), ("id-4".to_string(), 3)]);
+
+        // highlighting doesn't change the map
+        let (braille, highlighted_source_map) = get_braille_with_source_map("id-4")?;
+        assert_eq!(braille, "⠭⠀⠨⠅⠀⣼⠂⠆⣒");
+        assert_eq!(highlighted_source_map, source_map);
+        return Ok( () );
+    }
+
+    #[test]
+    fn source_map_multi_cell_operator() -> Result<()> {
+        let mathml_str = "<math id='id-0'><mrow id='id-1'>
+                <mi id='id-2'>x</mi><mo id='id-3'>≤</mo><mi id='id-4'>y</mi>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("BrailleCode", "Nemeth").unwrap();
+        set_mathml(mathml_str).unwrap();
+        let (braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, "⠭⠀⠐⠅⠱⠀⠽");
+        let expected = [("id-2", 0), ("id-3", 0), ("id-3", 0), ("id-3", 1), ("id-3", 2), ("id-3", 0), ("id-4", 0)];
+        assert_eq!(source_map, expected.map(|(id, offset)| (id.to_string(), offset)));
+        return Ok( () );
+    }
+
+    #[test]
+    fn source_map_too_long_for_table() {
+        // "x+x+...": the cleanup added an indicator at the start and removed an indicator in the middle
+        let n_terms = 2500;
+        let nodes = [
+            BrailleSource { id: "x".to_string(), is_leaf: true, is_operator: false },
+            BrailleSource { id: "+".to_string(), is_leaf: true, is_operator: true },
+        ];
+        let raw = "⠭⠬".repeat(n_terms) + "⠰⠭";
+        let mut raw_sources = [Some(0), Some(1)].repeat(n_terms);
+        raw_sources.extend([Some(0), Some(0)]);
+        let braille = "⠠".to_string() + &"⠭⠬".repeat(n_terms) + "⠭";
+        let cell_sources = align_braille_sources(&raw, &raw_sources, &braille, "UEB", &nodes);
+        assert_eq!(cell_sources.len(), 2*n_terms + 2);
+        assert_eq!(&cell_sources[..5], [None, Some(0), Some(1), Some(0), Some(1)]);
+        assert_eq!(&cell_sources[2*n_terms-1..], [Some(0), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn source_map_empty_math() -> Result<()> {
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("BrailleCode", "Nemeth").unwrap();
+        // canonicalization adds an 'mtext' for the missing content -- the cell for it is the only cell
+        let canonical_mathml = set_mathml("<math></math>").unwrap();
+        let (braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, "⠿");
+        let mtext_id = canonical_mathml.split(" id='").nth(2).unwrap().split('\'').next().unwrap();
+        assert_eq!(source_map, [(mtext_id.to_string(), 0)]);
+        return Ok( () );
+    }
+
     #[test]
     // This test probably should be repeated for each braille code and be taken out of here
     fn find_mathml_from_braille() -> Result<()> { 
@@ -3120,6 +3924,9 @@ mod tests {
             debug!("Time taken: {}ms", instant.elapsed().as_millis());
             assert_eq!(answers[i], id, "\nNemeth test ith position={}", i);
         }
+        let (source_braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, source_braille);
+        assert_eq!(answers.to_vec(), source_map.into_iter().map(|(id, _)| id).collect::<Vec<String>>(), "\nNemeth source map");
 
         set_preference("BrailleCode", "UEB").unwrap();
         let braille = get_braille("")?;
@@ -3134,6 +3941,9 @@ mod tests {
             debug!("Time taken: {}ms", instant.elapsed().as_millis());
             assert_eq!(answers[i], id, "\nUEB test ith position={}", i);
         }
+        let (source_braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, source_braille);
+        assert_eq!(answers.to_vec(), source_map.into_iter().map(|(id, _)| id).collect::<Vec<String>>(), "\nUEB source map");
         set_preference("BrailleCode", "CMU").unwrap();
         let braille = get_braille("")?;
         let answers= &[2, 3, 5, 7, 8, 9, 9, 9, 10, 10,   11, 13, 12, 14, 14, 15, 17, 17, 19, 19,   21, 11, 5, 4, 22, 23, 23, 25, 25, 22,];
@@ -3148,6 +3958,12 @@ mod tests {
             debug!("Time taken: {}ms", instant.elapsed().as_millis());
             assert_eq!(answers[i], id, "\nCMU test ith position={}", i);
         }
+        // the source map gives the grouping cells for the numerator, denominator, and root to the mfrac/msqrt whose rule adds them
+        let (source_braille, source_map) = get_braille_with_source_map("")?;
+        assert_eq!(braille, source_braille);
+        let answers= &[2, 3, 4, 7, 8, 9, 9, 9, 10, 10,   10, 13, 12, 14, 14, 15, 17, 17, 19, 19,   21, 10, 4, 4, 4, 23, 23, 25, 25, 4,];
+        let answers = answers.map(|num| format!("id-{}", num));
+        assert_eq!(answers.to_vec(), source_map.into_iter().map(|(id, _)| id).collect::<Vec<String>>(), "\nCMU source map");
         return Ok( () );
     }
     
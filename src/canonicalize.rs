@@ -9,6 +9,7 @@
 use crate::errors::*;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use sxd_document::dom::*;
 use sxd_document::QName;
 use phf::{phf_map, phf_set};
@@ -44,6 +45,115 @@ const MHCHEM_MMULTISCRIPTS_HACK: &str = "MHCHEM_SCRIPT_HACK";
 // (perfect) hash of operators built from MathML's operator dictionary
 static OPERATORS: phf::Map<&str, OperatorInfo> = include!("operator-info.in");
 
+// Operators that are referenced directly when parsing (e.g, the invisible operators) -- these can't be overridden by "OperatorDictionary"
+static NON_OVERRIDABLE_OPERATORS: phf::Set<&str> = phf_set! {
+	"\u{2061}", "\u{2062}", "\u{2063}", "\u{2064}", "+", "-", "×", "=", "∷",
+};
+
+/// Operators added to (or overriding) OPERATORS from the "OperatorDictionary" definition in definitions.yaml.
+/// The definition the operators were built from is kept so that they are only rebuilt when the definition changes.
+#[derive(Default)]
+struct OperatorExtensions {
+	definition: HashMap<String, String>,
+	operators: HashMap<String, &'static OperatorInfo>,
+}
+
+thread_local!{
+	static OPERATOR_EXTENSIONS: RefCell<OperatorExtensions> = RefCell::new( OperatorExtensions::default() );
+
+	/// The OperatorInfos built from "OperatorDictionary" entries, keyed by their (form, priority) alternatives.
+	/// They need to be 'static like the entries in OPERATORS, so each distinct entry is allocated once and reused
+	///   when a definition is reloaded or changed back (rather than allocating new ones each time the definition changes).
+	static OPERATOR_INFO_ARENA: RefCell<HashMap<Vec<(u32, usize)>, &'static OperatorInfo>> = RefCell::new( HashMap::new() );
+}
+
+/// Look up 'text' in the operator dictionary -- entries from the "OperatorDictionary" definition take precedence over OPERATORS
+fn get_operator_info(text: &str) -> Option<&'static OperatorInfo> {
+	if let Some(op) = OPERATOR_EXTENSIONS.with(|extensions| extensions.borrow().operators.get(text).copied()) {
+		return Some(op);
+	}
+	return OPERATORS.get(text);
+}
+
+/// Rebuild the operator dictionary extensions if the "OperatorDictionary" definition has changed
+fn update_operator_extensions() -> Result<()> {
+	let definition = crate::definitions::SPEECH_DEFINITIONS.with(|definitions| {
+		// an empty definition is read in as a set, not a map
+		return definitions.borrow().get_hashmap("OperatorDictionary").map(|map| map.clone()).unwrap_or_default();
+	});
+	return OPERATOR_EXTENSIONS.with(|extensions| {
+		let mut extensions = extensions.borrow_mut();
+		if extensions.definition == definition {
+			return Ok( () );
+		}
+		let mut operators = HashMap::with_capacity(definition.len());
+		for (op, value) in &definition {
+			if NON_OVERRIDABLE_OPERATORS.contains(op) {
+				bail!("'{}' can't be overridden in 'OperatorDictionary'", op);
+			}
+			let op_info = parse_operator_dictionary_entry(value)
+					.with_context(|| format!("in 'OperatorDictionary' entry for '{op}'"))?;
+			operators.insert(op.clone(), op_info);
+		}
+		*extensions = OperatorExtensions{ definition, operators };
+		return Ok( () );
+	});
+}
+
+/// Parse an "OperatorDictionary" value such as "infix 270" or "prefix fence 20 || infix 260".
+/// Each alternative has a form ("prefix", "infix", or "postfix"), a priority, and optionally "fence".
+/// "stretchy" is an error: stretchiness doesn't change how an mrow is parsed, so it isn't recorded (OPERATORS doesn't have it either).
+/// As with OPERATORS, the first alternative is used when the position doesn't match any of the forms.
+/// The OperatorInfo comes from OPERATOR_INFO_ARENA so that it is 'static like the entries in OPERATORS.
+fn parse_operator_dictionary_entry(value: &str) -> Result<&'static OperatorInfo> {
+	let mut alternatives: Vec<(OperatorTypes, usize)> = Vec::with_capacity(3);
+	for alternative in value.split("||") {
+		let mut op_type = OperatorTypes::NONE;
+		let mut priority = None;
+		let mut is_fence = false;
+		for word in alternative.split_whitespace() {
+			match word {
+				"prefix" if op_type == OperatorTypes::NONE => op_type = OperatorTypes::PREFIX,
+				"infix" if op_type == OperatorTypes::NONE => op_type = OperatorTypes::INFIX,
+				"postfix" if op_type == OperatorTypes::NONE => op_type = OperatorTypes::POSTFIX,
+				"fence" => is_fence = true,
+				"stretchy" => bail!("'stretchy' in '{}' isn't supported -- it doesn't change how the mrow is parsed", alternative.trim()),
+				_ => match word.parse::<usize>() {
+					Ok(n) if priority.is_none() && n < ILLEGAL_OPERATOR_INFO.priority => priority = Some(n),
+					_ => bail!("unexpected '{}' in '{}'", word, alternative.trim()),
+				},
+			}
+		}
+		if op_type == OperatorTypes::NONE {
+			bail!("'{}' is missing the form ('prefix', 'infix', or 'postfix')", alternative.trim());
+		}
+		let Some(priority) = priority else {
+			bail!("'{}' is missing the priority", alternative.trim());
+		};
+		if is_fence {
+			if op_type == OperatorTypes::INFIX {
+				bail!("'{}': only prefix and postfix operators can be fences", alternative.trim());
+			}
+			op_type |= OperatorTypes::FENCE;
+		}
+		if alternatives.iter().any(|(alt_op_type, _)| alt_op_type.difference(OperatorTypes::FENCE) == op_type.difference(OperatorTypes::FENCE)) {
+			bail!("the form in '{}' is given more than once", alternative.trim());
+		}
+		alternatives.push( (op_type, priority) );
+	}
+
+	let key: Vec<(u32, usize)> = alternatives.iter().map(|(op_type, priority)| (op_type.bits(), *priority)).collect();
+	return Ok( OPERATOR_INFO_ARENA.with(|arena| {
+		return *arena.borrow_mut().entry(key).or_insert_with(|| {
+			let mut next: &'static Option<OperatorInfo> = &None;
+			for (op_type, priority) in alternatives.into_iter().rev() {
+				next = Box::leak( Box::new( Some(OperatorInfo{ op_type, priority, next }) ) );
+			}
+			return next.as_ref().unwrap();
+		});
+	}) );
+}
+
 
 // The set of fence operators that can being either a left or right fence (or infix). For example: "|".
 static AMBIGUOUS_OPERATORS: phf::Set<&str> = phf_set! {
@@ -421,10 +531,195 @@ pub fn get_presentation_element(element: Element) -> (usize, Element) {
 ///   This preserves the data-width attr (with new name) added in the second attempt that helps resolve whether something is tweaking, a real space, or an omission.
 ///   It adds data-previous-space-width/data-following-space-width with values to indicate with the space was on the left or right (typically it placed on the previous token because that's easier)
 pub fn canonicalize(mathml: Element) -> Result<Element> {
+	let is_reporting_repairs = crate::prefs::PreferenceManager::get().borrow().pref_to_string("CanonicalizationReport") == "true";
+	REPAIRS.with(|repairs| repairs.replace( if is_reporting_repairs {Some(vec![])} else {None} ));
+	set_separators_for_expression(mathml);
 	let context = CanonicalizeContext::new();
 	return context.canonicalize(mathml);
 }
 
+/// A repair made to the MathML when it was canonicalized (see [`crate::interface::get_canonicalization_report`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalizationRepair {
+	/// The kind of repair (see [`crate::interface::get_canonicalization_report`] for the values)
+	pub kind: String,
+	/// The MathML before the repair (for "merged-mi" and "merged-number", all of the elements that were merged)
+	pub original: String,
+	/// The `id` of the repaired element in the canonical MathML (empty if a later cleanup removed the element)
+	pub id: String,
+	/// The MathML of the repaired element in the canonical MathML (empty if a later cleanup removed the element)
+	pub result: String,
+}
+
+/// Attribute (space separated indices into REPAIRS) added to an element that is the result of a reported repair.
+/// It is removed by [`finish_repair_report`] after the ids are added.
+/// The name is private to MathCAT, but the input could still have it, so values that aren't a valid index are ignored.
+const REPAIR_ATTR: &str = "data-mathcat-repair";
+
+thread_local!{
+	/// The repairs made by the last call to [`canonicalize`] -- None if the "CanonicalizationReport" pref isn't true
+	static REPAIRS: RefCell<Option<Vec<CanonicalizationRepair>>> = const { RefCell::new(None) };
+}
+
+/// Returns the MathML string for 'elements' if repairs are being reported -- call this before making the repair
+fn original_for_report(elements: &[Element]) -> Option<String> {
+	return REPAIRS.with(|repairs| {
+		if repairs.borrow().is_none() {
+			return None;
+		}
+		return Some( elements.iter().map(|&element| mml_to_string(element)).collect::<String>() );
+	});
+}
+
+/// Record that a repair of 'kind' turned 'original' (from [`original_for_report`]) into 'result'
+fn record_repair(kind: &str, original: Option<String>, result: Element) {
+	let Some(original) = original else {
+		return;
+	};
+	REPAIRS.with(|repairs| {
+		if let Some(repairs) = repairs.borrow_mut().as_mut() {
+			let index = repairs.len().to_string();
+			let marker = match result.attribute_value(REPAIR_ATTR) {
+				Some(value) => format!("{value} {index}"),
+				None => index,
+			};
+			result.set_attribute_value(REPAIR_ATTR, &marker);
+			repairs.push( CanonicalizationRepair {
+				kind: kind.to_string(),
+				original: original.trim().to_string(),
+				id: String::new(),
+				result: String::new(),
+			});
+		}
+	});
+}
+
+/// Fill in the id and resulting MathML of the repairs made by the last call to [`canonicalize`] and remove the markers.
+/// This should be called after the ids are added.
+pub fn finish_repair_report(mathml: Element) {
+	REPAIRS.with(|repairs| {
+		let mut repairs = repairs.borrow_mut();
+		if let Some(repairs) = repairs.as_mut() {
+			// remove all the markers before getting the MathML for the results so they don't show up there
+			let mut repaired = vec![];
+			remove_markers(mathml, &mut repaired);
+			for (index, element) in repaired {
+				let Some(repair) = repairs.get_mut(index) else {
+					continue;
+				};
+				if repair.id.is_empty() {
+					repair.id = element.attribute_value("id").unwrap_or_default().to_string();
+					repair.result = mml_to_string(element).trim().to_string();
+				}
+			}
+		}
+	});
+
+	fn remove_markers<'a>(mathml: Element<'a>, repaired: &mut Vec<(usize, Element<'a>)>) {
+		if let Some(markers) = mathml.attribute_value(REPAIR_ATTR) {
+			repaired.extend( markers.split(' ').filter_map(|index| index.parse::<usize>().ok()).map(|index| (index, mathml)) );
+			mathml.remove_attribute(REPAIR_ATTR);
+		}
+		if !is_leaf(mathml) {
+			for child in mathml.children() {
+				remove_markers(as_element(child), repaired);
+			}
+		}
+	}
+}
+
+/// Returns the repairs made by the last call to [`canonicalize`] (empty if the "CanonicalizationReport" pref isn't true)
+pub fn get_repair_report() -> Vec<CanonicalizationRepair> {
+	return REPAIRS.with(|repairs| repairs.borrow().clone().unwrap_or_default());
+}
+
+/// Canonicalize 'mathml' and return the repairs that were made, independent of the "CanonicalizationReport" pref.
+/// The reports (repairs and separators) from the last call to [`canonicalize`] are left untouched.
+/// Note: 'id's are not added, so the repair's 'id' is only set if the repaired element already had one.
+pub fn canonicalize_with_repairs(mathml: Element) -> Result<(Element, Vec<CanonicalizationRepair>)> {
+	let saved_repairs = REPAIRS.with(|repairs| repairs.replace(Some(vec![])));
+	let saved_separator_decision = get_separator_decision();
+	set_separators_for_expression(mathml);
+	let result = CanonicalizeContext::new().canonicalize(mathml);
+	if let Ok(result) = result {
+		finish_repair_report(result);
+	}
+	let repairs = REPAIRS.with(|repairs| repairs.replace(saved_repairs)).unwrap_or_default();
+	SEPARATOR_DECISION.with(|decision| decision.replace(saved_separator_decision));
+	return Ok( (result?, repairs) );
+}
+
+/// Returns true if 'text' is a known function name (e.g., "sin") or word (the same test used when merging 'mi's)
+pub fn is_known_function_name_or_word(text: &str) -> bool {
+	return crate::definitions::SPEECH_DEFINITIONS.with(|definitions| {
+		let definitions = definitions.borrow();
+		if let Some(function_names) = definitions.get_hashset("FunctionNames") {
+			if let Some(ascii_text) = CanonicalizeContext::math_alphanumeric_to_ascii(text)
+				&& function_names.contains(&ascii_text.to_lowercase()) {
+					return true;
+				}
+			if function_names.contains(text) {
+				return true;
+			}
+		}
+		return definitions.get_hashset("KnownWords").is_some_and(|word_map| word_map.contains(text));
+	});
+}
+
+/// Returns true if 'text' is in the operator dictionary
+pub fn is_operator_text(text: &str) -> bool {
+	return get_operator_info(text).is_some();
+}
+
+/// Returns true if 'text' is an operator like "|" that could be a left fence, right fence, or infix operator
+pub fn is_ambiguous_fence_operator(text: &str) -> bool {
+	return AMBIGUOUS_OPERATORS.contains(text);
+}
+
+// The heuristics that can be turned off with the "DisabledHeuristics" pref
+bitflags! {
+	#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+	struct Heuristics: u32 {
+		const ROMAN_NUMERALS	= 0x1;
+		const CHEMISTRY			= 0x2;
+		const SPLIT_POINTS		= 0x4;
+		const IMPLIED_COMMA		= 0x8;
+	}
+}
+
+/// The names of the heuristics that can be turned off with the "DisabledHeuristics" pref:
+/// * "roman-numerals" -- treating 'mi's and 'mtext's such as "IV" as Roman numerals (in a number context)
+/// * "chemistry" -- recognizing chemical formulas and equations (unless there is a chemistry 'intent')
+/// * "split-points" -- splitting an 'mi' such as "ABC" into points (e.g., in "∠ABC")
+/// * "implied-comma" -- adding an invisible comma between numbers in a script (e.g, the "1" and "2" in "a_{12}")
+const HEURISTIC_NAMES: [(&str, Heuristics); 4] = [
+	("roman-numerals", Heuristics::ROMAN_NUMERALS), ("chemistry", Heuristics::CHEMISTRY),
+	("split-points", Heuristics::SPLIT_POINTS), ("implied-comma", Heuristics::IMPLIED_COMMA),
+];
+
+/// Returns the heuristics named in 'names' (comma or space separated) or an error if any of them is not a known heuristic
+fn parse_heuristic_names(names: &str) -> Result<Heuristics> {
+	let mut heuristics = Heuristics::empty();
+	for name in names.split([',', ' ']).filter(|name| !name.is_empty()) {
+		match HEURISTIC_NAMES.iter().find(|(heuristic_name, _)| *heuristic_name == name) {
+			Some((_, heuristic)) => heuristics |= *heuristic,
+			None => bail!("'{}' is not a known heuristic. The known heuristics are: {}",
+						name, HEURISTIC_NAMES.map(|(heuristic_name, _)| heuristic_name).join(", ")),
+		}
+	}
+	return Ok( heuristics );
+}
+
+/// Returns an error if any of the (comma or space separated) 'names' is not a known heuristic
+pub fn check_heuristic_names(names: &str) -> Result<()> {
+	return parse_heuristic_names(names).map(|_| ());
+}
+
+/// Returns true if 'name' is the name of a heuristic that can be disabled
+pub fn is_heuristic_name(name: &str) -> bool {
+	return HEURISTIC_NAMES.iter().any(|(heuristic_name, _)| *heuristic_name == name);
+}
+
 #[derive(Debug, PartialEq)]
 enum FunctionNameCertainty {
 	True,
@@ -508,10 +803,7 @@ thread_local!{
 
 impl CanonicalizeContextPatternsCache {
 	fn new() -> CanonicalizeContextPatternsCache {
-		let pref_manager = crate::prefs::PreferenceManager::get();
-		let pref_manager = pref_manager.borrow();
-		let block_separator_pref = pref_manager.pref_to_string("BlockSeparators");
-		let decimal_separator_pref = pref_manager.pref_to_string("DecimalSeparators");
+		let (decimal_separator_pref, block_separator_pref) = get_separators();
 		return CanonicalizeContextPatternsCache {
 			patterns: Rc::new( CanonicalizeContextPatterns::new(&block_separator_pref, &decimal_separator_pref) ),
 			block_separator_pref,
@@ -521,10 +813,7 @@ impl CanonicalizeContextPatternsCache {
 
 	fn get() -> Rc<CanonicalizeContextPatterns> {
 		return PATTERN_CACHE.with( |cache| {
-			let pref_manager_rc = crate::prefs::PreferenceManager::get();
-			let pref_manager = pref_manager_rc.borrow();
-			let block_separator_pref = pref_manager.pref_to_string("BlockSeparators");
-			let decimal_separator_pref = pref_manager.pref_to_string("DecimalSeparators");
+			let (decimal_separator_pref, block_separator_pref) = get_separators();
 
 			let mut cache = cache.borrow_mut();
 			if block_separator_pref != cache.block_separator_pref || decimal_separator_pref != cache.decimal_separator_pref {
@@ -538,23 +827,146 @@ impl CanonicalizeContextPatternsCache {
 	}
 }
 
+/// The decimal and block separators used to clean up the numbers in the MathML that was set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeparatorDecision {
+	/// The decimal separators that were used, in the form of the `DecimalSeparators` preference (e.g., ".")
+	pub decimal_separators: String,
+	/// The block separators that were used, in the form of the `BlockSeparators` preference (e.g., ", \u{00A0}\u{202F}")
+	pub block_separators: String,
+	/// How the separators were chosen:
+	/// * "expression" -- `DecimalSeparator` is "Detect" and the numbers in the expression determined the separators
+	/// * "language" -- `DecimalSeparator` is "Detect", but the numbers didn't say, so the separators for the `Language` were used
+	/// * "preference" -- `DecimalSeparator` isn't "Detect", so the `DecimalSeparators` and `BlockSeparators` preferences were used
+	pub source: String,
+}
+
+thread_local!{
+	/// The separators used by the last call to [`canonicalize`] and how they were chosen
+	static SEPARATOR_DECISION: RefCell<Option<SeparatorDecision>> = const { RefCell::new(None) };
+}
+
+/// Record the separators to use for 'mathml' (see [`get_separators`]).
+/// If the "DecimalSeparator" pref is "Detect", they are based on the numbers in 'mathml'; otherwise they are the prefs' values.
+/// The "DecimalSeparators" and "BlockSeparators" prefs are not changed.
+fn set_separators_for_expression(mathml: Element) {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	let pref_manager = pref_manager.borrow();
+	let mut separators = (pref_manager.pref_to_string("DecimalSeparators"), pref_manager.pref_to_string("BlockSeparators"));
+	let source = if pref_manager.pref_to_string("DecimalSeparator") == "Detect" {
+		match detect_decimal_separator(mathml) {
+			Some(use_period) => {
+				separators = pref_manager.detected_separators(use_period);
+				"expression"
+			},
+			None => "language",		// when detecting, the prefs are set from the language
+		}
+	} else {
+		"preference"
+	};
+	SEPARATOR_DECISION.with(|decision| decision.replace( Some( SeparatorDecision {
+		decimal_separators: separators.0,
+		block_separators: separators.1,
+		source: source.to_string(),
+	})));
+}
+
+/// Returns the (decimal separators, block separators) to use for the math being canonicalized or that was set:
+///   the ones recorded for it if the "DecimalSeparator" pref is "Detect", otherwise the "DecimalSeparators" and "BlockSeparators" prefs.
+pub fn get_separators() -> (String, String) {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	let pref_manager = pref_manager.borrow();
+	if pref_manager.pref_to_string("DecimalSeparator") == "Detect" &&
+	   let Some(decision) = get_separator_decision() {
+		return (decision.decimal_separators, decision.block_separators);
+	}
+	return (pref_manager.pref_to_string("DecimalSeparators"), pref_manager.pref_to_string("BlockSeparators"));
+}
+
+/// Returns the separators used by the last call to [`canonicalize`] (None if it hasn't been called)
+pub fn get_separator_decision() -> Option<SeparatorDecision> {
+	return SEPARATOR_DECISION.with(|decision| decision.borrow().clone());
+}
+
+/// Returns Some(true) if more numbers ('mn's) in 'mathml' are only numbers when "." is the decimal separator
+///   than are only numbers when "," is the decimal separator, Some(false) if the reverse is true, and None if there is a tie.
+/// For example, "3,14" and "1.234,5" are only numbers with ",", but "1,234" is a number with either one.
+/// Numbers split across several elements (e.g., "3" "," "14") are ignored because they might be lists.
+fn detect_decimal_separator(mathml: Element) -> Option<bool> {
+	static PERIOD_PATTERNS: LazyLock<CanonicalizeContextPatterns> =
+			LazyLock::new(|| CanonicalizeContextPatterns::new(", \u{00A0}\u{202F}", "."));
+	static COMMA_PATTERNS: LazyLock<CanonicalizeContextPatterns> =
+			LazyLock::new(|| CanonicalizeContextPatterns::new(". \u{00A0}\u{202F}", ","));
+
+	let mut n_period = 0;
+	let mut n_comma = 0;
+	count_numbers(mathml, &mut n_period, &mut n_comma);
+	return match n_period.cmp(&n_comma) {
+		std::cmp::Ordering::Greater => Some(true),
+		std::cmp::Ordering::Less => Some(false),
+		std::cmp::Ordering::Equal => None,
+	};
+
+	fn count_numbers(mathml: Element, n_period: &mut usize, n_comma: &mut usize) {
+		if name(mathml) == "mn" {
+			let text = as_text(mathml).trim();
+			if text.contains(['.', ',']) {
+				let (mut is_period_number, mut is_comma_number) = (is_number(&PERIOD_PATTERNS, text), is_number(&COMMA_PATTERNS, text));
+				if is_period_number && is_comma_number && text.contains('.') && text.contains(',') {
+					// the patterns allow blocks after the decimal separator (e.g., "1,234.5" could be 1.2345),
+					//   but when both are used, the last one is almost certainly the decimal separator (if it is only used once)
+					let last_separator = text.chars().rfind(|&ch| ch == '.' || ch == ',').unwrap();
+					let is_used_once = text.matches(last_separator).count() == 1;
+					is_period_number = is_used_once && last_separator == '.';
+					is_comma_number = is_used_once && last_separator == ',';
+				}
+				if is_period_number && !is_comma_number {
+					*n_period += 1;
+				} else if is_comma_number && !is_period_number {
+					*n_comma += 1;
+				}
+			}
+		} else if !is_leaf(mathml) {
+			for child in mathml.children() {
+				count_numbers(as_element(child), n_period, n_comma);
+			}
+		}
+	}
+
+	fn is_number(patterns: &CanonicalizeContextPatterns, text: &str) -> bool {
+		return patterns.digit_only_decimal_number.is_match(text) ||
+			   patterns.block_3digit_pattern.is_match(text) ||
+			   patterns.block_3_5digit_pattern.is_match(text);
+	}
+}
+
 struct CanonicalizeContext {
 	patterns: Rc<CanonicalizeContextPatterns>,
+	disabled_heuristics: Heuristics,		// from the "DisabledHeuristics" pref
 }
 
 
 impl CanonicalizeContext {
 	fn new() -> CanonicalizeContext {
+		// the names are checked when the pref is set or read from a prefs file, so an error can't happen here
+		let disabled_heuristics = crate::prefs::PreferenceManager::get().borrow().pref_to_string("DisabledHeuristics");
 		return CanonicalizeContext {
 			patterns: CanonicalizeContextPatternsCache::get(),
+			disabled_heuristics: parse_heuristic_names(&disabled_heuristics).unwrap_or(Heuristics::empty()),
 		};
 	}
 
+	fn is_heuristic_disabled(&self, heuristic: Heuristics) -> bool {
+		return self.disabled_heuristics.contains(heuristic);
+	}
+
 	fn canonicalize<'a>(&self, mut mathml: Element<'a>) -> Result<Element<'a>> {
 		// debug!("MathML before canonicalize:\n{}", mml_to_string(mathml));
+		update_operator_extensions()?;
 	
 		if name(mathml) != "math" {
 			// debug!("Didn't start with <math> element -- attempting repair");
+			let original = original_for_report(&[mathml]);
 			let math_element = create_mathml_element(&mathml.document(), "math");
 			math_element.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
 			math_element.append_child(mathml);
@@ -562,19 +974,26 @@ impl CanonicalizeContext {
 			root.clear_children();
 			root.append_child(math_element);
 			mathml = root.children()[0].element().unwrap();
+			record_repair("missing-math", original, mathml);
 		}
+		crate::content_mathml::convert_content_mathml(mathml)?;
 		CanonicalizeContext::assure_mathml(mathml)?;
+		let original_for_chemistry = original_for_report(&[mathml]);
 		let mathml = self.clean_mathml(mathml).unwrap();	// 'math' is never removed
 		self.assure_nary_tag_has_one_child(mathml);
 		// debug!("Not chemistry -- retry:\n{}", mml_to_string(mathml));
 		let mut converted_mathml = self.canonicalize_mrows(mathml)
 				.with_context(|| format!("while processing\n{}", mml_to_string(mathml)))?;
-		if !crate::chemistry::scan_and_mark_chemistry(converted_mathml) {
+		if !crate::chemistry::scan_and_mark_chemistry(converted_mathml, self.is_heuristic_disabled(Heuristics::CHEMISTRY)) {
 			// debug!("canonicalize before canonicalize_mrows:\n{}", mml_to_string(converted_mathml));
 			self.assure_nary_tag_has_one_child(converted_mathml);
 			converted_mathml = self.canonicalize_mrows(mathml)
 				.with_context(|| format!("while processing\n{}", mml_to_string(mathml)))?;
 		}
+		let child = as_element(converted_mathml.children()[0]);
+		if is_guessed_chemistry(child) {
+			record_repair("chemistry", original_for_chemistry, child);
+		}
 		debug!("\nMathML after canonicalize:\n{}", mml_to_string(converted_mathml));
 		return Ok(converted_mathml);
 	}
@@ -789,6 +1208,7 @@ impl CanonicalizeContext {
 
 		match element_name {
 			"mn" => {
+				let original = original_for_report(&[mathml]);
 				let text = as_text(mathml);
 				let mut chars = text.chars();
 				let first_char = chars.next().unwrap();		// we have already made sure it is non-empty
@@ -818,6 +1238,9 @@ impl CanonicalizeContext {
 						mathml.replace_children([mn, mo]);
 					}
 				}
+				if name(mathml) != "mn" {
+					record_repair("split-number", original, mathml);
+				}
 				return Some(mathml);
 			},
 			"ms" | "mglyph" => {
@@ -825,12 +1248,13 @@ impl CanonicalizeContext {
 			},
 			"mi" => {
 				let text = as_text(mathml);
-				if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) {
+				if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) &&
+				   !self.is_heuristic_disabled(Heuristics::ROMAN_NUMERALS) {
 					// people tend to set them in a non-italic font and software makes that 'mtext'
 					CanonicalizeContext::make_roman_numeral(mathml);
 					return Some(mathml);
 			 	}
-				if let Some(dash) = canonicalize_dash(text) {		// needs to be before OPERATORS.get due to "--"
+				if let Some(dash) = canonicalize_dash(text) {		// needs to be before get_operator_info due to "--"
 					mathml.set_text(dash);
 					return Some(mathml);
 				} else if text.contains('_') {
@@ -845,7 +1269,7 @@ impl CanonicalizeContext {
 						set_mathml_name(mathml, "mo");
 					}
 					return Some(mathml);
-				} else if OPERATORS.get(text).is_some() {
+				} else if get_operator_info(text).is_some() {
 					if  let Some(intent_value) = mathml.attribute_value(INTENT_ATTR) {
 						// if it is a unit, it might be seconds, minutes, feet, ... not an operator
 						if intent_value.contains(":unit") {
@@ -855,7 +1279,7 @@ impl CanonicalizeContext {
 					set_mathml_name(mathml, "mo");
 
 					// For at least pandoc, ∇ is an 'mi' and it sometimes adds an invisible times -- remove them
-					let op = OPERATORS.get(text).unwrap();
+					let op = get_operator_info(text).unwrap();
 					let preceding_siblings = mathml.preceding_siblings();
 					if (op.is_infix() || op.is_postfix()) &&
 					   !preceding_siblings.is_empty() && CanonicalizeContext::is_invisible_char_element(as_element(preceding_siblings[0])) {
@@ -878,7 +1302,7 @@ impl CanonicalizeContext {
 				} else if text == "..." {
 					mathml.set_text("…");
 					return Some(mathml);
-				} else if let Some(result) = split_points(mathml) {
+				} else if !self.is_heuristic_disabled(Heuristics::SPLIT_POINTS) && let Some(result) = split_points(mathml) {
 					return Some(result);
 				} else if let Some(result) = merge_mi_sequence(mathml) {
 					return Some(result);
@@ -891,12 +1315,13 @@ impl CanonicalizeContext {
 
 				if let Some(result) = merge_arc_trig(mathml) {
 					return Some(result);
-				} else if let Some(result) = split_points(mathml) {
+				} else if !self.is_heuristic_disabled(Heuristics::SPLIT_POINTS) && let Some(result) = split_points(mathml) {
 					return Some(result);
 				}
 
 				let text = as_text(mathml);
-				if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) {
+				if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) &&
+				   !self.is_heuristic_disabled(Heuristics::ROMAN_NUMERALS) {
 					// people tend to set them in a non-italic font and software makes that 'mtext'
 					CanonicalizeContext::make_roman_numeral(mathml);
 					return Some(mathml);
@@ -922,7 +1347,7 @@ impl CanonicalizeContext {
 					return Some(mathml);
 				} else if let Some(dash) = canonicalize_dash(text) {
 					mathml.set_text(dash);
-				} else if OPERATORS.get(text).is_some() {
+				} else if get_operator_info(text).is_some() {
 					set_mathml_name(mathml, "mo");
 					return Some(mathml);
 				}
@@ -984,7 +1409,12 @@ impl CanonicalizeContext {
 				});
 				// note: chemistry test is done later as part of another phase of chemistry cleanup
 			},
-			"mfenced" => {return self.clean_mathml( convert_mfenced_to_mrow(mathml) )},
+			"mfenced" => {
+				let original = original_for_report(&[mathml]);
+				let mrow = convert_mfenced_to_mrow(mathml);
+				record_repair("mfenced", original, mrow);
+				return self.clean_mathml(mrow);
+			},
 			"a" => {
 				// convert 'a' into 'mrow'
 				set_mathml_name(mathml, "mrow");
@@ -1054,6 +1484,8 @@ impl CanonicalizeContext {
 			},
 			_  => {
 				let children = mathml.children();
+				// the children get cleaned below -- the report should show what the author wrote
+				let original_msubsup = if element_name == "msubsup" {original_for_report(&[mathml])} else {None};
 				if element_name == "mrow" {
 					// handle special cases of empty mrows and mrows which just one element
 					if children.is_empty() && mathml.attribute(INTENT_ATTR).is_none() {
@@ -1110,7 +1542,7 @@ impl CanonicalizeContext {
 									// can't do this above in 'match' because this changes the tree and
 									// lifting single element mrows messes with structure in a conflicting way
 									// Note: if clean_chemistry_leaf() made changes, they don't need cleaning because they will be "ok" mi's
-									clean_chemistry_leaf(as_element(mathml.children()[i]));
+									clean_chemistry_leaf(as_element(mathml.children()[i]), self.is_heuristic_disabled(Heuristics::CHEMISTRY));
 								} else {
 									// If the attach call does something, children are inserted *before* child (i.e., into parent)
 									// We return the new start at the expense of re-cleaning the script
@@ -1169,7 +1601,7 @@ impl CanonicalizeContext {
 						}
 					}
 					let mathml = if element_name == "mmultiscripts" {clean_mmultiscripts(mathml).unwrap()} else {mathml};
-					if !is_chemistry_off(mathml) {
+					if !is_chemistry_off(mathml, self.is_heuristic_disabled(Heuristics::CHEMISTRY)) {
 						let likely_chemistry = likely_adorned_chem_formula(mathml);
 						// debug!("likely_chemistry={}, {}", likely_chemistry, mml_to_string(mathml));
 						if likely_chemistry >= 0 {
@@ -1178,7 +1610,11 @@ impl CanonicalizeContext {
 					}
 
 					if element_name == "msubsup" {
-						return Some( clean_msubsup(mathml) );
+						let result = clean_msubsup(mathml);
+						if name(result) != "msubsup" {
+							record_repair("msubsup", original_msubsup, result);
+						}
+						return Some(result);
 					} else {
 						return Some(mathml);
 					}
@@ -1188,7 +1624,7 @@ impl CanonicalizeContext {
 				// debug!("clean_mathml: after loop\n{}", mml_to_string(mathml));
 
 				if element_name == "mrow" || ELEMENTS_WITH_ONE_CHILD.contains(element_name) {
-					clean_chemistry_mrow(mathml);
+					clean_chemistry_mrow(mathml, self.is_heuristic_disabled(Heuristics::CHEMISTRY));
 				}
 				self.assure_nary_tag_has_one_child(mathml);
 				if crate::xpath_functions::IsNode::is_2D(mathml) {
@@ -1294,7 +1730,7 @@ impl CanonicalizeContext {
 							"∷" | "::" => return Some(true),		// "::" might not be canonicalized yet
 							"∶" => return Some(false),
 							_ => {
-								if let Some(op) = OPERATORS.get(text)
+								if let Some(op) = get_operator_info(text)
 									&& op.priority < *PROPORTIONAL_PRIORITY {
 										return None;		// no "∷"
 									}
@@ -1373,8 +1809,8 @@ impl CanonicalizeContext {
 		}
 
 		/// Splits the leaf element into chemical elements if needed
-		fn clean_chemistry_leaf(mathml: Element) -> Element {
-			if !(is_chemistry_off(mathml) || mathml.attribute(MAYBE_CHEMISTRY).is_some()) {
+		fn clean_chemistry_leaf(mathml: Element, is_heuristic_disabled: bool) -> Element {
+			if !(is_chemistry_off(mathml, is_heuristic_disabled) || mathml.attribute(MAYBE_CHEMISTRY).is_some()) {
 				assert!(name(mathml)=="mi" || name(mathml)=="mtext");
 				// this is hack -- VII is more likely to be roman numeral than the molecule V I I so prevent that from happening
 				// FIX: come up with a less hacky way to prevent chem element misinterpretation
@@ -2058,6 +2494,7 @@ impl CanonicalizeContext {
 			return None;
 
 			fn split_element(leaf: Element) -> Element {
+				let original = original_for_report(&[leaf]);
 				let mut children = Vec::with_capacity(leaf.children().len());
 				for ch in as_text(leaf).chars() {
 					let new_leaf = create_mathml_element(&leaf.document(), "mi");
@@ -2066,6 +2503,7 @@ impl CanonicalizeContext {
 				}
 				set_mathml_name(leaf, "mrow");
 				leaf.replace_children(children);
+				record_repair("split-mi", original, leaf);
 				return leaf;
 			}
 		}
@@ -2192,12 +2630,14 @@ impl CanonicalizeContext {
 			return merge_from_text(mi, &text, &following_mi_siblings);
 
 			fn merge_from_text<'a>(mi: Element<'a>, text: &str, following_siblings: &[Element<'a>]) -> Option<Element<'a>> {
+				let original = original_for_report( &[&[mi], following_siblings].concat() );
 				// remove trailing mi's
 				let i_last_child = following_siblings.len()-1;
 				let last_child = following_siblings[i_last_child];
 				if name(last_child) == "mi" {
 					following_siblings.iter().for_each(|sibling| sibling.remove_from_parent());
 					mi.set_text(text);
+					record_repair("merged-mi", original, mi);
 					return Some(mi);
 				} else {
 					// replace the base of the scripted element (the last child) with the run (e.g. 's i n^2' -> {sin}^2)
@@ -2209,6 +2649,7 @@ impl CanonicalizeContext {
 						base = as_element(base.children()[0]);
 					}
 					base.set_text(text);
+					record_repair("merged-mi", original, last_child);
 					return Some(last_child);
 				}
 			}
@@ -2360,7 +2801,7 @@ impl CanonicalizeContext {
 
 		/// Merge the number block from start..end
 		fn merge_block(children: &mut Vec<ChildOfElement>, start: usize, end: usize) {
-
+			let original = original_for_report( &children[start..end].iter().map(|&child| as_element(child)).collect::<Vec<Element>>() );
 			// debug!("merge_block: merging {}..{}", start, end);
 			let mut mn_text = String::with_capacity(4*(end-start)-1);		// true size less than #3 digit blocks + separator
 			for &child_as_element in children.iter().take(end).skip(start) {
@@ -2370,6 +2811,7 @@ impl CanonicalizeContext {
 			let child = as_element(children[start]);
 			set_mathml_name(child, "mn");
 			child.set_text(&mn_text);
+			record_repair("merged-number", original, child);
 
 			children.drain(start+1..end);
 		}
@@ -3390,7 +3832,7 @@ impl CanonicalizeContext {
 		let found_op_info = if mo_node.attribute_value(CHEMICAL_BOND).is_some() {
 			Some(&IMPLIED_CHEMICAL_BOND)
 		} else {
-			OPERATORS.get(as_text(mo_node))
+			get_operator_info(as_text(mo_node))
 		};
 		if found_op_info.is_none() {
 			// no known operator -- return the unknown operator with the correct "fix" type
@@ -3488,7 +3930,7 @@ impl CanonicalizeContext {
 		// if in a prefix location, it is a left fence
 		// note:  if there is an operator on the top of the stack, it wants an operand (otherwise it would have been reduced)
 		let operator_str = as_text(mo_node);
-		let found_op_info = OPERATORS.get(operator_str);
+		let found_op_info = get_operator_info(operator_str);
 		if found_op_info.is_none() {
 			return original_op;
 		}
@@ -3931,7 +4373,7 @@ impl CanonicalizeContext {
 
 	/// implied comma when two numbers are adjacent and are in a script position
 	fn is_implied_comma<'a>(&self, prev: Element<'a>, current: Element<'a>, mrow: Element<'a>) -> bool {
-		if name(prev) != "mn" || name(current) != "mn" {
+		if name(prev) != "mn" || name(current) != "mn" || self.is_heuristic_disabled(Heuristics::IMPLIED_COMMA) {
 			return false;
 		}
 
@@ -6672,5 +7114,254 @@ mod canonicalize_tests {
 	}
 
 
+	#[test]
+	fn repair_report_off() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_mathml("<math><mfenced><mi>a</mi><mi>b</mi></mfenced></math>").unwrap();
+		assert!(get_canonicalization_report().unwrap().is_empty());
+	}
+
+	#[test]
+	fn repair_report_mfenced_and_numbers() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("CanonicalizationReport", "true").unwrap();
+		let canonical_mathml = set_mathml("<math>
+				<mfenced><mi>a</mi><mi>b</mi></mfenced><mo>+</mo>
+				<mn>1</mn><mo>,</mo><mn>234</mn><mo>+</mo><mn>-3</mn>
+			</math>").unwrap();
+		set_preference("CanonicalizationReport", "false").unwrap();
+		assert!(!canonical_mathml.contains(REPAIR_ATTR));
+		let report = get_canonicalization_report().unwrap();
+		assert_eq!(report.iter().map(|repair| repair.kind.as_str()).collect::<Vec<&str>>(),
+				   vec!["mfenced", "split-number", "merged-number"]);		// children are cleaned first
+		assert!(report[0].original.starts_with("<mfenced>"));
+		assert!(report[0].result.starts_with(&format!("<mrow id='{}'", report[0].id)));
+		assert_eq!(report[1].original, "<mn>-3</mn>");
+		assert!(canonical_mathml.contains(&format!("<mrow data-changed='added' id='{}'", report[1].id)));
+		assert_eq!(report[2].original.split_whitespace().collect::<String>(), "<mn>1</mn><mo>,</mo><mn>234</mn>");
+		assert!(report[2].result.ends_with(">1,234</mn>"));
+	}
+
+	#[test]
+	fn repair_report_ignores_bad_markers() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("CanonicalizationReport", "true").unwrap();
+		let canonical_mathml = set_mathml(&format!("<math>
+				<mrow {REPAIR_ATTR}='x'><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mo>+</mo>
+				<mrow {REPAIR_ATTR}='99'><mi>c</mi><mo>+</mo><mi>d</mi></mrow><mo>+</mo><mn>-3</mn>
+			</math>")).unwrap();
+		set_preference("CanonicalizationReport", "false").unwrap();
+		assert!(!canonical_mathml.contains(REPAIR_ATTR));
+		let report = get_canonicalization_report().unwrap();
+		assert_eq!(report.len(), 1);
+		assert_eq!(report[0].kind, "split-number");
+		assert!(canonical_mathml.contains(&format!("<mrow data-changed='added' id='{}'", report[0].id)));
+	}
+
+	#[test]
+	fn repair_report_merged_mi() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("CanonicalizationReport", "true").unwrap();
+		set_mathml("<math><mi>s</mi><mi>i</mi><mi>n</mi><mi>x</mi></math>").unwrap();
+		set_preference("CanonicalizationReport", "false").unwrap();
+		let report = get_canonicalization_report().unwrap();
+		assert_eq!(report.len(), 1);
+		assert_eq!(report[0].kind, "merged-mi");
+		assert_eq!(report[0].original.split_whitespace().collect::<String>(), "<mi>s</mi><mi>i</mi><mi>n</mi><mi>x</mi>");
+		assert_eq!(report[0].result, format!("<mi id='{}' data-id-added='true'>sinx</mi>", report[0].id));
+	}
+
+	#[test]
+	fn repair_report_chemistry() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("CanonicalizationReport", "true").unwrap();
+		set_mathml("<math><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>").unwrap();
+		let report = get_canonicalization_report().unwrap();
+		assert_eq!(report.len(), 1);
+		assert_eq!(report[0].kind, "chemistry");
+		assert!(report[0].result.contains("data-chem-formula"));
+
+		// not a guess if the author said it is chemistry
+		set_mathml("<math intent=':chemical-formula'><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>").unwrap();
+		set_preference("CanonicalizationReport", "false").unwrap();
+		assert!(get_canonicalization_report().unwrap().is_empty());
+	}
+
+
+	/// Canonicalize 'test' with "OperatorDictionary" set to 'entries' and compare it to 'target'
+	fn canonicalize_with_operator_dictionary(test: &str, target: &str, entries: &[(&str, &str)]) -> Result<bool> {
+		use crate::definitions::{SPEECH_DEFINITIONS, Contains};
+		use crate::interface::{get_element, trim_element, is_same_element};
+		crate::interface::set_rules_dir(abs_rules_dir_path()).unwrap();
+		crate::speech::SPEECH_RULES.with(|rules|  rules.borrow_mut().read_files().unwrap());
+		let entries = entries.iter().map(|(op, value)| (op.to_string(), value.to_string())).collect::<HashMap<String, String>>();
+		SPEECH_DEFINITIONS.with(|definitions| {
+			definitions.borrow_mut().name_to_var_mapping.insert("OperatorDictionary".to_string(), Contains::Map( Rc::new( RefCell::new(entries) ) ));
+		});
+		let package1 = &parser::parse(test).expect("Failed to parse test input");
+		let mathml = get_element(package1);
+		trim_element(mathml, false);
+		let result = canonicalize(mathml);
+		SPEECH_DEFINITIONS.with(|definitions| definitions.borrow_mut().name_to_var_mapping.remove("OperatorDictionary"));
+		let mathml_test = result?;
+
+		let package2 = &parser::parse(target).expect("Failed to parse target input");
+		let mathml_target = get_element(package2);
+		trim_element(mathml_target, false);
+		if let Err(e) = is_same_element(mathml_test, mathml_target, &[]) {
+			panic!("{}\nResult:\n{}\nTarget:\n{}", e, mml_to_string(mathml_test), mml_to_string(mathml_target));
+		}
+		return Ok(true);
+	}
+
+	#[test]
+	fn operator_dictionary_new_fences() {
+		let test_str = "<math><mo>⟅</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>⟆</mo><mo>=</mo><mi>c</mi></math>";
+		let target_str = "<math>
+				<mrow data-changed='added'>
+					<mrow data-changed='added'>
+						<mo>⟅</mo>
+						<mrow data-changed='added'><mi>a</mi><mo>,</mo><mi>b</mi></mrow>
+						<mo>⟆</mo>
+					</mrow>
+					<mo>=</mo>
+					<mi>c</mi>
+				</mrow>
+			</math>";
+		assert!(canonicalize_with_operator_dictionary(test_str, target_str,
+					&[("⟅", "prefix fence 20"), ("⟆", "postfix fence 20")]).unwrap());
+	}
+
+	#[test]
+	fn operator_dictionary_override() {
+		// '⇝' normally has a higher priority than '=', so this would be (a⇝b)=c
+		let test_str = "<math><mi>a</mi><mo>⇝</mo><mi>b</mi><mo>=</mo><mi>c</mi></math>";
+		let target_str = "<math>
+				<mrow data-changed='added'>
+					<mi>a</mi>
+					<mo>⇝</mo>
+					<mrow data-changed='added'><mi>b</mi><mo>=</mo><mi>c</mi></mrow>
+				</mrow>
+			</math>";
+		assert!(canonicalize_with_operator_dictionary(test_str, target_str, &[("⇝", "infix 200")]).unwrap());
+	}
+
+	#[test]
+	fn operator_dictionary_entries_are_reused() {
+		// reloading (or changing back to) a definition doesn't allocate new entries
+		let op = parse_operator_dictionary_entry("prefix fence 20 || infix 260").unwrap();
+		assert!(std::ptr::eq(op, parse_operator_dictionary_entry("prefix  fence 20 ||  infix 260").unwrap()));
+		assert!(!std::ptr::eq(op, parse_operator_dictionary_entry("prefix fence 20 || infix 270").unwrap()));
+		assert_eq!(op.priority, 20);
+		assert_eq!(op.next.as_ref().unwrap().priority, 260);
+	}
+
+	#[test]
+	fn operator_dictionary_errors() {
+		let test_str = "<math><mi>a</mi><mo>⇝</mo><mi>b</mi></math>";
+		for (op, value) in [("⇝", "between 200"), ("⇝", "infix"), ("⇝", "infix fence 20"), ("⇝", "infix 200 || infix 300"), ("⇝", "infix stretchy 200"), ("+", "infix 200")] {
+			let error = canonicalize_with_operator_dictionary(test_str, test_str, &[(op, value)]).unwrap_err();
+			assert!(format!("{error:#}").contains("OperatorDictionary"), "no error for '{op}': '{value}'");
+		}
+	}
+
+	#[test]
+	fn detect_separators_from_expression() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("Language", "en").unwrap();
+		set_preference("DecimalSeparator", "Detect").unwrap();
+		let canonical_mathml = set_mathml("<math><mn>3,14</mn><mo>+</mo><mn>1.234,5</mn></math>").unwrap();
+		assert!(canonical_mathml.contains(">3,14</mn>"));
+		let decision = get_separator_decision().unwrap();
+		assert_eq!(decision.decimal_separators, ",");
+		assert_eq!(decision.block_separators, ". \u{00A0}\u{202F}");
+		assert_eq!(decision.source, "expression");
+		// the detected separators don't change the user's prefs
+		assert_eq!(get_preference("DecimalSeparators").unwrap(), ".");
+		assert_eq!(get_preference("BlockSeparators").unwrap(), ", \u{00A0}\u{202F}");
+
+		// both separators are used -- the last one is the decimal separator
+		set_mathml("<math><mn>1,234.5</mn></math>").unwrap();
+		let decision = get_separator_decision().unwrap();
+		assert_eq!(decision.decimal_separators, ".");
+		assert_eq!(decision.source, "expression");
+	}
+
+	#[test]
+	fn detect_separators_uses_language() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("Language", "de").unwrap();
+		set_preference("DecimalSeparator", "Detect").unwrap();
+		// "1.234" is a number with either separator
+		set_mathml("<math><mn>1.234</mn><mo>+</mo><mn>2</mn></math>").unwrap();
+		let decision = get_separator_decision().unwrap();
+		assert_eq!(decision.decimal_separators, ",");
+		assert_eq!(decision.source, "language");
+
+		set_mathml("<math><mn>1.5</mn></math>").unwrap();
+		assert_eq!(get_separator_decision().unwrap().decimal_separators, ".");
+
+		// linting doesn't change the decision for the math that was set
+		crate::interface::lint_mathml("<math><mn>0,5</mn></math>").unwrap();
+		assert_eq!(get_separator_decision().unwrap().decimal_separators, ".");
+		assert_eq!(get_preference("DecimalSeparators").unwrap(), ",");
+	}
+
+	#[test]
+	fn separator_decision_from_preference() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		set_preference("DecimalSeparator", ".").unwrap();
+		set_mathml("<math><mn>3,14</mn></math>").unwrap();
+		let decision = get_separator_decision().unwrap();
+		assert_eq!(decision.decimal_separators, ".");
+		assert_eq!(decision.source, "preference");
+	}
+
+	#[test]
+	fn disabled_heuristics() {
+		use crate::interface::*;
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		let roman_numeral = "<math><mi>XII</mi><mo>+</mo><mn>3</mn></math>";
+		let chemistry = "<math><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+		let points = "<math><mo>∠</mo><mi>ABC</mi></math>";
+		let implied_comma = "<math><msub><mi>a</mi><mrow><mn>1</mn><mn>2</mn></mrow></msub></math>";
+
+		assert!(set_mathml(roman_numeral).unwrap().contains("data-roman-numeral"));
+		assert!(set_mathml(chemistry).unwrap().contains("data-chem-formula"));
+		assert!(!set_mathml(points).unwrap().contains(">ABC<"));
+		assert!(set_mathml(implied_comma).unwrap().contains("&#x2063;"));
+
+		set_preference("DisabledHeuristics", "roman-numerals").unwrap();
+		assert!(!set_mathml(roman_numeral).unwrap().contains("data-roman-numeral"));
+		assert!(set_mathml(chemistry).unwrap().contains("data-chem-formula"));
+
+		set_preference("DisabledHeuristics", "chemistry").unwrap();
+		assert!(set_mathml(roman_numeral).unwrap().contains("data-roman-numeral"));
+		assert!(!set_mathml(chemistry).unwrap().contains("data-chem-formula"));
+
+		set_preference("DisabledHeuristics", "split-points").unwrap();
+		assert!(set_mathml(points).unwrap().contains(">ABC<"));
+
+		set_preference("DisabledHeuristics", "implied-comma").unwrap();
+		let mathml = set_mathml(implied_comma).unwrap();
+		assert!(!mathml.contains("&#x2063;") && mathml.contains("&#x2062;"));
+
+		set_preference("DisabledHeuristics", "roman-numerals, split-points").unwrap();
+		assert!(!set_mathml(roman_numeral).unwrap().contains("data-roman-numeral"));
+		assert!(set_mathml(points).unwrap().contains(">ABC<"));
+
+		set_preference("DisabledHeuristics", "").unwrap();
+		assert!(set_mathml(roman_numeral).unwrap().contains("data-roman-numeral"));
+
+		assert!(set_preference("DisabledHeuristics", "roman-numerals,xyz").is_err());
+	}
 }
 
@@ -106,16 +106,30 @@ fn has_inherited_property(mathml: Element, property: &str) -> bool {
     return false;
 }
 
-pub fn is_chemistry_off(mathml: Element) -> bool {
+/// Returns true if chemistry shouldn't be recognized in 'mathml' (a chemistry intent overrides the prefs)
+/// 'is_heuristic_disabled' is true if "chemistry" is in the "DisabledHeuristics" pref
+pub fn is_chemistry_off(mathml: Element, is_heuristic_disabled: bool) -> bool {
     if has_chem_intent(mathml, ":chemical-formula") || has_chem_intent(mathml, ":chemical-equation") {
         return false;
     }
     let pref_manager = crate::prefs::PreferenceManager::get();
-    return pref_manager.borrow().pref_to_string("Chemistry") == "Off";
+    return pref_manager.borrow().pref_to_string("Chemistry") == "Off" || is_heuristic_disabled;
 }
 
-pub fn clean_chemistry_mrow(mathml: Element) {
-    if is_chemistry_off(mathml) {
+/// Returns true if 'mathml' was marked as a chemical formula/equation by the heuristics (not because of an intent or `\ce`)
+pub fn is_guessed_chemistry(mathml: Element) -> bool {
+    if mathml.attribute(CHEM_FORMULA).is_none() && mathml.attribute(CHEM_EQUATION).is_none() {
+        return false;
+    }
+    let is_ce = mathml.parent().and_then(|parent| parent.element())
+                    .and_then(|parent| parent.attribute_value("data-latex"))
+                    .is_some_and(|latex| latex.trim_start().starts_with(r"\ce"));
+    return !(is_ce ||
+             has_inherited_property(mathml, ":chemical-formula") || has_inherited_property(mathml, ":chemical-equation"));
+}
+
+pub fn clean_chemistry_mrow(mathml: Element, is_heuristic_disabled: bool) {
+    if is_chemistry_off(mathml, is_heuristic_disabled) {
         return;
     }
     // debug!("clean_chemistry_mrow:\n{}", mml_to_string(mathml));
@@ -419,8 +433,8 @@ pub fn convert_leaves_to_chem_elements(mathml: Element) -> Option<Vec<Element>>
 /// This function assumes proper structure
 /// 
 /// Returns true if not chemistry -- added attrs, mrows, and leaves are removed in preparation for a second parse
-pub fn scan_and_mark_chemistry(mathml: Element) -> bool {
-    if is_chemistry_off(mathml) {
+pub fn scan_and_mark_chemistry(mathml: Element, is_heuristic_disabled: bool) -> bool {
+    if is_chemistry_off(mathml, is_heuristic_disabled) {
         return true;
     }
 
@@ -1240,11 +1254,7 @@ fn collect_elements(mrow: Element<'_>) -> Option<Vec<&str>> {
                     elements.push(as_text(base));
                 }   // else skip and let recursive likely_chem_formula call check the contents
             },
-            "mo" => {
-                if likely_chem_formula_operator(child) < 0 {
-                    return None;
-                }
-            },
+            "mo" if likely_chem_formula_operator(child) < 0 => return None,
             _ => (),    // let loop in likely_chem_formula() deal with all the negatives
         }
     }
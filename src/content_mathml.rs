@@ -0,0 +1,1331 @@
+//! Converts Content MathML (both strict and pragmatic) into presentation MathML.
+//!
+//! Content MathML describes the meaning of an expression (e.g., `<apply><plus/><ci>x</ci><cn>1</cn></apply>`),
+//! but the speech and braille rules only understand presentation MathML. The conversion generates the usual
+//! presentation for the expression (adding parens based on operator precedence) and adds an `intent` where the
+//! presentation by itself is ambiguous (e.g., an open interval looks like a point).
+//!
+//! This is called as the first step of canonicalization:
+//! * content elements found in the tree (typically the entire `math` content) are replaced by presentation MathML.
+//! * for `semantics`, the content branch is used when there is no presentation branch.
+//!   If there is also a presentation branch, intents from the converted content are copied onto it where the
+//!   structures match.
+//!
+//! Strict Content MathML (`csymbol`s with content dictionaries and `bind`) is mapped onto the pragmatic names
+//! so both forms share the conversion code.
+#![allow(clippy::needless_return)]
+
+use sxd_document::dom::*;
+use phf::{phf_map, phf_set};
+use crate::canonicalize::{as_element, create_mathml_element, name};
+use crate::errors::*;
+use crate::pretty_print::mml_to_string;
+
+/// Content MathML element names (other than the empty operator elements in `OPERATORS` and `CONSTANTS`)
+static CONTENT_ELEMENTS: phf::Set<&str> = phf_set! {
+	"apply", "bind", "ci", "cn", "csymbol", "cs", "cbytes", "cerror", "share",
+	"bvar", "degree", "logbase", "lowlimit", "uplimit", "condition", "domainofapplication", "momentabout",
+	"set", "list", "interval", "vector", "matrix", "matrixrow", "lambda", "piecewise", "piece", "otherwise",
+	"declare", "reln", "fn", "sep",
+};
+
+/// Names used by strict Content MathML `csymbol`s that differ from the pragmatic element names
+static STRICT_NAMES: phf::Map<&str, &str> = phf_map! {
+	"unary_minus" => "minus",
+	"remainder" => "rem",
+	"cartesian_product" => "cartesianproduct",
+	"size" => "card",
+	"left_compose" => "compose",
+	"argument" => "arg",
+	"defint" => "int",
+	"e" => "exponentiale",
+	"i" => "imaginaryi",
+	"gamma" => "eulergamma",
+	"NaN" => "notanumber",
+	"N" => "naturalnumbers",
+	"Z" => "integers",
+	"Q" => "rationals",
+	"R" => "reals",
+	"C" => "complexes",
+	"P" => "primes",
+	"interval" => "interval_cc",
+	"integer_interval" => "interval_cc",
+};
+
+/// Constants and their presentation (all are 'mi's)
+static CONSTANTS: phf::Map<&str, &str> = phf_map! {
+	"pi" => "π",
+	"exponentiale" => "e",
+	"imaginaryi" => "i",
+	"infinity" => "∞",
+	"emptyset" => "∅",
+	"eulergamma" => "γ",
+	"true" => "true",
+	"false" => "false",
+	"notanumber" => "NaN",
+	"naturalnumbers" => "ℕ",
+	"integers" => "ℤ",
+	"rationals" => "ℚ",
+	"reals" => "ℝ",
+	"complexes" => "ℂ",
+	"primes" => "ℙ",
+};
+
+/// How an operator is displayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Form {
+	/// n-ary infix operator with its precedence (e.g., "+")
+	Infix(u8),
+	/// relations can be chained (e.g., a < b < c)
+	Relation,
+	/// prefix function like "sin" -- parens are only added when the argument isn't simple
+	Function,
+	/// function that always uses parens and (possibly) multiple args (e.g., "gcd(a,b)")
+	ParenFunction,
+	/// the arg is surrounded by the pair of fences (e.g., "|x|")
+	Fenced(&'static str, &'static str),
+	/// everything else is handled specially
+	Special,
+}
+
+/// precedences used to decide whether an operand needs parens
+const PREC_IMPLIES: u8 = 1;
+const PREC_OR: u8 = 2;
+const PREC_AND: u8 = 3;
+const PREC_NOT: u8 = 4;
+const PREC_RELATION: u8 = 5;
+const PREC_PLUS: u8 = 6;
+const PREC_TIMES: u8 = 7;
+const PREC_FUNCTION: u8 = 8;	// "sin x", "∑ x" -- extends to the right
+const PREC_POSTFIX: u8 = 9;
+const PREC_POWER: u8 = 10;
+const PREC_APPLY: u8 = 11;		// "f(x)"
+const PREC_ATOM: u8 = u8::MAX;
+
+/// The operators (pragmatic names) along with the presentation used for them
+static OPERATORS: phf::Map<&str, (&str, Form)> = phf_map! {
+	"plus" => ("+", Form::Infix(PREC_PLUS)),
+	"minus" => ("−", Form::Special),
+	"times" => ("\u{2062}", Form::Special),
+	"divide" => ("/", Form::Special),
+	"power" => ("", Form::Special),
+	"root" => ("", Form::Special),
+	"quotient" => ("", Form::Special),
+	"rem" => ("mod", Form::Infix(PREC_TIMES)),
+	"factorial" => ("!", Form::Special),
+	"compose" => ("∘", Form::Infix(PREC_TIMES)),
+	"and" => ("∧", Form::Infix(PREC_AND)),
+	"or" => ("∨", Form::Infix(PREC_OR)),
+	"xor" => ("⊻", Form::Infix(PREC_OR)),
+	"not" => ("¬", Form::Special),
+	"implies" => ("⇒", Form::Infix(PREC_IMPLIES)),
+	"equivalent" => ("⇔", Form::Infix(PREC_IMPLIES)),
+	"forall" => ("∀", Form::Special),
+	"exists" => ("∃", Form::Special),
+	"eq" => ("=", Form::Relation),
+	"neq" => ("≠", Form::Relation),
+	"lt" => ("<", Form::Relation),
+	"gt" => (">", Form::Relation),
+	"leq" => ("≤", Form::Relation),
+	"geq" => ("≥", Form::Relation),
+	"approx" => ("≈", Form::Relation),
+	"factorof" => ("∣", Form::Relation),
+	"tendsto" => ("→", Form::Relation),
+	"in" => ("∈", Form::Relation),
+	"notin" => ("∉", Form::Relation),
+	"subset" => ("⊆", Form::Relation),
+	"prsubset" => ("⊂", Form::Relation),
+	"notsubset" => ("⊈", Form::Relation),
+	"notprsubset" => ("⊄", Form::Relation),
+	"union" => ("∪", Form::Infix(PREC_PLUS)),
+	"intersect" => ("∩", Form::Infix(PREC_TIMES)),
+	"setdiff" => ("∖", Form::Special),
+	"cartesianproduct" => ("×", Form::Infix(PREC_TIMES)),
+	"vectorproduct" => ("×", Form::Infix(PREC_TIMES)),
+	"scalarproduct" => ("⋅", Form::Infix(PREC_TIMES)),
+	"outerproduct" => ("⊗", Form::Infix(PREC_TIMES)),
+	"abs" => ("", Form::Fenced("|", "|")),
+	"card" => ("", Form::Fenced("|", "|")),
+	"floor" => ("", Form::Fenced("⌊", "⌋")),
+	"ceiling" => ("", Form::Fenced("⌈", "⌉")),
+	"sin" => ("sin", Form::Function), "cos" => ("cos", Form::Function), "tan" => ("tan", Form::Function),
+	"sec" => ("sec", Form::Function), "csc" => ("csc", Form::Function), "cot" => ("cot", Form::Function),
+	"sinh" => ("sinh", Form::Function), "cosh" => ("cosh", Form::Function), "tanh" => ("tanh", Form::Function),
+	"sech" => ("sech", Form::Function), "csch" => ("csch", Form::Function), "coth" => ("coth", Form::Function),
+	"arcsin" => ("arcsin", Form::Function), "arccos" => ("arccos", Form::Function), "arctan" => ("arctan", Form::Function),
+	"arcsec" => ("arcsec", Form::Function), "arccsc" => ("arccsc", Form::Function), "arccot" => ("arccot", Form::Function),
+	"arcsinh" => ("arcsinh", Form::Function), "arccosh" => ("arccosh", Form::Function), "arctanh" => ("arctanh", Form::Function),
+	"arcsech" => ("arcsech", Form::Function), "arccsch" => ("arccsch", Form::Function), "arccoth" => ("arccoth", Form::Function),
+	"ln" => ("ln", Form::Function),
+	"log" => ("log", Form::Special),
+	"exp" => ("", Form::Special),
+	"determinant" => ("det", Form::Function),
+	"arg" => ("arg", Form::Function),
+	"real" => ("Re", Form::Function),
+	"imaginary" => ("Im", Form::Function),
+	"ident" => ("id", Form::Function),
+	"domain" => ("dom", Form::ParenFunction),
+	"codomain" => ("codom", Form::ParenFunction),
+	"image" => ("im", Form::ParenFunction),
+	"gcd" => ("gcd", Form::ParenFunction),
+	"lcm" => ("lcm", Form::ParenFunction),
+	"max" => ("max", Form::ParenFunction),
+	"min" => ("min", Form::ParenFunction),
+	"mean" => ("mean", Form::ParenFunction),
+	"sdev" => ("σ", Form::ParenFunction),
+	"variance" => ("var", Form::ParenFunction),
+	"median" => ("median", Form::ParenFunction),
+	"mode" => ("mode", Form::ParenFunction),
+	"divergence" => ("∇⋅", Form::Special),
+	"grad" => ("∇", Form::Special),
+	"curl" => ("∇×", Form::Special),
+	"laplacian" => ("∇", Form::Special),
+	"conjugate" => ("¯", Form::Special),
+	"transpose" => ("T", Form::Special),
+	"inverse" => ("", Form::Special),
+	"selector" => ("", Form::Special),
+	"diff" => ("d", Form::Special),
+	"partialdiff" => ("∂", Form::Special),
+	"int" => ("∫", Form::Special),
+	"sum" => ("∑", Form::Special),
+	"product" => ("∏", Form::Special),
+	"limit" => ("lim", Form::Special),
+	"interval_oo" => ("", Form::Special),
+	"interval_cc" => ("", Form::Special),
+	"interval_oc" => ("", Form::Special),
+	"interval_co" => ("", Form::Special),
+	"set" => ("", Form::Special),
+	"list" => ("", Form::Special),
+	"vector" => ("", Form::Special),
+	"matrix" => ("", Form::Special),
+	"matrixrow" => ("", Form::Special),
+	"lambda" => ("", Form::Special),
+};
+
+/// Returns true if 'element' is a Content MathML element
+pub fn is_content_element(element: Element) -> bool {
+	let element_name = name(element);
+	return CONTENT_ELEMENTS.contains(element_name) ||
+		   OPERATORS.contains_key(element_name) ||
+		   CONSTANTS.contains_key(element_name);
+}
+
+/// Replace any Content MathML in the tree rooted at 'mathml' with presentation MathML.
+/// 'semantics' elements are restructured so that the presentation is always the first child.
+pub fn convert_content_mathml(mathml: Element) -> Result<()> {
+	for child in mathml.children() {
+		let Some(child) = child.element() else {
+			continue;	// text of a leaf
+		};
+		let child_name = name(child);
+		if child_name == "semantics" {
+			convert_semantics(child)?;
+		} else if child_name == "annotation" || child_name == "annotation-xml" {
+			continue;		// error caught later if not in 'semantics'
+		} else if is_content_element(child) {
+			let presentation = convert(child)?.element;
+			replace_element(child, presentation);
+		} else {
+			convert_content_mathml(child)?;
+		}
+	}
+	return Ok( () );
+}
+
+/// If the first child of 'semantics' is Content MathML, move the presentation (if any) or else the converted content to be the first child.
+/// If there is a content annotation, copy any intents from its conversion onto the presentation.
+fn convert_semantics(semantics: Element) -> Result<()> {
+	let children = semantics.children();
+	if children.is_empty() {
+		return Ok( () );
+	}
+	let first_child = as_element(children[0]);
+	let presentation_annotation = children.iter()
+		.map(|&child| as_element(child))
+		.find(|&child| name(child) == "annotation-xml" && child.attribute_value("encoding") == Some("MathML-Presentation"));
+	if is_content_element(first_child) {
+		let converted = convert(first_child)?.element;
+		let presentation = match presentation_annotation {
+			Some(annotation) if annotation.children().len() == 1 => {
+				let presentation = as_element(annotation.children()[0]);
+				annotation.remove_from_parent();
+				copy_intents(converted, presentation);
+				presentation
+			},
+			_ => converted,
+		};
+		// keep the content around as an annotation
+		let content_annotation = create_mathml_element(&semantics.document(), "annotation-xml");
+		content_annotation.set_attribute_value("encoding", "MathML-Content");
+		replace_element(first_child, content_annotation);
+		content_annotation.append_child(first_child);
+		let mut new_children = vec![ChildOfElement::Element(presentation)];
+		new_children.append(&mut semantics.children());
+		semantics.replace_children(new_children);
+	} else {
+		for child in children.iter().skip(1) {
+			let annotation = as_element(*child);
+			if name(annotation) == "annotation-xml" &&
+			   matches!(annotation.attribute_value("encoding"), Some("MathML-Content") | Some("MathML-Content-Strict")) &&
+			   annotation.children().len() == 1 {
+				let content = as_element(annotation.children()[0]);
+				if is_content_element(content) {
+					let converted = convert(content)?.element;
+					let presentation = match presentation_annotation {
+						Some(presentation_annotation) if presentation_annotation.children().len() == 1 =>
+							as_element(presentation_annotation.children()[0]),
+						_ => first_child,
+					};
+					copy_intents(converted, presentation);
+				}
+			}
+		}
+	}
+	let (_, presentation) = crate::canonicalize::get_presentation_element(semantics);
+	return convert_content_mathml(presentation);
+}
+
+/// Replace 'old' with 'new' in the parent of 'old' (there must be a parent)
+fn replace_element<'a>(old: Element<'a>, new: Element<'a>) {
+	let parent = old.parent().unwrap().element().unwrap();
+	let new_children = parent.children().into_iter()
+		.map(|child| if child.element() == Some(old) {ChildOfElement::Element(new)} else {child})
+		.collect::<Vec<ChildOfElement>>();
+	parent.replace_children(new_children);
+}
+
+/// Copy 'intent' and 'arg' attrs from 'converted' to 'presentation' where the two trees have the same shape.
+/// The presentation wins if it already has an intent.
+fn copy_intents(converted: Element, presentation: Element) {
+	if name(converted) != name(presentation) || converted.children().len() != presentation.children().len() {
+		return;
+	}
+	if same_shape(converted, presentation) {
+		for attr in ["intent", "arg"] {
+			if let Some(value) = converted.attribute_value(attr) &&
+			   presentation.attribute(attr).is_none() {
+				presentation.set_attribute_value(attr, value);
+			}
+		}
+	} else if converted.attribute("intent").is_some() {
+		return;		// args would be out of sync
+	}
+	for (converted_child, presentation_child) in converted.children().iter().zip(presentation.children()) {
+		if let (Some(converted_child), Some(presentation_child)) = (converted_child.element(), presentation_child.element()) {
+			copy_intents(converted_child, presentation_child);
+		}
+	}
+
+	fn same_shape(converted: Element, presentation: Element) -> bool {
+		let converted_children = converted.children();
+		let presentation_children = presentation.children();
+		return name(converted) == name(presentation) &&
+			converted_children.len() == presentation_children.len() &&
+			converted_children.iter().zip(presentation_children.iter()).all(|(c, p)|
+				match (c.element(), p.element()) {
+					(Some(c), Some(p)) => same_shape(c, p),
+					(None, None) => true,
+					_ => false,
+				}
+			);
+	}
+}
+
+/// The presentation for some content, along with the precedence of its top-level operator
+struct Presentation<'a> {
+	element: Element<'a>,
+	precedence: u8,
+}
+
+impl<'a> Presentation<'a> {
+	fn new(element: Element<'a>, precedence: u8) -> Presentation<'a> {
+		return Presentation{ element, precedence };
+	}
+
+	fn atom(element: Element<'a>) -> Presentation<'a> {
+		return Presentation{ element, precedence: PREC_ATOM };
+	}
+}
+
+/// The parts of an 'apply' or 'bind' (or container elements like 'set') that are qualifiers
+#[derive(Default)]
+struct Qualifiers<'a> {
+	bvars: Vec<Element<'a>>,		// 'bvar' children
+	lowlimit: Option<Element<'a>>,
+	uplimit: Option<Element<'a>>,
+	condition: Option<Element<'a>>,
+	domain: Option<Element<'a>>,	// 'domainofapplication' or an 'interval' qualifier
+	degree: Option<Element<'a>>,
+	logbase: Option<Element<'a>>,
+}
+
+fn leaf<'a>(doc: &Document<'a>, element_name: &str, text: &str) -> Element<'a> {
+	let element = create_mathml_element(doc, element_name);
+	element.set_text(text);
+	return element;
+}
+
+fn mo<'a>(doc: &Document<'a>, text: &str) -> Element<'a> {
+	return leaf(doc, "mo", text);
+}
+
+fn mrow<'a>(doc: &Document<'a>, children: Vec<Element<'a>>) -> Element<'a> {
+	if children.len() == 1 {
+		return children[0];
+	}
+	let mrow = create_mathml_element(doc, "mrow");
+	mrow.append_children(children);
+	return mrow;
+}
+
+fn layout<'a>(doc: &Document<'a>, element_name: &str, children: Vec<Element<'a>>) -> Element<'a> {
+	let element = create_mathml_element(doc, element_name);
+	element.append_children(children);
+	return element;
+}
+
+fn fenced<'a>(doc: &Document<'a>, open: &str, close: &str, contents: Vec<Element<'a>>) -> Element<'a> {
+	let mut children = vec![mo(doc, open)];
+	children.extend(contents);
+	children.push(mo(doc, close));
+	let mrow = create_mathml_element(doc, "mrow");
+	mrow.append_children(children);
+	return mrow;
+}
+
+/// Return the operand, adding parens if its precedence is less than 'min_precedence'
+fn operand<'a>(doc: &Document<'a>, operand: Presentation<'a>, min_precedence: u8) -> Element<'a> {
+	if operand.precedence < min_precedence {
+		return fenced(doc, "(", ")", vec![operand.element]);
+	}
+	return operand.element;
+}
+
+/// Comma separated list of the presentation of 'children'
+fn comma_separated<'a>(doc: &Document<'a>, children: Vec<Element<'a>>) -> Vec<Element<'a>> {
+	let mut result = Vec::with_capacity(2 * children.len());
+	for (i, child) in children.into_iter().enumerate() {
+		if i > 0 {
+			result.push(mo(doc, ","));
+		}
+		result.push(child);
+	}
+	return result;
+}
+
+/// Get the text of a token element (ci, cn, csymbol, ...) -- whitespace is trimmed
+fn text_of(element: Element) -> String {
+	return element.children().iter()
+		.filter_map(|child| child.text().map(|text| text.text()))
+		.collect::<String>()
+		.trim()
+		.to_string();
+}
+
+/// The pragmatic name of the operator/constant 'element' represents (None if it isn't an operator or constant)
+fn operator_name(element: Element) -> Option<String> {
+	let element_name = name(element);
+	if element_name == "csymbol" {
+		if presentation_children(element).is_some() {
+			return None;
+		}
+		let text = text_of(element);
+		return Some( STRICT_NAMES.get(text.as_str()).map_or(text, |name| name.to_string()) );
+	}
+	if OPERATORS.contains_key(element_name) || CONSTANTS.contains_key(element_name) {
+		return Some(element_name.to_string());
+	}
+	return None;
+}
+
+/// Convert a content element to presentation MathML
+fn convert(content: Element) -> Result<Presentation> {
+	let doc = content.document();
+	let element_name = name(content);
+	let result = match element_name {
+		"ci" => convert_ci(content),
+		"cn" => convert_cn(content)?,
+		"csymbol" => match (presentation_children(content), operator_name(content)) {
+			(Some(children), _) => Presentation::atom(mrow(&doc, children)),
+			(None, Some(symbol_name)) => match CONSTANTS.get(symbol_name.as_str()) {
+				Some(text) => Presentation::atom(leaf(&doc, "mi", text)),
+				None => convert_operator_name(&doc, &symbol_name),
+			},
+			(None, None) => unreachable!(),
+		},
+		"cs" => Presentation::atom(leaf(&doc, "ms", &text_of(content))),
+		"cbytes" => Presentation::atom(leaf(&doc, "mtext", &text_of(content))),
+		"share" => Presentation::atom(leaf(&doc, "mtext", content.attribute_value("href").unwrap_or("?"))),
+		"cerror" => {
+			let children = children_of(content).into_iter()
+				.map(|child| convert(child).map(|p| p.element))
+				.collect::<Result<Vec<Element>>>()?;
+			Presentation::atom(layout(&doc, "merror", vec![mrow(&doc, children)]))
+		},
+		"apply" | "bind" | "reln" => convert_apply(content)?,
+		"fn" => match children_of(content).first() {
+			Some(&child) => convert(child)?,
+			None => bail!("'fn' element is empty"),
+		},
+		"semantics" => {
+			let children = children_of(content);
+			let presentation = children.iter().find(|&&child|
+				name(child) == "annotation-xml" && child.attribute_value("encoding") == Some("MathML-Presentation") &&
+				child.children().len() == 1);
+			match (presentation, children.first()) {
+				(Some(&presentation), _) => Presentation::new(crate::interface::copy_mathml(as_element(presentation.children()[0])), PREC_APPLY),
+				(None, Some(&first)) => convert(first)?,
+				(None, None) => bail!("'semantics' element is empty"),
+			}
+		},
+		"set" | "list" | "interval" | "vector" | "matrix" | "matrixrow" | "lambda" | "piecewise" => {
+			convert_container(element_name, content, &children_of(content))?
+		},
+		_ => {
+			if let Some(text) = CONSTANTS.get(element_name) {
+				Presentation::atom(leaf(&doc, "mi", text))
+			} else if OPERATORS.contains_key(element_name) {
+				convert_operator_name(&doc, element_name)
+			} else if is_content_element(content) {
+				bail!("Content MathML element '{}' is not allowed here:\n{}", element_name, mml_to_string(content));
+			} else {
+				// presentation MathML inside content
+				Presentation::new(crate::interface::copy_mathml(content), PREC_APPLY)
+			}
+		},
+	};
+	return Ok(result);
+}
+
+fn children_of(element: Element) -> Vec<Element> {
+	return element.children().into_iter().filter_map(|child| child.element()).collect();
+}
+
+fn convert_ci(ci: Element) -> Presentation {
+	let doc = ci.document();
+	let children = children_of(ci);
+	if children.is_empty() {
+		return Presentation::atom(leaf(&doc, "mi", &text_of(ci)));
+	}
+	// presentation markup inside of 'ci'
+	let children = children.into_iter().map(crate::interface::copy_mathml).collect::<Vec<Element>>();
+	return Presentation::atom(mrow(&doc, children));
+}
+
+fn convert_cn(cn: Element) -> Result<Presentation> {
+	let doc = cn.document();
+	// parts separated by 'sep'
+	let mut parts = vec![String::new()];
+	for child in cn.children() {
+		match child {
+			ChildOfElement::Text(text) => parts.last_mut().unwrap().push_str(text.text()),
+			ChildOfElement::Element(element) if name(element) == "sep" => parts.push(String::new()),
+			_ => (),
+		}
+	}
+	let parts = parts.iter().map(|part| part.trim()).collect::<Vec<&str>>();
+	let number = |text: &str| -> Presentation {
+		return match text.strip_prefix('-') {
+			Some(positive) => Presentation::new(mrow(&doc, vec![mo(&doc, "−"), leaf(&doc, "mn", positive)]), PREC_PLUS),
+			None => Presentation::atom(leaf(&doc, "mn", text)),
+		};
+	};
+	match (cn.attribute_value("type").unwrap_or("real"), parts.as_slice()) {
+		("rational", [numerator, denominator]) => {
+			let fraction = layout(&doc, "mfrac", vec![leaf(&doc, "mn", numerator.trim_start_matches('-')), leaf(&doc, "mn", denominator)]);
+			if numerator.starts_with('-') {
+				return Ok( Presentation::new(mrow(&doc, vec![mo(&doc, "−"), fraction]), PREC_PLUS) );
+			}
+			return Ok( Presentation::atom(fraction) );
+		},
+		("e-notation", [mantissa, exponent]) => {
+			let power = layout(&doc, "msup", vec![leaf(&doc, "mn", "10"), number(exponent).element]);
+			return Ok( Presentation::new(mrow(&doc, vec![number(mantissa).element, mo(&doc, "×"), power]), PREC_TIMES) );
+		},
+		("complex-cartesian", [real, imaginary]) => {
+			let (op, imaginary) = match imaginary.strip_prefix('-') {
+				Some(imaginary) => ("−", imaginary),
+				None => ("+", *imaginary),
+			};
+			let imaginary = mrow(&doc, vec![leaf(&doc, "mn", imaginary), mo(&doc, "\u{2062}"), leaf(&doc, "mi", "i")]);
+			return Ok( Presentation::new(mrow(&doc, vec![number(real).element, mo(&doc, op), imaginary]), PREC_PLUS) );
+		},
+		("complex-polar", [magnitude, angle]) => {
+			let exponent = mrow(&doc, vec![leaf(&doc, "mi", "i"), mo(&doc, "\u{2062}"), number(angle).element]);
+			let power = layout(&doc, "msup", vec![leaf(&doc, "mi", "e"), exponent]);
+			return Ok( Presentation::new(mrow(&doc, vec![number(magnitude).element, mo(&doc, "\u{2062}"), power]), PREC_TIMES) );
+		},
+		("constant", [constant]) => return Ok( Presentation::atom(leaf(&doc, "mi", constant)) ),
+		(_, [value]) => {
+			let value = number(value);
+			if let Some(base) = cn.attribute_value("base") &&
+			   base != "10" {
+				let element = layout(&doc, "msub", vec![value.element, leaf(&doc, "mn", base)]);
+				return Ok( Presentation::atom(element) );
+			}
+			return Ok(value);
+		},
+		_ => bail!("Unknown form of 'cn':\n{}", mml_to_string(cn)),
+	}
+}
+
+/// The presentation of an operator when it is not applied (e.g., "sin" in "sin ∘ cos")
+fn convert_operator_name<'a>(doc: &Document<'a>, op_name: &str) -> Presentation<'a> {
+	return match OPERATORS.get(op_name) {
+		Some((text, Form::Function | Form::ParenFunction)) => Presentation::atom(leaf(doc, "mi", text)),
+		Some((text, _)) if !text.is_empty() => Presentation::atom(mo(doc, text)),
+		_ => Presentation::atom(leaf(doc, "mi", op_name)),
+	};
+}
+
+/// Presentation MathML children of a 'csymbol' (if any)
+fn presentation_children(element: Element) -> Option<Vec<Element>> {
+	if name(element) != "csymbol" {
+		return None;
+	}
+	let children = children_of(element);
+	if children.is_empty() {
+		return None;
+	}
+	return Some( children.into_iter().map(crate::interface::copy_mathml).collect() );
+}
+
+/// Split the children into the head, qualifiers, and arguments.
+/// Strict forms (e.g., a lambda 'bind' as the last arg of 'int') are rewritten into pragmatic qualifiers.
+fn split_apply<'a>(head_name: &str, children: &[Element<'a>]) -> Result<(Qualifiers<'a>, Vec<Element<'a>>)> {
+	let mut qualifiers = Qualifiers::default();
+	let mut args = Vec::with_capacity(children.len());
+	for &child in children {
+		match name(child) {
+			"bvar" => qualifiers.bvars.push(child),
+			"lowlimit" => qualifiers.lowlimit = Some(only_child(child)?),
+			"uplimit" => qualifiers.uplimit = Some(only_child(child)?),
+			"condition" => qualifiers.condition = Some(only_child(child)?),
+			"domainofapplication" => qualifiers.domain = Some(only_child(child)?),
+			"degree" => qualifiers.degree = Some(only_child(child)?),
+			"logbase" => qualifiers.logbase = Some(only_child(child)?),
+			"momentabout" => (),
+			"interval" if matches!(head_name, "int" | "sum" | "product") && !children.iter().any(|&child| name(child) == "lowlimit") => {
+				qualifiers.domain = Some(child);
+			},
+			_ => args.push(child),
+		}
+	}
+
+	// strict content puts the bound variables in a lambda expression (e.g., int(lambda(x, body)))
+	if qualifiers.bvars.is_empty() &&
+	   matches!(head_name, "int" | "sum" | "product" | "limit" | "diff" | "partialdiff") &&
+	   let Some(&last) = args.last() &&
+	   let Some((bvars, body)) = as_lambda(last) {
+		qualifiers.bvars = bvars;
+		*args.last_mut().unwrap() = body;
+		if head_name == "limit" && args.len() == 3 {
+			// strict limit: limit(a, both_sides/above/below, lambda)
+			qualifiers.lowlimit = Some(args[0]);
+			args.drain(0..2);
+		} else if args.len() == 2 && qualifiers.domain.is_none() {
+			qualifiers.domain = Some(args.remove(0));
+		}
+	}
+
+	// an interval domain gives the lower/upper limits
+	if let Some(domain) = qualifiers.domain &&
+	   let Some((low, high)) = interval_limits(domain) {
+		qualifiers.lowlimit = Some(low);
+		qualifiers.uplimit = Some(high);
+		qualifiers.domain = None;
+	}
+	return Ok( (qualifiers, args) );
+
+	fn only_child(element: Element) -> Result<Element> {
+		let children = children_of(element);
+		if children.len() != 1 {
+			bail!("'{}' should have exactly one child:\n{}", name(element), mml_to_string(element));
+		}
+		return Ok(children[0]);
+	}
+}
+
+/// If 'element' is a lambda expression (pragmatic or strict), return the bvars and the body
+fn as_lambda(element: Element) -> Option<(Vec<Element>, Element)> {
+	let children = children_of(element);
+	let is_lambda = match name(element) {
+		"lambda" => true,
+		"bind" => children.first().and_then(|&head| operator_name(head)).is_some_and(|head| head == "lambda"),
+		_ => false,
+	};
+	if !is_lambda {
+		return None;
+	}
+	let start = if name(element) == "bind" {1} else {0};
+	let bvars = children[start..].iter().filter(|&&child| name(child) == "bvar").copied().collect::<Vec<Element>>();
+	let body = children[start..].iter().rfind(|&&child| name(child) != "bvar").copied()?;
+	return Some( (bvars, body) );
+}
+
+/// If 'element' is an interval (pragmatic or strict), return the endpoints
+fn interval_limits(element: Element) -> Option<(Element, Element)> {
+	let children = children_of(element);
+	let args = match name(element) {
+		"interval" => &children[..],
+		"apply" if children.first().and_then(|&head| operator_name(head)).is_some_and(|head| head.starts_with("interval_")) => &children[1..],
+		_ => return None,
+	};
+	if args.len() != 2 {
+		return None;
+	}
+	return Some( (args[0], args[1]) );
+}
+
+/// The variable inside a 'bvar' and its (optional) degree
+fn bvar_parts(bvar: Element) -> Result<(Presentation, Option<Presentation>)> {
+	let children = children_of(bvar);
+	let Some(&var) = children.iter().find(|&&child| name(child) != "degree") else {
+		bail!("'bvar' without a variable:\n{}", mml_to_string(bvar));
+	};
+	let degree = match children.iter().find(|&&child| name(child) == "degree") {
+		Some(&degree) => match children_of(degree).first() {
+			Some(&degree) => Some(convert(degree)?),
+			None => None,
+		},
+		None => None,
+	};
+	return Ok( (convert(var)?, degree) );
+}
+
+fn convert_apply(apply: Element) -> Result<Presentation> {
+	let doc = apply.document();
+	let children = children_of(apply);
+	let Some(&head) = children.first() else {
+		bail!("'{}' has no children", name(apply));
+	};
+	let Some(head_name) = operator_name(head) else {
+		// user defined function (or something like an inverse function) applied to arguments
+		let head = convert(head)?;
+		let args = children[1..].iter().map(|&arg| convert(arg).map(|p| p.element)).collect::<Result<Vec<Element>>>()?;
+		let head = operand(&doc, head, PREC_POWER);
+		let args = fenced(&doc, "(", ")", vec![mrow(&doc, comma_separated(&doc, args))]);
+		return Ok( Presentation::new(mrow(&doc, vec![head, mo(&doc, "\u{2061}"), args]), PREC_APPLY) );
+	};
+	let (qualifiers, args) = split_apply(&head_name, &children[1..])?;
+	let mut args = args.into_iter().map(convert).collect::<Result<Vec<Presentation>>>()?;
+	let head_name = head_name.as_str();
+	let (op_text, form) = match OPERATORS.get(head_name) {
+		Some(&(op_text, form)) => (op_text, form),
+		None => (head_name, Form::ParenFunction),		// unknown csymbol
+	};
+
+	if !qualifiers.bvars.is_empty() || qualifiers.condition.is_some() {
+		match head_name {
+			"int" | "sum" | "product" | "limit" | "diff" | "partialdiff" | "forall" | "exists" | "set" | "lambda" => (),
+			_ => {
+				// something like "union over a set" -- treat it like a large operator
+				return convert_large_op(&doc, op_text, &qualifiers, args);
+			},
+		}
+	}
+
+	match form {
+		Form::Infix(precedence) => {
+			if args.len() == 1 && head_name == "plus" {
+				let arg = operand(&doc, args.remove(0), PREC_POWER);
+				return Ok( Presentation::new(mrow(&doc, vec![mo(&doc, "+"), arg]), PREC_PLUS) );
+			}
+			return Ok( infix(&doc, op_text, precedence, args) );
+		},
+		Form::Relation => {
+			let children = args.into_iter().map(|arg| operand(&doc, arg, PREC_RELATION + 1)).collect::<Vec<Element>>();
+			let mut result = Vec::with_capacity(2 * children.len());
+			for (i, child) in children.into_iter().enumerate() {
+				if i > 0 {
+					result.push(mo(&doc, op_text));
+				}
+				result.push(child);
+			}
+			return Ok( Presentation::new(mrow(&doc, result), PREC_RELATION) );
+		},
+		Form::Function => {
+			if args.len() != 1 {
+				return Ok( paren_function(&doc, leaf(&doc, "mi", op_text), args) );
+			}
+			return Ok( function(&doc, leaf(&doc, "mi", op_text), args.remove(0)) );
+		},
+		Form::ParenFunction => return Ok( paren_function(&doc, leaf(&doc, "mi", op_text), args) ),
+		Form::Fenced(open, close) => {
+			let contents = args.into_iter().map(|arg| arg.element).collect::<Vec<Element>>();
+			return Ok( Presentation::atom(fenced(&doc, open, close, vec![mrow(&doc, comma_separated(&doc, contents))])) );
+		},
+		Form::Special => (),
+	}
+
+	let n_args = args.len();
+	let result = match (head_name, n_args) {
+		("minus", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_TIMES);
+			Presentation::new(mrow(&doc, vec![mo(&doc, "−"), arg]), PREC_PLUS)
+		},
+		("minus", 2) | ("setdiff", 2) => {
+			let right = operand(&doc, args.remove(1), PREC_PLUS + 1);
+			let left = operand(&doc, args.remove(0), PREC_PLUS);
+			Presentation::new(mrow(&doc, vec![left, mo(&doc, op_text), right]), PREC_PLUS)
+		},
+		("times", _) => times(&doc, args),
+		("divide", 2) => {
+			let denominator = args.remove(1).element;
+			let numerator = args.remove(0).element;
+			Presentation::atom(layout(&doc, "mfrac", vec![numerator, denominator]))
+		},
+		("quotient", 2) => {
+			let denominator = args.remove(1).element;
+			let numerator = args.remove(0).element;
+			let fraction = layout(&doc, "mfrac", vec![numerator, denominator]);
+			Presentation::atom(fenced(&doc, "⌊", "⌋", vec![fraction]))
+		},
+		("power", 2) => {
+			let exponent = args.remove(1).element;
+			power(&doc, apply_to_power(&children[1..]), args.remove(0), exponent)?
+		},
+		("exp", 1) => {
+			let exponent = args.remove(0).element;
+			Presentation::new(layout(&doc, "msup", vec![leaf(&doc, "mi", "e"), exponent]), PREC_POWER)
+		},
+		("root", 1) => {
+			let base = args.remove(0).element;
+			match qualifiers.degree {
+				Some(degree) => Presentation::atom(layout(&doc, "mroot", vec![base, convert(degree)?.element])),
+				None => Presentation::atom(layout(&doc, "msqrt", vec![base])),
+			}
+		},
+		("root", 2) => {	// strict form: root(x, n)
+			let index = args.remove(1).element;
+			let base = args.remove(0).element;
+			if crate::xpath_functions::is_leaf(index) && crate::canonicalize::as_text(index) == "2" {
+				Presentation::atom(layout(&doc, "msqrt", vec![base]))
+			} else {
+				Presentation::atom(layout(&doc, "mroot", vec![base, index]))
+			}
+		},
+		("factorial", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			Presentation::new(mrow(&doc, vec![arg, mo(&doc, "!")]), PREC_POSTFIX)
+		},
+		("not", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_NOT);
+			Presentation::new(mrow(&doc, vec![mo(&doc, "¬"), arg]), PREC_NOT)
+		},
+		("log", 1) => {
+			let log = match qualifiers.logbase {
+				Some(base) => layout(&doc, "msub", vec![leaf(&doc, "mi", "log"), convert(base)?.element]),
+				None => leaf(&doc, "mi", "log"),
+			};
+			function(&doc, log, args.remove(0))
+		},
+		("log", 2) => {		// strict form: log(base, x)
+			let arg = args.remove(1);
+			let log = layout(&doc, "msub", vec![leaf(&doc, "mi", "log"), args.remove(0).element]);
+			function(&doc, log, arg)
+		},
+		("divergence" | "curl", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			let op = if head_name == "divergence" {"⋅"} else {"×"};
+			Presentation::new(mrow(&doc, vec![mo(&doc, "∇"), mo(&doc, op), arg]), PREC_FUNCTION)
+		},
+		("grad", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			Presentation::new(mrow(&doc, vec![mo(&doc, "∇"), arg]), PREC_FUNCTION)
+		},
+		("laplacian", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			let nabla_squared = layout(&doc, "msup", vec![mo(&doc, "∇"), leaf(&doc, "mn", "2")]);
+			Presentation::new(mrow(&doc, vec![nabla_squared, arg]), PREC_FUNCTION)
+		},
+		("conjugate", 1) => {
+			let arg = args.remove(0).element;
+			arg.set_attribute_value("arg", "z");
+			let conjugate = layout(&doc, "mover", vec![arg, mo(&doc, "¯")]);
+			conjugate.set_attribute_value("intent", "complex-conjugate($z)");
+			Presentation::new(conjugate, PREC_POWER)
+		},
+		("transpose", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			arg.set_attribute_value("arg", "m");
+			let transpose = layout(&doc, "msup", vec![arg, leaf(&doc, "mi", "T")]);
+			transpose.set_attribute_value("intent", "transpose($m)");
+			Presentation::new(transpose, PREC_POWER)
+		},
+		("inverse", 1) => {
+			let arg = operand(&doc, args.remove(0), PREC_POWER + 1);
+			arg.set_attribute_value("arg", "f");
+			let minus_one = mrow(&doc, vec![mo(&doc, "−"), leaf(&doc, "mn", "1")]);
+			let inverse = layout(&doc, "msup", vec![arg, minus_one]);
+			inverse.set_attribute_value("intent", "inverse-function($f)");
+			Presentation::new(inverse, PREC_POWER)
+		},
+		("selector", n) if n >= 2 => {
+			let base = operand(&doc, args.remove(0), PREC_POWER + 1);
+			let indices = args.into_iter().map(|arg| arg.element).collect::<Vec<Element>>();
+			Presentation::new(layout(&doc, "msub", vec![base, mrow(&doc, comma_separated(&doc, indices))]), PREC_POWER)
+		},
+		("diff", _) | ("partialdiff", _) => derivative(&doc, head_name == "partialdiff", &qualifiers, args)?,
+		("int", _) => integral(&doc, &qualifiers, args)?,
+		("sum" | "product" | "limit", _) => convert_large_op(&doc, op_text, &qualifiers, args)?,
+		("forall" | "exists", _) => {
+			let mut children = vec![mo(&doc, op_text)];
+			match qualifiers.condition {
+				Some(condition) => children.push(convert(condition)?.element),
+				None => children.extend(comma_separated(&doc, bvar_elements(&qualifiers)?)),
+			}
+			if !args.is_empty() {
+				children.push(mo(&doc, ":"));
+				children.push(args.remove(0).element);
+			}
+			Presentation::new(mrow(&doc, children), PREC_IMPLIES)
+		},
+		("interval_oo" | "interval_cc" | "interval_oc" | "interval_co", 2) => {
+			let right = args.remove(1).element;
+			let left = args.remove(0).element;
+			interval(&doc, &head_name["interval_".len()..], left, right)
+		},
+		("set" | "list" | "vector" | "matrix" | "matrixrow" | "lambda", _) => {
+			let container_name = head_name;
+			return convert_container(container_name, apply, &children[1..]);
+		},
+		_ => {
+			let head = convert_operator_name(&doc, head_name);
+			paren_function(&doc, head.element, args)
+		},
+	};
+	return Ok(result);
+}
+
+/// Infix operator such as "+" -- a "+" followed by a negation turns into a "−"
+fn infix<'a>(doc: &Document<'a>, op_text: &str, precedence: u8, args: Vec<Presentation<'a>>) -> Presentation<'a> {
+	let mut children = Vec::with_capacity(2 * args.len());
+	for (i, arg) in args.into_iter().enumerate() {
+		if i > 0 {
+			if op_text == "+" && is_negation(&arg) {
+				// a + (-b) is displayed as a - b
+				let negated = arg.element.children();
+				children.push(mo(doc, "−"));
+				children.push(as_element(negated[1]));
+				continue;
+			}
+			children.push(mo(doc, op_text));
+		}
+		children.push(operand(doc, arg, precedence));
+	}
+	return Presentation::new(mrow(doc, children), precedence);
+
+	fn is_negation(arg: &Presentation) -> bool {
+		let children = arg.element.children();
+		return arg.precedence == PREC_PLUS && name(arg.element) == "mrow" && children.len() == 2 &&
+			children[0].element().is_some_and(|op| name(op) == "mo" && crate::canonicalize::as_text(op) == "−");
+	}
+}
+
+/// Multiplication uses invisible times unless a number follows
+fn times<'a>(doc: &Document<'a>, args: Vec<Presentation<'a>>) -> Presentation<'a> {
+	let n_args = args.len();
+	let mut children = Vec::with_capacity(2 * n_args);
+	for (i, arg) in args.into_iter().enumerate() {
+		// functions such as "sin x" extend to the right, so they need parens unless they are last
+		let min_precedence = if i + 1 < n_args {PREC_FUNCTION + 1} else {PREC_TIMES};
+		let arg = operand(doc, arg, min_precedence);
+		if i > 0 {
+			children.push(mo(doc, if starts_with_number(arg) {"×"} else {"\u{2062}"}));
+		}
+		children.push(arg);
+	}
+	return Presentation::new(mrow(doc, children), PREC_TIMES);
+
+	fn starts_with_number(element: Element) -> bool {
+		let element_name = name(element);
+		if element_name == "mn" {
+			return true;
+		}
+		if element_name == "mrow" || element_name == "msup" || element_name == "msub" {
+			return element.children().first()
+				.and_then(|child| child.element())
+				.is_some_and(starts_with_number);
+		}
+		return false;
+	}
+}
+
+/// Function application such as "sin x" or "sin(x+y)"
+fn function<'a>(doc: &Document<'a>, function_name: Element<'a>, arg: Presentation<'a>) -> Presentation<'a> {
+	if arg.precedence == PREC_ATOM && name(arg.element) != "mfrac" {
+		return Presentation::new(mrow(doc, vec![function_name, mo(doc, "\u{2061}"), arg.element]), PREC_FUNCTION);
+	}
+	let arg = fenced(doc, "(", ")", vec![arg.element]);
+	return Presentation::new(mrow(doc, vec![function_name, mo(doc, "\u{2061}"), arg]), PREC_APPLY);
+}
+
+/// Function application with the args in parens such as "gcd(a,b)"
+fn paren_function<'a>(doc: &Document<'a>, function_name: Element<'a>, args: Vec<Presentation<'a>>) -> Presentation<'a> {
+	let args = args.into_iter().map(|arg| arg.element).collect::<Vec<Element>>();
+	let args = fenced(doc, "(", ")", vec![mrow(doc, comma_separated(doc, args))]);
+	return Presentation::new(mrow(doc, vec![function_name, mo(doc, "\u{2061}"), args]), PREC_APPLY);
+}
+
+/// If the base of power is a trig function (e.g., "sin x"), return the function name and argument so we can generate "sin² x"
+fn apply_to_power<'a>(args: &[Element<'a>]) -> Option<(&'static str, Element<'a>)> {
+	let base = *args.first()?;
+	if name(base) != "apply" {
+		return None;
+	}
+	let children = children_of(base);
+	if children.len() != 2 {
+		return None;
+	}
+	let head_name = operator_name(children[0])?;
+	return match OPERATORS.get(head_name.as_str()) {
+		Some(&(text, Form::Function)) => Some( (text, children[1]) ),
+		_ => None,
+	};
+}
+
+fn power<'a>(doc: &Document<'a>, trig: Option<(&'static str, Element<'a>)>, base: Presentation<'a>, exponent: Element<'a>) -> Result<Presentation<'a>> {
+	if let Some((function_name, arg)) = trig {
+		let is_integer = name(exponent) == "mn" && crate::canonicalize::as_text(exponent).chars().all(|ch| ch.is_ascii_digit());
+		if is_integer {
+			let function_name = layout(doc, "msup", vec![leaf(doc, "mi", function_name), exponent]);
+			return Ok( function(doc, function_name, convert(arg)?) );
+		}
+	}
+	let base = operand(doc, base, PREC_APPLY);
+	return Ok( Presentation::new(layout(doc, "msup", vec![base, exponent]), PREC_POWER) );
+}
+
+/// The presentation of the bound variables
+fn bvar_elements<'a>(qualifiers: &Qualifiers<'a>) -> Result<Vec<Element<'a>>> {
+	return qualifiers.bvars.iter()
+		.map(|&bvar| bvar_parts(bvar).map(|(var, _)| var.element))
+		.collect();
+}
+
+/// "d/dx f" or "∂²/∂x∂y f" -- if there is no bound var, this is "f′"
+fn derivative<'a>(doc: &Document<'a>, is_partial: bool, qualifiers: &Qualifiers<'a>, mut args: Vec<Presentation<'a>>) -> Result<Presentation<'a>> {
+	if args.len() != 1 {
+		bail!("derivative should have one argument (found {})", args.len());
+	}
+	let d = || if is_partial {mo(doc, "∂")} else {differential_d(doc)};
+	if qualifiers.bvars.is_empty() {
+		let function_name = operand(doc, args.remove(0), PREC_APPLY);
+		return Ok( Presentation::new(layout(doc, "msup", vec![function_name, mo(doc, "′")]), PREC_POWER) );
+	}
+	let mut denominator = Vec::new();
+	let mut total_degree = 0;
+	let mut total_degree_is_known = true;
+	for &bvar in &qualifiers.bvars {
+		let (var, degree) = bvar_parts(bvar)?;
+		let var = match degree {
+			None => {
+				total_degree += 1;
+				var.element
+			},
+			Some(degree) => {
+				match crate::xpath_functions::is_leaf(degree.element).then(|| crate::canonicalize::as_text(degree.element).parse::<usize>()) {
+					Some(Ok(n)) => total_degree += n,
+					_ => total_degree_is_known = false,
+				}
+				layout(doc, "msup", vec![var.element, degree.element])
+			},
+		};
+		denominator.push(d());
+		denominator.push(var);
+	}
+	let numerator = if let Some(degree) = qualifiers.degree {
+		layout(doc, "msup", vec![d(), convert(degree)?.element])
+	} else if total_degree == 1 && total_degree_is_known {
+		d()
+	} else if total_degree_is_known {
+		layout(doc, "msup", vec![d(), leaf(doc, "mn", &total_degree.to_string())])
+	} else {
+		bail!("can't determine the total degree of the derivative -- a 'degree' element is needed");
+	};
+	let fraction = layout(doc, "mfrac", vec![numerator, mrow(doc, denominator)]);
+	let body = operand(doc, args.remove(0), PREC_FUNCTION);
+	return Ok( Presentation::new(mrow(doc, vec![fraction, body]), PREC_FUNCTION) );
+}
+
+/// The "d" used in derivatives and integrals
+fn differential_d<'a>(doc: &Document<'a>) -> Element<'a> {
+	let d = leaf(doc, "mi", "d");
+	d.set_attribute_value("mathvariant", "normal");
+	return d;
+}
+
+/// The integral sign with limits followed by the integrand and "dx"
+fn integral<'a>(doc: &Document<'a>, qualifiers: &Qualifiers<'a>, mut args: Vec<Presentation<'a>>) -> Result<Presentation<'a>> {
+	if args.len() != 1 {
+		bail!("integral should have one argument (found {})", args.len());
+	}
+	let mut children = vec![large_op(doc, "∫", qualifiers, false)?];
+	children.push(operand(doc, args.remove(0), PREC_TIMES));
+	for &bvar in &qualifiers.bvars {
+		let (var, _) = bvar_parts(bvar)?;
+		children.push(differential_d(doc));
+		children.push(var.element);
+	}
+	return Ok( Presentation::new(mrow(doc, children), PREC_FUNCTION) );
+}
+
+/// Large operators such as "∑" and "lim" along with their argument
+fn convert_large_op<'a>(doc: &Document<'a>, op_text: &str, qualifiers: &Qualifiers<'a>, mut args: Vec<Presentation<'a>>) -> Result<Presentation<'a>> {
+	if args.len() != 1 {
+		bail!("'{}' should have one argument (found {})", op_text, args.len());
+	}
+	let op = large_op(doc, op_text, qualifiers, true)?;
+	let body = operand(doc, args.remove(0), PREC_TIMES);
+	return Ok( Presentation::new(mrow(doc, vec![op, body]), PREC_FUNCTION) );
+}
+
+/// Generate the operator with its limits
+fn large_op<'a>(doc: &Document<'a>, op_text: &str, qualifiers: &Qualifiers<'a>, show_bvar: bool) -> Result<Element<'a>> {
+	let op = if op_text.chars().all(|ch| ch.is_alphabetic()) {leaf(doc, "mi", op_text)} else {mo(doc, op_text)};
+	let is_limit = op_text == "lim";
+	let bvar = match qualifiers.bvars.first() {
+		Some(&bvar) if show_bvar => Some(bvar_parts(bvar)?.0.element),
+		_ => None,
+	};
+	let lower = match (qualifiers.lowlimit, qualifiers.condition, qualifiers.domain) {
+		(Some(low), _, _) => {
+			let low = convert(low)?.element;
+			match bvar {
+				Some(bvar) => Some(mrow(doc, vec![bvar, mo(doc, if is_limit {"→"} else {"="}), low])),
+				None => Some(low),
+			}
+		},
+		(None, Some(condition), _) => Some(convert(condition)?.element),
+		(None, None, Some(domain)) => {
+			let domain = convert(domain)?.element;
+			match bvar {
+				Some(bvar) => Some(mrow(doc, vec![bvar, mo(doc, "∈"), domain])),
+				None => Some(domain),
+			}
+		},
+		(None, None, None) => bvar,
+	};
+	let upper = match qualifiers.uplimit {
+		Some(high) if !is_limit => Some(convert(high)?.element),
+		_ => None,
+	};
+	let is_integral = op_text == "∫";
+	return Ok( match (lower, upper) {
+		(Some(lower), Some(upper)) => layout(doc, if is_integral {"msubsup"} else {"munderover"}, vec![op, lower, upper]),
+		(Some(lower), None) => layout(doc, if is_integral {"msub"} else {"munder"}, vec![op, lower]),
+		(None, Some(upper)) => layout(doc, if is_integral {"msup"} else {"mover"}, vec![op, upper]),
+		(None, None) => op,
+	} );
+}
+
+fn interval<'a>(doc: &Document<'a>, closure: &str, left: Element<'a>, right: Element<'a>) -> Presentation<'a> {
+	let (open, close, intent) = match closure {
+		"oo" => ("(", ")", "open-interval"),
+		"oc" => ("(", "]", "open-closed-interval"),
+		"co" => ("[", ")", "closed-open-interval"),
+		_ => ("[", "]", "closed-interval"),
+	};
+	left.set_attribute_value("arg", "start");
+	right.set_attribute_value("arg", "end");
+	let result = fenced(doc, open, close, vec![left, mo(doc, ","), right]);
+	result.set_attribute_value("intent", &format!("{intent}($start,$end)"));
+	return Presentation::atom(result);
+}
+
+/// Containers such as 'set' and 'matrix' -- 'children' are the children after any head
+fn convert_container<'a>(container_name: &str, element: Element<'a>, children: &[Element<'a>]) -> Result<Presentation<'a>> {
+	let doc = element.document();
+	if container_name == "piecewise" {
+		return convert_piecewise(&doc, children);
+	}
+	let (qualifiers, args) = split_apply(container_name, children)?;
+	let mut args = args.into_iter().map(convert).collect::<Result<Vec<Presentation>>>()?;
+	let result = match container_name {
+		"set" | "list" => {
+			let (open, close) = if container_name == "set" {("{", "}")} else {("(", ")")};
+			if qualifiers.bvars.is_empty() && qualifiers.condition.is_none() {
+				let contents = args.into_iter().map(|arg| arg.element).collect::<Vec<Element>>();
+				if contents.is_empty() && container_name == "set" {
+					return Ok( Presentation::atom(leaf(&doc, "mi", "∅")) );
+				}
+				Presentation::atom(fenced(&doc, open, close, vec![mrow(&doc, comma_separated(&doc, contents))]))
+			} else {
+				// set builder notation: {x | condition} or {f(x) | condition}
+				let mut lhs = match args.pop() {
+					Some(arg) => arg.element,
+					None => mrow(&doc, comma_separated(&doc, bvar_elements(&qualifiers)?)),
+				};
+				if let Some(domain) = qualifiers.domain {
+					lhs = mrow(&doc, vec![lhs, mo(&doc, "∈"), convert(domain)?.element]);
+				}
+				let mut contents = vec![lhs];
+				if let Some(condition) = qualifiers.condition {
+					contents.push(mo(&doc, "|"));
+					contents.push(convert(condition)?.element);
+				}
+				Presentation::atom(fenced(&doc, open, close, contents))
+			}
+		},
+		"interval" => {
+			if args.len() != 2 {
+				bail!("'interval' should have two children:\n{}", mml_to_string(element));
+			}
+			let closure = match element.attribute_value("closure").unwrap_or("closed") {
+				"open" => "oo",
+				"open-closed" => "oc",
+				"closed-open" => "co",
+				_ => "cc",
+			};
+			let right = args.remove(1).element;
+			let left = args.remove(0).element;
+			interval(&doc, closure, left, right)
+		},
+		"vector" => {
+			let rows = args.into_iter()
+				.map(|arg| layout(&doc, "mtr", vec![layout(&doc, "mtd", vec![arg.element])]))
+				.collect::<Vec<Element>>();
+			Presentation::atom(fenced(&doc, "(", ")", vec![layout(&doc, "mtable", rows)]))
+		},
+		"matrix" => {
+			let rows = args.into_iter().map(|arg| arg.element).collect::<Vec<Element>>();
+			if let Some(row) = rows.iter().find(|&&row| name(row) != "mtr") {
+				bail!("'matrix' children must be 'matrixrow's:\n{}", mml_to_string(*row));
+			}
+			Presentation::atom(fenced(&doc, "(", ")", vec![layout(&doc, "mtable", rows)]))
+		},
+		"matrixrow" => {
+			let cells = args.into_iter()
+				.map(|arg| layout(&doc, "mtd", vec![arg.element]))
+				.collect::<Vec<Element>>();
+			Presentation::atom(layout(&doc, "mtr", cells))
+		},
+		"lambda" => {
+			let Some(body) = args.pop() else {
+				bail!("'lambda' is missing its body:\n{}", mml_to_string(element));
+			};
+			let vars = bvar_elements(&qualifiers)?;
+			let vars = if vars.len() == 1 {vars[0]} else {fenced(&doc, "(", ")", vec![mrow(&doc, comma_separated(&doc, vars))])};
+			Presentation::new(mrow(&doc, vec![vars, mo(&doc, "↦"), body.element]), PREC_IMPLIES)
+		},
+		_ => bail!("Internal error: unknown container '{}'", container_name),
+	};
+	return Ok(result);
+}
+
+/// 'piece's are a value and condition; 'otherwise' is just a value
+fn convert_piecewise<'a>(doc: &Document<'a>, pieces: &[Element<'a>]) -> Result<Presentation<'a>> {
+	let rows = pieces.iter().map(|&piece| {
+		let parts = children_of(piece).into_iter().map(convert).collect::<Result<Vec<Presentation>>>()?;
+		let mut cells = parts.into_iter()
+			.map(|part| layout(doc, "mtd", vec![part.element]))
+			.collect::<Vec<Element>>();
+		if name(piece) == "otherwise" {
+			cells.push(layout(doc, "mtd", vec![leaf(doc, "mtext", "otherwise")]));
+		}
+		Ok( layout(doc, "mtr", cells) )
+	}).collect::<Result<Vec<Element>>>()?;
+	return Ok( Presentation::atom(mrow(doc, vec![mo(doc, "{"), layout(doc, "mtable", rows)])) );
+}
+
+
+#[cfg(test)]
+mod tests {
+	#[allow(unused_imports)]
+	use crate::init_logger;
+	use crate::are_strs_canonically_equal;
+
+	#[test]
+	fn polynomial() {
+		let content = "<math><apply><plus/>
+				<apply><power/><ci>x</ci><cn>2</cn></apply>
+				<apply><times/><cn>2</cn><ci>x</ci></apply>
+				<apply><minus/><cn>1</cn></apply>
+			</apply></math>";
+		let target = "<math><mrow>
+				<msup><mi>x</mi><mn>2</mn></msup><mo>+</mo>
+				<mrow><mn>2</mn><mo>&#x2062;</mo><mi>x</mi></mrow><mo>-</mo><mn>1</mn>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn strict_csymbols() {
+		let content = "<math><apply><csymbol cd='relation1'>eq</csymbol>
+				<apply><csymbol cd='arith1'>times</csymbol><ci>a</ci>
+					<apply><csymbol cd='arith1'>plus</csymbol><ci>b</ci><ci>c</ci></apply>
+				</apply>
+				<apply><csymbol cd='arith1'>divide</csymbol><cn type='integer'>1</cn><cn type='integer'>2</cn></apply>
+			</apply></math>";
+		let target = "<math><mrow>
+				<mrow><mi>a</mi><mo>&#x2062;</mo><mrow><mo>(</mo><mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow><mo>)</mo></mrow></mrow>
+				<mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn function_application() {
+		let content = "<math><apply><sin/><apply><plus/><ci>x</ci><cn>1</cn></apply></apply></math>";
+		let target = "<math><mrow>
+				<mi>sin</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mo>)</mo></mrow>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn rational_cn() {
+		let content = "<math><cn type='rational'>3<sep/>4</cn></math>";
+		let target = "<math><mfrac><mn>3</mn><mn>4</mn></mfrac></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn strict_definite_integral() {
+		let content = "<math><apply><csymbol cd='calculus1'>defint</csymbol>
+				<apply><csymbol cd='interval1'>interval</csymbol><cn>0</cn><cn>1</cn></apply>
+				<bind><csymbol cd='fns1'>lambda</csymbol><bvar><ci>x</ci></bvar>
+					<apply><csymbol cd='arith1'>power</csymbol><ci>x</ci><cn>2</cn></apply>
+				</bind>
+			</apply></math>";
+		let target = "<math><mrow>
+				<msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup>
+				<mrow data-changed='added'>
+					<msup><mi>x</mi><mn>2</mn></msup><mo data-changed='added'>&#x2062;</mo><mi mathvariant='normal'>d</mi><mo data-changed='added'>&#x2062;</mo><mi>x</mi>
+				</mrow>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn open_interval_intent() {
+		let content = "<math><interval closure='open'><ci>a</ci><ci>b</ci></interval></math>";
+		let target = "<math><mrow intent='open-interval($start,$end)'>
+				<mo>(</mo><mrow data-changed='added'><mi arg='start'>a</mi><mo>,</mo><mi arg='end'>b</mi></mrow><mo>)</mo>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &[]));
+	}
+
+	#[test]
+	fn semantics_content_only() {
+		let content = "<math><semantics>
+				<apply><abs/><ci>x</ci></apply>
+				<annotation encoding='application/x-tex'>|x|</annotation>
+			</semantics></math>";
+		let target = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &["data-annotation-xml-MathML-Content", "data-annotation-application_slash_x-tex"]));
+	}
+
+	#[test]
+	fn semantics_copies_intent() {
+		let content = "<math><semantics>
+				<mrow><mo>(</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>)</mo></mrow>
+				<annotation-xml encoding='MathML-Content'><interval closure='open'><cn>0</cn><cn>1</cn></interval></annotation-xml>
+			</semantics></math>";
+		let target = "<math><mrow intent='open-interval($start,$end)'>
+				<mo>(</mo><mrow data-changed='added'><mn arg='start'>0</mn><mo>,</mo><mn arg='end'>1</mn></mrow><mo>)</mo>
+			</mrow></math>";
+		assert!(are_strs_canonically_equal(content, target, &["data-annotation-xml-MathML-Content"]));
+	}
+}
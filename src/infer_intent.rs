@@ -13,9 +13,10 @@ use crate::errors::*;
 use std::fmt;
 use std::sync::LazyLock;
 use crate::pretty_print::mml_to_string;
-use crate::xpath_functions::is_leaf;
+use crate::xpath_functions::{is_leaf, IsNode};
+use std::collections::HashMap;
 use regex::Regex;
-use phf::phf_set;
+use phf::{phf_map, phf_set};
 use log::{debug, error, warn};
 
 const IMPLICIT_FUNCTION_NAME: &str = "apply-function";
@@ -616,6 +617,152 @@ fn find_arg<'r, 'c, 's:'c, 'm:'c>(
     return Ok(None);               // not present
 }
 
+/// The intent concepts that are written out by [`add_intent_attributes`] and the MathML 4 (core or open) name they are written as.
+/// Other concepts (e.g., 'skip-super' or 'modified-variable') are internal to MathCAT's rules and are not exported,
+///   nor are concepts such as 'power' that are just the default meaning of the element.
+static EXPORTED_CONCEPTS: phf::Map<&str, &str> = phf_map! {
+    "absolute-value" => "absolute-value", "binomial" => "binomial", "determinant" => "determinant",
+    "closed-interval" => "closed-interval", "open-interval" => "open-interval",
+    "closed-open-interval" => "closed-open-interval", "open-closed-interval" => "open-closed-interval",
+    "cross-product" => "cross-product", "dot-product" => "dot-product", "gradient" => "gradient", "laplacian" => "laplacian",
+    "real-part" => "real-part", "imaginary-part" => "imaginary-part", "transpose" => "transpose", "norm" => "norm",
+    "line-segment" => "line-segment", "ray" => "ray",
+};
+
+/// The intent properties that are written out by [`add_intent_attributes`] (the MathML 4 core properties)
+static EXPORTED_PROPERTIES: phf::Set<&str> = phf_set! {
+    "function", "infix", "prefix", "postfix", "silent",
+};
+
+/// Write the (inferred) intent tree back onto the canonical MathML it came from as MathML 4 `intent` and `arg` attributes.
+/// 
+/// Nodes in the intent tree keep the `id` of the MathML element they were built from, so each intent concept is put on that element
+///   and the children of the concept become `$arg` references to the corresponding descendants.
+/// Only the concepts in [`EXPORTED_CONCEPTS`] and the properties in [`EXPORTED_PROPERTIES`] are written out.
+/// Elements that already have an `intent` are left alone. If a concept can't be expressed in terms of the descendants of its
+///   element (e.g., a child was restructured by the rules), no `intent` is written for it and inference will rebuild it.
+pub fn add_intent_attributes(mathml: Element, intent: Element) {
+    let mut id_map = HashMap::new();
+    collect_ids(mathml, &mut id_map);
+    add_intent_attrs(intent, &id_map);
+
+    fn collect_ids<'a>(mathml: Element<'a>, id_map: &mut HashMap<String, Element<'a>>) {
+        if let Some(id) = mathml.attribute_value("id") {
+            id_map.insert(id.to_string(), mathml);
+        }
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                collect_ids(child, id_map);
+            }
+        }
+    }
+
+    fn add_intent_attrs(intent: Element, id_map: &HashMap<String, Element>) {
+        if is_intent_concept(intent) &&
+           let Some(mathml) = intent.attribute_value("id").and_then(|id| id_map.get(id)) &&
+           mathml.attribute_value(INTENT_ATTR).is_none() {
+            let mut args = vec![];
+            if let Some(intent_str) = intent_expression(intent, *mathml, id_map, &mut args) {
+                let mut used_names = vec![];
+                // the element's own 'arg' (if any) belongs to the enclosing intent, so only the descendants are checked
+                for child in mathml.children() {
+                    if let Some(child) = child.element() {
+                        collect_arg_names(child, &mut used_names);
+                    }
+                }
+                let mut n_generated = 0;
+                for arg in args {
+                    if arg.attribute_value("arg").is_none() {
+                        let arg_name = loop {
+                            n_generated += 1;
+                            let arg_name = format!("a{n_generated}");
+                            if !used_names.contains(&arg_name) {
+                                break arg_name;
+                            }
+                        };
+                        arg.set_attribute_value("arg", &arg_name);
+                    }
+                }
+                // the argument names weren't known when the string was built, so the placeholders are filled in now
+                let intent_str = ARG_PLACEHOLDER.replace_all(&intent_str, |cap: &regex::Captures| {
+                    let id = &cap[1];
+                    format!("${}", id_map[id].attribute_value("arg").unwrap())
+                });
+                mathml.set_attribute_value(INTENT_ATTR, &intent_str);
+            }
+        }
+
+        for child in intent.children() {
+            if let Some(child) = child.element() {
+                add_intent_attrs(child, id_map);
+            }
+        }
+    }
+
+    /// An intent concept is something that isn't just a (possibly cleaned up) copy of a MathML element.
+    /// Concepts wrapping text (e.g., 'chemical-element') can't be written as an `intent` without losing the text.
+    fn is_intent_concept(intent: Element) -> bool {
+        return !IsNode::is_mathml(intent) && intent.attribute_value(MATHML_FROM_NAME_ATTR).is_some() &&
+               intent.children().iter().all(|child| child.element().is_some());
+    }
+
+    /// Returns the `intent` value for 'intent' with '$\u{0}id\u{0}' placeholders for the args (pushed onto 'args').
+    /// Returns None if the concept can't be expressed as an `intent` value on 'mathml'.
+    fn intent_expression<'a>(intent: Element, mathml: Element<'a>, id_map: &HashMap<String, Element<'a>>, args: &mut Vec<Element<'a>>) -> Option<String> {
+        let mut result = EXPORTED_CONCEPTS.get(name(intent))?.to_string();
+        for property in intent.attribute_value(INTENT_PROPERTY).unwrap_or_default().split(':') {
+            if EXPORTED_PROPERTIES.contains(property) {
+                result.push(':');
+                result.push_str(property);
+            }
+        }
+
+        let children: Vec<Element> = intent.children().iter().filter_map(|child| child.element()).collect();
+        if children.is_empty() {
+            return Some(result);
+        }
+        let mut arguments = Vec::with_capacity(children.len());
+        for child in children {
+            if let Some(arg) = child.attribute_value("id").and_then(|id| id_map.get(id)) &&
+               crate::xpath_functions::is_ancestor(mathml, *arg) {
+                if !args.contains(arg) {
+                    args.push(*arg);
+                }
+                arguments.push(format!("$\u{0}{}\u{0}", child.attribute_value("id").unwrap()));
+            } else if is_leaf(child) && child.attribute_value("id").is_none_or(|id| !id_map.contains_key(id)) {
+                // a made-up literal such as the 'a' in 'f($x, a)'
+                let text = as_text(child);
+                if !(NUMBER.find(text).is_some_and(|m| m.end() == text.len()) ||
+                     CONCEPT_OR_LITERAL.find(text).is_some_and(|m| m.end() == text.len())) {
+                    return None;
+                }
+                arguments.push(text.to_string());
+            } else if is_intent_concept(child) && !child.attribute_value("id").is_some_and(|id| id_map.contains_key(id)) {
+                arguments.push(intent_expression(child, mathml, id_map, args)?);
+            } else {
+                return None;
+            }
+        }
+        result.push('(');
+        result.push_str(&arguments.join(","));
+        result.push(')');
+        return Some(result);
+    }
+
+    fn collect_arg_names(mathml: Element, names: &mut Vec<String>) {
+        if let Some(arg) = mathml.attribute_value("arg") {
+            names.push(arg.to_string());
+        }
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                collect_arg_names(child, names);
+            }
+        }
+    }
+}
+
+static ARG_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new("\\$\u{0}([^\u{0}]*)\u{0}").unwrap());
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
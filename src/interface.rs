@@ -7,7 +7,7 @@ use std::sync::LazyLock;
 
 use crate::canonicalize::{as_text, create_mathml_element};
 use crate::errors::*;
-use phf::phf_map;
+use phf::{phf_map, phf_set};
 use regex::{Captures, Regex};
 use sxd_document::dom::*;
 use sxd_document::parser;
@@ -49,6 +49,7 @@ fn cleanup_mathml(mathml: Element) -> Result<Element> {
     trim_element(mathml, false);
     let mathml = crate::canonicalize::canonicalize(mathml)?;
     let mathml = add_ids(mathml);
+    crate::canonicalize::finish_repair_report(mathml);
     return Ok(mathml);
 }
 
@@ -88,15 +89,10 @@ pub fn get_version() -> String {
 /// This will override any previous MathML that was set.
 /// This returns canonical MathML with 'id's set on any node that doesn't have an id.
 /// The ids can be used for sync highlighting if the `Bookmark` API preference is true.
+/// Content MathML (strict or pragmatic) is converted to presentation MathML, as is the content branch of a `semantics`
+///   element that doesn't have a presentation branch.
 pub fn set_mathml(mathml_str: impl AsRef<str>) -> Result<String> {
     enable_logs();
-    // if these are present when resent to MathJaX, MathJaX crashes (https://github.com/mathjax/MathJax/issues/2822)
-    static MATHJAX_V2: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"class *= *['"]MJX-.*?['"]"#).unwrap());
-    static MATHJAX_V3: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"class *= *['"]data-mjx-.*?['"]"#).unwrap());
-    static NAMESPACE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"xmlns:[[:alpha:]]+"#).unwrap()); // very limited namespace prefix match
-    static PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(</?)[[:alpha:]]+:"#).unwrap()); // very limited namespace prefix match
-    static HTML_ENTITIES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"&([a-zA-Z]+?);"#).unwrap());
-
     NAVIGATION_STATE.with(|nav_stack| {
         nav_stack.borrow_mut().reset();
     });
@@ -105,39 +101,8 @@ pub fn set_mathml(mathml_str: impl AsRef<str>) -> Result<String> {
     // This call reads all of them for the current preferences, but that's ok since they will likely be used
     crate::speech::SPEECH_RULES.with(|rules| rules.borrow_mut().read_files())?;
 
-    let mathml_str = mathml_str.as_ref();
     return MATHML_INSTANCE.with(|old_package| {
-        static HTML_ENTITIES_MAPPING: phf::Map<&str, &str> = include!("entities.in");
-
-        let mut error_message = "".to_string(); // can't return a result inside the replace_all, so we do this hack of setting the message and then returning the error
-                                                // need to deal with character data and convert to something the parser knows
-        let mathml_str =
-            HTML_ENTITIES.replace_all(mathml_str, |cap: &Captures| match HTML_ENTITIES_MAPPING.get(&cap[1]) {
-                None => {
-                    error_message = format!("No entity named '{}'", &cap[0]);
-                    cap[0].to_string()
-                }
-                Some(&ch) => ch.to_string(),
-            });
-
-        if !error_message.is_empty() {
-            bail!(error_message);
-        }
-        let mathml_str = MATHJAX_V2.replace_all(&mathml_str, "");
-        let mathml_str = MATHJAX_V3.replace_all(&mathml_str, "");
-
-        // the speech rules use the xpath "name" function and that includes the prefix
-        // getting rid of the prefix properly probably involves a recursive replacement in the tree
-        // if the prefix is used, it is almost certainly something like "m" or "mml", so this cheat will work.
-        let mathml_str = NAMESPACE_DECL.replace(&mathml_str, "xmlns"); // do this before the PREFIX replace!
-        let mathml_str = PREFIX.replace_all(&mathml_str, "$1");
-
-        let new_package = parser::parse(&mathml_str);
-        if let Err(e) = new_package {
-            bail!("Invalid MathML input:\n{}\nError is: {}", &mathml_str, &e.to_string());
-        }
-
-        let new_package = new_package.unwrap();
+        let new_package = parse_mathml_string(mathml_str.as_ref())?;
         let mathml = get_element(&new_package);
         let mathml = cleanup_mathml(mathml)?;
         let mathml_string = mml_to_string(mathml);
@@ -147,6 +112,84 @@ pub fn set_mathml(mathml_str: impl AsRef<str>) -> Result<String> {
     });
 }
 
+/// Parse the MathML string after replacing HTML entities and removing things (MathJax classes, namespace prefixes) that cause problems
+fn parse_mathml_string(mathml_str: &str) -> Result<Package> {
+    // if these are present when resent to MathJaX, MathJaX crashes (https://github.com/mathjax/MathJax/issues/2822)
+    static MATHJAX_V2: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"class *= *['"]MJX-.*?['"]"#).unwrap());
+    static MATHJAX_V3: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"class *= *['"]data-mjx-.*?['"]"#).unwrap());
+    static NAMESPACE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"xmlns:[[:alpha:]]+"#).unwrap()); // very limited namespace prefix match
+    static PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(</?)[[:alpha:]]+:"#).unwrap()); // very limited namespace prefix match
+    static HTML_ENTITIES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"&([a-zA-Z]+?);"#).unwrap());
+    static HTML_ENTITIES_MAPPING: phf::Map<&str, &str> = include!("entities.in");
+
+    let mut error_message = "".to_string(); // can't return a result inside the replace_all, so we do this hack of setting the message and then returning the error
+                                            // need to deal with character data and convert to something the parser knows
+    let mathml_str =
+        HTML_ENTITIES.replace_all(mathml_str, |cap: &Captures| match HTML_ENTITIES_MAPPING.get(&cap[1]) {
+            None => {
+                error_message = format!("No entity named '{}'", &cap[0]);
+                cap[0].to_string()
+            }
+            Some(&ch) => ch.to_string(),
+        });
+
+    if !error_message.is_empty() {
+        bail!(error_message);
+    }
+    let mathml_str = MATHJAX_V2.replace_all(&mathml_str, "");
+    let mathml_str = MATHJAX_V3.replace_all(&mathml_str, "");
+
+    // the speech rules use the xpath "name" function and that includes the prefix
+    // getting rid of the prefix properly probably involves a recursive replacement in the tree
+    // if the prefix is used, it is almost certainly something like "m" or "mml", so this cheat will work.
+    let mathml_str = NAMESPACE_DECL.replace(&mathml_str, "xmlns"); // do this before the PREFIX replace!
+    let mathml_str = PREFIX.replace_all(&mathml_str, "$1");
+
+    return match parser::parse(&mathml_str) {
+        Ok(package) => Ok(package),
+        Err(e) => bail!("Invalid MathML input:\n{}\nError is: {}", &mathml_str, &e.to_string()),
+    };
+}
+
+/// Set the math to speak/braille/navigate from a LaTeX string (e.g., `\\frac{1}{2}` or `$x^2$`).
+/// The LaTeX is converted to MathML, which is then handled as if it were passed to [`set_mathml`].
+/// Along with the standard math commands, the `amsmath` environments (`matrix`, `cases`, `aligned`, ...)
+///   and mhchem's `\\ce{...}` are supported.
+/// This returns the same canonical MathML that [`set_mathml`] returns.
+pub fn set_latex(latex: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    let mathml = crate::latex::latex_to_mathml(latex.as_ref())?;
+    return set_mathml(mathml);
+}
+
+/// Set the math from an AsciiMath string (e.g., `x^2/(a+b)`) instead of MathML.
+/// The AsciiMath is converted to MathML, which is then handled as if it were passed to [`set_mathml`].
+/// This returns the same canonical MathML that [`set_mathml`] returns.
+pub fn set_asciimath(asciimath: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    let mathml = crate::asciimath::asciimath_to_mathml(asciimath.as_ref())?;
+    return set_mathml(mathml);
+}
+
+/// Set the math from a UnicodeMath (Microsoft Office linear format) string (e.g., `(a+b)/c`) instead of MathML.
+/// The UnicodeMath is converted to MathML, which is then handled as if it were passed to [`set_mathml`].
+/// This returns the same canonical MathML that [`set_mathml`] returns.
+pub fn set_unicodemath(unicodemath: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    let mathml = crate::unicodemath::unicodemath_to_mathml(unicodemath.as_ref())?;
+    return set_mathml(mathml);
+}
+
+/// Set the math from Office Math Markup Language (the `m:oMath` or `m:oMathPara` XML used for equations in `.docx` files).
+/// The OMML is converted to MathML, which is then handled as if it were passed to [`set_mathml`].
+/// Namespace declarations are not required (equation XML can be cut directly out of `word/document.xml`).
+/// This returns the same canonical MathML that [`set_mathml`] returns.
+pub fn set_omml(omml: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    let mathml = crate::omml::omml_to_mathml(omml.as_ref())?;
+    return set_mathml(mathml);
+}
+
 /// Get the spoken text of the MathML that was set.
 /// The speech takes into account any AT or user preferences.
 pub fn get_spoken_text() -> Result<String> {
@@ -167,7 +210,7 @@ pub fn get_spoken_text() -> Result<String> {
 
 /// Get the spoken text for an overview of the MathML that was set.
 /// The speech takes into account any AT or user preferences.
-/// Note: this implementation for is currently minimal and should not be used.
+/// The overview is limited to "OverviewWordBudget" words by replacing sub-expressions with their category names.
 pub fn get_overview_text() -> Result<String> {
     enable_logs();
     // use std::time::{Instant};
@@ -181,6 +224,106 @@ pub fn get_overview_text() -> Result<String> {
     });
 }
 
+/// Get the canonical MathML that was set (the same MathML that [`set_mathml`] returns) with the intent MathCAT infers
+///   written onto it as MathML 4 `intent` and `arg` attributes.
+/// Only MathML 4 core/open concepts and properties are written; the attributes MathCAT added (e.g., `data-changed` and the `id`s it added) are removed.
+/// The author's attributes, including their `data-*` attributes, are kept.
+/// Existing `intent` attributes are kept. Feeding the result back to [`set_mathml`] produces the same speech.
+pub fn get_mathml_with_intent() -> Result<String> {
+    enable_logs();
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let new_package = Package::new();
+        let intent = crate::speech::intent_from_mathml(mathml, new_package.as_document())?;
+        let annotated_mathml = copy_mathml(mathml);
+        crate::infer_intent::add_intent_attributes(annotated_mathml, intent);
+        remove_internal_attrs(annotated_mathml);
+        return Ok(mml_to_string(annotated_mathml));
+    });
+
+    /// The attributes MathCAT adds while cleaning up and analyzing the MathML
+    static INTERNAL_ATTRS: phf::Set<&str> = phf_set! {
+        "data-acts_as_operator", "data-added", "data-changed", "data-chemical-bond", "data-empty-in-2D",
+        "data-following-space-width", "data-from-mathml", "data-function-guess", "data-function-likelihood",
+        "data-id-added", "data-id-offset", "data-intent-property", "data-maybe-chemistry", "data-mathcat-repair",
+        "data-merged", "data-nemeth-frac-level", "data-number", "data-overview-collapsed", "data-previous-space-width",
+        "data-pseudo-script", "data-roman-numeral", "data-space-after", "data-split", "data-unicode", "data-was-mo", "data-width",
+    };
+    /// The prefixes of the names of other attributes MathCAT adds (chemistry and the annotations moved into attributes)
+    const INTERNAL_ATTR_PREFIXES: [&str; 2] = ["data-chem-", "data-annotation"];
+
+    fn remove_internal_attrs(mathml: Element) {
+        if mathml.attribute_value("data-id-added") == Some("true") {
+            mathml.remove_attribute("id");
+        }
+        for attr in mathml.attributes() {
+            let attr_name = attr.name().local_part();
+            if INTERNAL_ATTRS.contains(attr_name) || INTERNAL_ATTR_PREFIXES.iter().any(|prefix| attr_name.starts_with(prefix)) {
+                mathml.remove_attribute(attr.name());
+            }
+        }
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                remove_internal_attrs(child);
+            }
+        }
+    }
+}
+
+pub use crate::canonicalize::CanonicalizationRepair;
+
+/// Get the repairs that were made to the MathML that was set when it was converted to canonical MathML.
+/// The repairs are only recorded if the `CanonicalizationReport` preference is `true` when [`set_mathml`] is called;
+///   otherwise, the list is empty.
+/// The kinds of repairs are:
+/// * "missing-math" -- a `math` element was added around the MathML
+/// * "mfenced" -- an `mfenced` was converted to an `mrow` with `mo`s for the fences and separators
+/// * "msubsup" -- an `msubsup` with an empty script was converted to an `msub`/`msup` (or just the base)
+/// * "merged-mi" -- a sequence of `mi`s was merged into a single `mi` (e.g., a function name or a word)
+/// * "split-mi" -- an `mi` with several letters was split into separate `mi`s (e.g., the points in ∠ABC)
+/// * "merged-number" -- digits and separators in several elements were merged into a single `mn`
+/// * "split-number" -- an `mn` was split because it included a minus sign or a degree sign, etc.
+/// * "chemistry" -- the MathML was guessed to be a chemical formula or equation
+pub fn get_canonicalization_report() -> Result<Vec<CanonicalizationRepair>> {
+    return Ok( crate::canonicalize::get_repair_report() );
+}
+
+pub use crate::canonicalize::SeparatorDecision;
+
+/// Get the decimal and block separators that were used when the MathML that was set was converted to canonical MathML.
+/// This is most useful when the `DecimalSeparator` preference is "Detect" -- if the decision is wrong,
+///   set `DecimalSeparator` to "." or "," and call [`set_mathml`] again.
+/// Detected separators are only used for that MathML; the `DecimalSeparators` and `BlockSeparators` preferences are not changed.
+pub fn get_separator_decision() -> Result<SeparatorDecision> {
+    return crate::canonicalize::get_separator_decision()
+        .ok_or_else(|| anyhow!("get_separator_decision: no MathML has been set"));
+}
+
+pub use crate::lint::LintDiagnostic;
+
+/// Check MathML for markup that degrades speech or braille (e.g., before publishing it).
+/// The checks use the same heuristics that are used to clean up the MathML when [`set_mathml`] is called.
+/// The math that was set by [`set_mathml`] is not changed.
+/// The diagnostics are in document order. The kinds of diagnostics are:
+/// * "unknown-multi-letter-mi" -- an `mi` with several letters that isn't a known function name or word
+/// * "split-mi" -- the letters of a function name or word are in separate `mi`s
+/// * "missing-function-application" -- a function name isn't followed by an invisible function application (U+2061)
+/// * "missing-invisible-times" -- there is no invisible times (U+2062) between adjacent operands
+/// * "split-number" -- the digits of a number are split across several `mn`s (or other elements)
+/// * "mn-with-operator" -- an `mn` includes a non-digit such as a minus sign or a degree sign
+/// * "mtext-operator" -- an `mtext` is used for an operator
+/// * "ambiguous-vertical-bar" -- there are several vertical bars in an `mrow` and which ones are paired must be guessed
+/// * "deprecated-mfenced" -- `mfenced` is deprecated; an `mrow` with `mo`s for the fences and separators should be used
+pub fn lint_mathml(mathml_str: impl AsRef<str>) -> Result<Vec<LintDiagnostic>> {
+    enable_logs();
+    // the heuristics need the definition files
+    crate::speech::SPEECH_RULES.with(|rules| rules.borrow_mut().read_files())?;
+    let package = parse_mathml_string(mathml_str.as_ref())?;
+    let mathml = get_element(&package);
+    return crate::lint::lint_mathml(mathml);
+}
+
 /// Get the value of the named preference.
 /// None is returned if `name` is not a known preference.
 pub fn get_preference(name: impl AsRef<str>) -> Result<String> {
@@ -214,6 +357,7 @@ pub fn get_preference(name: impl AsRef<str>) -> Result<String> {
 /// * Voice -- set a voice to use (not implemented)
 /// * Gender -- set pick any voice of the given gender (not implemented)
 /// * Bookmark -- set to `true` if a `mark`/`bookmark` should be part of the returned speech (used for sync highlighting)
+/// * CanonicalizationReport -- set to `true` to record the repairs made to the MathML (see [`get_canonicalization_report`])
 ///
 /// Important: both the preference name and value are case-sensitive
 ///
@@ -251,6 +395,9 @@ pub fn set_preference(name: impl AsRef<str>, value: impl AsRef<str>) -> Result<(
             bail!("'LanguageAuto' can not have the value 'Auto'");
         }
     }
+    if name == "DisabledHeuristics" {
+        crate::canonicalize::check_heuristic_names(&value)?;
+    }
 
     crate::speech::SPEECH_RULES.with(|rules| {
         let rules = rules.borrow_mut();
@@ -274,7 +421,7 @@ pub fn set_preference(name: impl AsRef<str>, value: impl AsRef<str>) -> Result<(
             pref_manager.set_api_boolean_pref(name, value.to_lowercase() == "true");
         } else {
             match name {
-                "Pitch" | "Rate" | "Volume" | "CapitalLetters_Pitch" | "MathRate" | "PauseFactor" => {
+                "Pitch" | "Rate" | "Volume" | "CapitalLetters_Pitch" | "MathRate" | "PauseFactor" | "OverviewWordBudget" | "NavHistoryCount" => {
                     pref_manager.set_api_float_pref(name, to_float(name, &value)?)
                 }
                 _ => {
@@ -367,6 +514,7 @@ pub fn get_navigation_braille() -> Result<String> {
 
 /// Given a key code along with the modifier keys, the current node is moved accordingly (or value reported in some cases).
 /// `key` is the [keycode](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode#constants_for_keycode_value) for the key (in JavaScript, `ev.key_code`)
+/// The command for the key press comes from the key binding scheme set by the `KeyBindings` preference (see `Rules/keybindings.yaml`).
 /// The spoken text for the new current node is returned.
 pub fn do_navigate_keypress(
     key: usize,
@@ -382,6 +530,19 @@ pub fn do_navigate_keypress(
     });
 }
 
+/// Given a touch gesture such as "SwipeLeft", "TwoFingerSwipeUp", or "DoubleTap", the current node is moved accordingly (or value reported in some cases).
+/// This is meant for mobile apps where the screen reader delivers gestures instead of key presses.
+/// The command for the gesture comes from the gesture scheme set by the `GestureBindings` preference (see `Rules/gestures.yaml`).
+/// The spoken text for the new current node is returned.
+pub fn do_navigate_gesture(gesture: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return do_mathml_navigate_gesture(mathml, gesture.as_ref());
+    });
+}
+
 /// Given a navigation command, the current node is moved accordingly.
 /// This is a higher level interface than `do_navigate_keypress` for applications that want to interpret the keys themselves.
 /// The valid commands are:
@@ -391,14 +552,14 @@ pub fn do_navigate_keypress(
 ///   `MoveCellPrevious`, `MoveCellNext`, `MoveCellUp`, `MoveCellDown`, `MoveColumnStart`, `MoveColumnEnd`
 /// * Moving into children or out to parents:
 ///   `ZoomIn`, `ZoomOut`, `ZoomOutAll`, `ZoomInAll`
-/// * Undo the last movement command:
-///   `MoveLastLocation`
+/// * Undo the last movement command (and redo what was undone):
+///   `MoveLastLocation`, `MoveNextLocation`
 /// * Read commands (standard speech):
 ///   `ReadPrevious`, `ReadNext`, `ReadCurrent`, `ReadCellCurrent`, `ReadStart`, `ReadEnd`, `ReadLineStart`, `ReadLineEnd`
 /// * Describe commands (overview):
 ///   `DescribePrevious`, `DescribeNext`, `DescribeCurrent`
-/// * Location information:
-///   `WhereAmI`, `WhereAmIAll`
+/// * Location information (`ReadHistory` speaks the last `NavHistoryCount` positions visited):
+///   `WhereAmI`, `WhereAmIAll`, `ReadHistory`
 /// * Change navigation modes (circle up/down):
 ///   `ToggleZoomLockUp`, `ToggleZoomLockDown`
 /// * Speak the current navigation mode
@@ -414,6 +575,15 @@ pub fn do_navigate_keypress(
 /// * Moving:
 ///   `MoveTo0`, `MoveTo1`, `MoveTo2`, `MoveTo3`, `MoveTo4`, `MoveTo5`, `MoveTo6`, `MoveTo7`, `MoveTo8`, `MoveTo9`
 ///
+/// Search (see [`do_navigate_find`] to set what to search for):
+///   `FindNext`, `FindPrevious`
+///
+/// Tables and columnar math (`mstack`, `mlongdiv`) (see [`do_navigate_move_to`] to set the row/column number):
+///   `ReadRow`, `ReadColumn`, `ReadRowHeader`, `ReadColumnHeader`, `MoveToRow`, `MoveToColumn`
+///
+/// Jump to the next/previous relation, term (of a sum), fraction, root, power, function application, table, or chemical formula:
+///   `MoveNextRelation`, `MovePreviousRelation`, `MoveNextTerm`, `MovePreviousTerm`, `MoveNextFraction`, `MovePreviousFraction`, `MoveNextRoot`, `MovePreviousRoot`, `MoveNextPower`, `MovePreviousPower`, `MoveNextFunction`, `MovePreviousFunction`, `MoveNextTable`, `MovePreviousTable`, `MoveNextChemistry`, `MovePreviousChemistry`
+///
 /// When done with Navigation, call with `Exit`
 pub fn do_navigate_command(command: impl AsRef<str>) -> Result<String> {
     enable_logs();
@@ -429,6 +599,81 @@ pub fn do_navigate_command(command: impl AsRef<str>) -> Result<String> {
     });
 }
 
+/// The result of a navigation command -- everything a UI needs to update the speech and highlighting in one call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationResult {
+    /// The spoken text (the same as [`do_navigate_command`] returns)
+    pub speech: String,
+    /// The `id` of the new current (navigation) node
+    pub id: String,
+    /// The offset in characters for a leaf node (see [`get_navigation_mathml_id`])
+    pub offset: usize,
+    /// The braille cells to highlight (the same as [`get_braille_position`] returns)
+    pub braille_start: usize,
+    pub braille_end: usize,
+    /// The number of ancestors of the current node (0 when the whole expression is the current node)
+    pub zoom_depth: usize,
+    /// `true` if the command tried to move but couldn't (e.g., "no next item"), so the current node didn't change
+    pub at_boundary: bool,
+}
+
+/// The same as [`do_navigate_command`], but the result also contains the new current node, the braille highlight range, etc.
+/// This saves calling [`get_navigation_mathml_id`] and [`get_braille_position`] after each command.
+pub fn do_navigate_command_with_result(command: impl AsRef<str>) -> Result<NavigationResult> {
+    enable_logs();
+    let speech = do_navigate_command(command)?;
+    let (id, offset) = get_navigation_mathml_id()?;
+    let (braille_start, braille_end) = get_braille_position()?;
+    let (zoom_depth, at_boundary) = MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return NAVIGATION_STATE.with(|nav_state| {
+            let nav_state = nav_state.borrow();
+            let (mut node, _) = nav_state.get_navigation_mathml(mathml)?;
+            let mut zoom_depth = 0;
+            while name(node) != "math" && let Some(parent) = node.parent().and_then(|parent| parent.element()) {
+                zoom_depth += 1;
+                node = parent;
+            }
+            return Ok::<(usize, bool), Error>( (zoom_depth, nav_state.is_at_boundary()) );
+        });
+    })?;
+    return Ok( NavigationResult { speech, id, offset, braille_start, braille_end, zoom_depth, at_boundary } );
+}
+
+/// Move to the next (`FindNext`) or previous (`FindPrevious`) node in reading order that matches `target`.
+/// `target` is the text of a leaf (e.g., `x` or `=`) or the name of a notation/intent (e.g., `fraction`).
+/// If `target` is empty, the target of the last search is used (calling [`do_navigate_command`] with `FindNext` does the same).
+/// The spoken text for the new current node is returned (or that nothing was found).
+pub fn do_navigate_find(command: impl AsRef<str>, target: impl AsRef<str>) -> Result<String> {
+    enable_logs();
+    let command = match NAV_COMMANDS.get_key(command.as_ref()) {
+        Some(command) => *command,
+        None => bail!("Unknown command in call to DoNavigateFind()"),
+    };
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return do_navigate_find_string(mathml, command, target.as_ref());
+    });
+}
+
+/// Move to row `number` (`MoveToRow`) or column `number` (`MoveToColumn`) of the current table or columnar math (1-based).
+/// Calling [`do_navigate_command`] with `MoveToRow` or `MoveToColumn` uses the last number given.
+/// The spoken text for the new current node is returned.
+pub fn do_navigate_move_to(command: impl AsRef<str>, number: usize) -> Result<String> {
+    enable_logs();
+    let command = match NAV_COMMANDS.get_key(command.as_ref()) {
+        Some(command) => *command,
+        None => bail!("Unknown command in call to DoNavigateMoveTo()"),
+    };
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return do_navigate_command_with_number(mathml, command, number);
+    });
+}
+
 /// Given an 'id' and an offset (for tokens), set the navigation node to that id.
 /// An error is returned if the 'id' doesn't exist
 pub fn set_navigation_node(id: impl AsRef<str>, offset: usize) -> Result<()> {
@@ -469,6 +714,53 @@ pub fn get_navigation_mathml_id() -> Result<(String, usize)> {
     });
 }
 
+/// Return the navigation state (position history, positions to redo, place markers, the FindNext/FindPrevious target, navigation mode,
+///   and overview setting) as a string.
+/// An app can save this and restore it with [`set_navigation_state`] after calling [`set_mathml`] with the same MathML.
+pub fn get_navigation_state() -> Result<String> {
+    enable_logs();
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return Ok(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().serialize(mathml)));
+    });
+}
+
+/// Restore the navigation state saved by [`get_navigation_state`].
+/// This must be called after [`set_mathml`] with the same MathML as when the state was saved.
+/// The saved positions don't depend on the ids that [`set_mathml`] adds, so MathML without ids can be restored.
+/// The `NavMode` and `Overview` preferences are set to the saved values.
+pub fn set_navigation_state(state: impl AsRef<str>) -> Result<()> {
+    enable_logs();
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let new_state = NavigationState::deserialize(mathml, state.as_ref())?;
+        {
+            let pref_manager = crate::prefs::PreferenceManager::get();
+            let mut pref_manager = pref_manager.borrow_mut();
+            if !new_state.get_mode().is_empty() {     // empty if navigation hadn't started
+                pref_manager.set_string_pref("NavMode", &new_state.get_mode())?;
+            }
+            pref_manager.set_api_boolean_pref("Overview", new_state.get_speak_overview());
+        }
+        NAVIGATION_STATE.with(|nav_state| *nav_state.borrow_mut() = new_state);
+        return Ok( () );
+    });
+}
+
+/// Return the navigation history (oldest first) so that, for example, a breadcrumb trail can be shown.
+/// Each entry is the `id` and `offset` of a position visited along with the navigation command that moved there.
+/// The last entry is the current (navigation) node. The history is empty if navigation hasn't started.
+pub fn get_navigation_history() -> Result<Vec<(String, usize, String)>> {
+    enable_logs();
+    return Ok(NAVIGATION_STATE.with(|nav_stack| {
+        return nav_stack.borrow().get_history().into_iter()
+                .map(|(id, offset, command)| (id, offset, command.to_string()))
+                .collect();
+    }));
+}
+
 /// Return the start and end braille character positions associated with the current (navigation) node.
 pub fn get_braille_position() -> Result<(usize, usize)> {
     enable_logs();
@@ -492,6 +784,19 @@ pub fn get_navigation_node_from_braille_position(position: usize) -> Result<(Str
     });
 }
 
+/// Get the braille associated with the MathML that was set by [`set_mathml`] (see [`get_braille`]) along with its source mapping.
+/// The vector has one entry per braille cell: the id of the smallest MathML node enclosing the cell and,
+/// if that node is a leaf, the offset of the cell in the leaf (0 otherwise).
+/// The mapping is recorded while the braille is generated, so it is cheap enough to call on every braille display refresh.
+pub fn get_braille_with_source_map(nav_node_id: impl AsRef<str>) -> Result<(String, Vec<(String, usize)>)> {
+    enable_logs();
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::braille::braille_mathml_with_source_map(mathml, nav_node_id.as_ref());
+    });
+}
+
 pub fn get_supported_braille_codes() -> Vec<String> {
     enable_logs();
     let rules_dir = crate::prefs::PreferenceManager::get().borrow().get_rules_dir();
@@ -666,6 +971,18 @@ pub fn trim_element(e: Element, allow_structure_in_leaves: bool) {
     const WHITESPACE: &[char] = &[' ', '\u{0009}', '\u{000A}','\u{000C}', '\u{000D}'];
     static WHITESPACE_MATCH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"[ \u{0009}\u{000A}\u{00C}\u{000D}]+"#).unwrap());
 
+    if matches!(name(e), "ci" | "cn" | "csymbol") && e.children().iter().any(|child| child.element().is_some()) {
+        // Content MathML token with structure (e.g., "1<sep/>2" or presentation MathML) -- it gets converted during canonicalization
+        for child in e.children() {
+            match child {
+                ChildOfElement::Element(c) => trim_element(c, allow_structure_in_leaves),
+                ChildOfElement::Text(t) if !t.text().trim_matches(WHITESPACE).is_empty() => (),
+                _ => e.remove_child(child),
+            }
+        }
+        return;
+    }
+
     if is_leaf(e) && (!allow_structure_in_leaves || IsNode::is_mathml(e)) {
         // Assume it is HTML inside of the leaf -- turn the HTML into a string
         make_leaf_element(e);
@@ -1171,4 +1488,28 @@ mod tests {
         let target = "<math><mrow><mtext>if&#xa0;</mtext><msup><mi>n</mi><mn>2</mn></msup><mtext>&#xa0;is real</mtext></mrow></math>";
         assert!(are_parsed_strs_equal(test, target));
     }
+
+    #[test]
+    fn navigation_result() -> Result<()> {
+        set_rules_dir(super::super::abs_rules_dir_path())?;
+        set_mathml("<math id='math'><mrow id='mrow'><mi id='x'>x</mi><mo id='plus'>+</mo><mn id='one'>1</mn></mrow></math>")?;
+        let result = do_navigate_command_with_result("ZoomIn")?;
+        assert_eq!( (result.id.as_str(), result.offset, result.zoom_depth, result.at_boundary), ("x", 0, 2, false) );
+        assert_eq!(result.speech, do_navigate_command("ReadCurrent")?);
+        assert_eq!( (result.braille_start, result.braille_end), get_braille_position()? );
+        let result = do_navigate_command_with_result("MovePrevious")?;
+        assert_eq!( (result.id.as_str(), result.at_boundary), ("x", true) );
+        let result = do_navigate_command_with_result("ReadNext")?;
+        assert_eq!( (result.id.as_str(), result.at_boundary), ("x", false) );
+        let result = do_navigate_command_with_result("MoveNext")?;
+        assert_eq!( (result.id.as_str(), result.braille_start == 0, result.at_boundary), ("plus", false, false) );
+        let result = do_navigate_command_with_result("ZoomIn")?;           // already at a leaf
+        assert_eq!( (result.id.as_str(), result.at_boundary), ("plus", true) );
+        let result = do_navigate_command_with_result("FindPrevious")?;     // no find target
+        assert_eq!( (result.id.as_str(), result.at_boundary), ("plus", true) );
+        let result = do_navigate_command_with_result("MoveLastLocation")?;
+        assert_eq!( (result.id.as_str(), result.at_boundary), ("x", false) );
+        assert!(do_navigate_command_with_result("MoveSideways").is_err());
+        return Ok( () );
+    }
 }
@@ -0,0 +1,1062 @@
+//! Converts LaTeX math to MathML so that authors can hand MathCAT TeX directly (see [`crate::interface::set_latex`]).
+//!
+//! The parser handles the math subset of LaTeX that authors commonly use (along with the `amsmath` environments)
+//!   and mhchem's `\ce{...}`.
+//! The MathML it produces is deliberately simple -- it is not canonical MathML.
+//! Instead, it is meant to be passed to `set_mathml` so that it goes through the same cleanup as any other MathML.
+#![allow(clippy::needless_return)]
+
+use crate::errors::*;
+use phf::{phf_map, phf_set};
+
+/// Convert a LaTeX math string to MathML.
+/// The string can optionally be surrounded by the math delimiters `$...$`, `$$...$$`, `\(...\)`, or `\[...\]`.
+pub fn latex_to_mathml(latex: &str) -> Result<String> {
+    let latex = strip_math_delimiters(latex.trim());
+    let mut parser = LaTeXParser::new(latex);
+    let children = parser.parse_expression()?;
+    parser.skip_whitespace();
+    if !parser.at_end() {
+        bail!("LaTeX error: unexpected '{}' in '{}'", parser.next_token(), latex);
+    }
+    return Ok(format!("<math>{}</math>", wrap_in_mrow(children)));
+}
+
+fn strip_math_delimiters(latex: &str) -> &str {
+    for (start, end) in [("$$", "$$"), ("$", "$"), ("\\(", "\\)"), ("\\[", "\\]")] {
+        if latex.len() >= start.len() + end.len() && latex.starts_with(start) && latex.ends_with(end) {
+            return latex[start.len()..latex.len() - end.len()].trim();
+        }
+    }
+    return latex;
+}
+
+/// Escape the characters that are special in XML text
+pub(crate) fn escape_xml(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+pub(crate) fn wrap_in_mrow(mut children: Vec<String>) -> String {
+    if children.len() == 1 {
+        return children.pop().unwrap();
+    }
+    return format!("<mrow>{}</mrow>", children.join(""));
+}
+
+pub(crate) fn mo(ch: &str) -> String {
+    return format!("<mo>{}</mo>", escape_xml(ch));
+}
+
+/// Letters that are identifiers (`<mi>`)
+pub(crate) static IDENTIFIERS: phf::Map<&str, &str> = phf_map! {
+    "alpha" => "α", "beta" => "β", "gamma" => "γ", "delta" => "δ", "epsilon" => "ϵ", "varepsilon" => "ε",
+    "zeta" => "ζ", "eta" => "η", "theta" => "θ", "vartheta" => "ϑ", "iota" => "ι", "kappa" => "κ",
+    "lambda" => "λ", "mu" => "μ", "nu" => "ν", "xi" => "ξ", "omicron" => "ο", "pi" => "π", "varpi" => "ϖ",
+    "rho" => "ρ", "varrho" => "ϱ", "sigma" => "σ", "varsigma" => "ς", "tau" => "τ", "upsilon" => "υ",
+    "phi" => "ϕ", "varphi" => "φ", "chi" => "χ", "psi" => "ψ", "omega" => "ω",
+    "Gamma" => "Γ", "Delta" => "Δ", "Theta" => "Θ", "Lambda" => "Λ", "Xi" => "Ξ", "Pi" => "Π",
+    "Sigma" => "Σ", "Upsilon" => "Υ", "Phi" => "Φ", "Psi" => "Ψ", "Omega" => "Ω",
+    "infty" => "∞", "partial" => "∂", "nabla" => "∇", "emptyset" => "∅", "varnothing" => "∅",
+    "hbar" => "ℏ", "ell" => "ℓ", "aleph" => "ℵ", "Re" => "ℜ", "Im" => "ℑ", "wp" => "℘", "imath" => "ı", "jmath" => "ȷ",
+};
+
+/// Operators, relations, arrows, and fences (`<mo>`)
+pub(crate) static OPERATORS: phf::Map<&str, &str> = phf_map! {
+    "pm" => "±", "mp" => "∓", "times" => "×", "div" => "÷", "cdot" => "⋅", "ast" => "∗", "star" => "⋆",
+    "circ" => "∘", "bullet" => "∙", "cap" => "∩", "cup" => "∪", "setminus" => "∖", "wedge" => "∧", "land" => "∧",
+    "vee" => "∨", "lor" => "∨", "neg" => "¬", "lnot" => "¬", "oplus" => "⊕", "ominus" => "⊖", "otimes" => "⊗",
+    "odot" => "⊙", "dagger" => "†", "ddagger" => "‡", "wr" => "≀", "amalg" => "⨿",
+    "leq" => "≤", "le" => "≤", "geq" => "≥", "ge" => "≥", "neq" => "≠", "ne" => "≠", "approx" => "≈",
+    "equiv" => "≡", "sim" => "∼", "simeq" => "≃", "cong" => "≅", "propto" => "∝", "doteq" => "≐",
+    "in" => "∈", "notin" => "∉", "ni" => "∋", "subset" => "⊂", "supset" => "⊃", "subseteq" => "⊆",
+    "supseteq" => "⊇", "subsetneq" => "⊊", "supsetneq" => "⊋", "ll" => "≪", "gg" => "≫", "prec" => "≺", "succ" => "≻",
+    "perp" => "⊥", "parallel" => "∥", "mid" => "∣", "nmid" => "∤", "vdash" => "⊢", "models" => "⊨",
+    "to" => "→", "rightarrow" => "→", "leftarrow" => "←", "gets" => "←", "leftrightarrow" => "↔",
+    "Rightarrow" => "⇒", "Leftarrow" => "⇐", "Leftrightarrow" => "⇔", "iff" => "⟺", "implies" => "⟹",
+    "longrightarrow" => "⟶", "longleftarrow" => "⟵", "mapsto" => "↦", "uparrow" => "↑", "downarrow" => "↓",
+    "rightleftharpoons" => "⇌", "hookrightarrow" => "↪",
+    "forall" => "∀", "exists" => "∃", "nexists" => "∄", "therefore" => "∴", "because" => "∵",
+    "ldots" => "…", "dots" => "…", "dotsc" => "…", "dotsb" => "⋯", "cdots" => "⋯", "vdots" => "⋮", "ddots" => "⋱",
+    "prime" => "′", "angle" => "∠", "triangle" => "△", "square" => "□", "colon" => ":",
+    "lvert" => "|", "rvert" => "|", "vert" => "|", "lVert" => "‖", "rVert" => "‖", "Vert" => "‖", "|" => "‖",
+    "langle" => "⟨", "rangle" => "⟩", "lfloor" => "⌊", "rfloor" => "⌋", "lceil" => "⌈", "rceil" => "⌉",
+    "{" => "{", "}" => "}", "lbrace" => "{", "rbrace" => "}", "backslash" => "\\",
+    "%" => "%", "$" => "$", "#" => "#", "&" => "&", "_" => "_",
+};
+
+/// Large operators: the character and whether scripts go above and below it
+pub(crate) static LARGE_OPERATORS: phf::Map<&str, (&str, bool)> = phf_map! {
+    "sum" => ("∑", true), "prod" => ("∏", true), "coprod" => ("∐", true),
+    "bigcup" => ("⋃", true), "bigcap" => ("⋂", true), "bigoplus" => ("⨁", true), "bigotimes" => ("⨂", true),
+    "bigvee" => ("⋁", true), "bigwedge" => ("⋀", true), "bigsqcup" => ("⨆", true),
+    "int" => ("∫", false), "iint" => ("∬", false), "iiint" => ("∭", false), "oint" => ("∮", false),
+};
+
+/// Function names that are written upright
+pub(crate) static FUNCTIONS: phf::Set<&str> = phf_set! {
+    "sin", "cos", "tan", "cot", "sec", "csc", "sinh", "cosh", "tanh", "coth",
+    "arcsin", "arccos", "arctan", "log", "ln", "lg", "exp", "deg", "dim", "ker", "hom", "arg",
+    "lim", "limsup", "liminf", "max", "min", "sup", "inf", "det", "gcd", "Pr",
+};
+
+/// The functions from [`FUNCTIONS`] whose scripts go above/below them
+pub(crate) static FUNCTIONS_WITH_LIMITS: phf::Set<&str> = phf_set! {
+    "lim", "limsup", "liminf", "max", "min", "sup", "inf", "det", "gcd", "Pr",
+};
+
+/// Font commands and the `mathvariant` they correspond to
+static MATH_VARIANTS: phf::Map<&str, &str> = phf_map! {
+    "mathrm" => "normal", "mathit" => "italic", "mathbf" => "bold", "mathbb" => "double-struck",
+    "mathcal" => "script", "mathscr" => "script", "mathfrak" => "fraktur", "mathsf" => "sans-serif",
+    "mathtt" => "monospace", "boldsymbol" => "bold-italic", "bm" => "bold-italic",
+};
+
+/// Old-style font switches (e.g., `{\rm d}x`) that apply to the rest of the group
+static FONT_SWITCHES: phf::Map<&str, &str> = phf_map! {
+    "rm" => "normal", "it" => "italic", "bf" => "bold", "cal" => "script", "sf" => "sans-serif", "tt" => "monospace",
+};
+
+/// Accents: the character and whether it goes over (true) or under (false) the base
+static ACCENTS: phf::Map<&str, (&str, bool)> = phf_map! {
+    "hat" => ("^", true), "widehat" => ("^", true), "check" => ("ˇ", true), "breve" => ("˘", true),
+    "bar" => ("¯", true), "overline" => ("¯", true), "vec" => ("→", true), "overrightarrow" => ("→", true),
+    "overleftarrow" => ("←", true), "dot" => ("˙", true), "ddot" => ("¨", true), "tilde" => ("~", true),
+    "widetilde" => ("~", true), "acute" => ("´", true), "grave" => ("`", true),
+    "overbrace" => ("⏞", true), "underbrace" => ("⏟", false), "underline" => ("_", false),
+};
+
+/// Spacing commands and their widths
+static SPACES: phf::Map<&str, &str> = phf_map! {
+    "," => "0.167em", "thinspace" => "0.167em", ":" => "0.222em", ">" => "0.222em", "medspace" => "0.222em",
+    ";" => "0.278em", "thickspace" => "0.278em", "!" => "-0.167em", "negthinspace" => "-0.167em",
+    " " => "0.333em", "quad" => "1em", "qquad" => "2em",
+};
+
+/// Commands that only affect the visual layout and so are dropped (the value is the number of arguments to skip)
+static IGNORED_COMMANDS: phf::Map<&str, usize> = phf_map! {
+    "displaystyle" => 0, "textstyle" => 0, "scriptstyle" => 0, "scriptscriptstyle" => 0,
+    "nonumber" => 0, "notag" => 0, "hline" => 0, "limits" => 0, "nolimits" => 0, "mathstrut" => 0, "strut" => 0,
+    "label" => 1, "tag" => 1, "color" => 1, "vphantom" => 1,
+};
+
+/// Characters produced by `\not` followed by the character
+static NEGATIONS: phf::Map<char, char> = phf_map! {
+    '=' => '≠', '<' => '≮', '>' => '≯', '∈' => '∉', '≡' => '≢', '∼' => '≁', '≤' => '≰', '≥' => '≱',
+    '⊂' => '⊄', '⊃' => '⊅', '⊆' => '⊈', '⊇' => '⊉', '∣' => '∤', '≈' => '≉', '≅' => '≇',
+};
+
+/// Environments that become an `mtable`: the fences around the table and the alignment of the columns
+static ENVIRONMENTS: phf::Map<&str, (&str, &str, ColumnAlignment)> = phf_map! {
+    "matrix" => ("", "", ColumnAlignment::Center), "smallmatrix" => ("", "", ColumnAlignment::Center),
+    "pmatrix" => ("(", ")", ColumnAlignment::Center), "bmatrix" => ("[", "]", ColumnAlignment::Center),
+    "Bmatrix" => ("{", "}", ColumnAlignment::Center), "vmatrix" => ("|", "|", ColumnAlignment::Center),
+    "Vmatrix" => ("‖", "‖", ColumnAlignment::Center),
+    "cases" => ("{", "", ColumnAlignment::Left), "rcases" => ("", "}", ColumnAlignment::Left),
+    "aligned" => ("", "", ColumnAlignment::RightLeft), "align" => ("", "", ColumnAlignment::RightLeft),
+    "align*" => ("", "", ColumnAlignment::RightLeft), "alignat" => ("", "", ColumnAlignment::RightLeft),
+    "alignat*" => ("", "", ColumnAlignment::RightLeft), "alignedat" => ("", "", ColumnAlignment::RightLeft),
+    "split" => ("", "", ColumnAlignment::RightLeft), "eqnarray" => ("", "", ColumnAlignment::RightLeft),
+    "eqnarray*" => ("", "", ColumnAlignment::RightLeft),
+    "gathered" => ("", "", ColumnAlignment::Center), "gather" => ("", "", ColumnAlignment::Center),
+    "gather*" => ("", "", ColumnAlignment::Center), "equation" => ("", "", ColumnAlignment::Center),
+    "equation*" => ("", "", ColumnAlignment::Center), "multline" => ("", "", ColumnAlignment::Center),
+    "array" => ("", "", ColumnAlignment::Spec), "subarray" => ("", "", ColumnAlignment::Spec),
+};
+
+#[derive(Debug, Clone, Copy)]
+enum ColumnAlignment {
+    Center,
+    Left,
+    RightLeft,  // alternates, as in 'align'
+    Spec,       // given by an argument (e.g., "{lcr}")
+}
+
+/// A parsed piece of the LaTeX along with whether any scripts on it go above/below it (e.g., `\sum`)
+///   and whether it is a function name that needs an invisible function application after it (e.g., `\operatorname`)
+struct Atom {
+    mathml: String,
+    limits: bool,
+    is_function: bool,
+}
+
+impl Atom {
+    fn new(mathml: String) -> Atom {
+        return Atom { mathml, limits: false, is_function: false };
+    }
+}
+
+struct LaTeXParser {
+    chars: Vec<char>,
+    i: usize,
+    variant: Option<&'static str>,  // the current 'mathvariant' (from \mathbf, etc)
+}
+
+impl LaTeXParser {
+    fn new(latex: &str) -> LaTeXParser {
+        return LaTeXParser { chars: latex.chars().collect(), i: 0, variant: None };
+    }
+
+    fn at_end(&self) -> bool {
+        return self.i >= self.chars.len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.i).copied();
+    }
+
+    /// The next token (for error messages): a command (along with its name for `\begin`/`\end`), a word, or a char
+    fn next_token(&self) -> String {
+        let chars = &self.chars[self.i.min(self.chars.len())..];
+        let mut end = match chars.first() {
+            None => return "end of input".to_string(),
+            Some('\\') => 1 + chars[1..].iter().take_while(|ch| ch.is_ascii_alphabetic()).count().max(1),
+            Some(ch) if ch.is_alphanumeric() => chars.iter().take_while(|ch| ch.is_alphanumeric()).count(),
+            Some(_) => 1,
+        };
+        let command: String = chars[..end.min(chars.len())].iter().collect();
+        if (command == "\\begin" || command == "\\end") && chars.get(end) == Some(&'{') &&
+           let Some(close) = chars[end..].iter().position(|&ch| ch == '}') {
+            end += close + 1;
+        }
+        return chars[..end.min(chars.len())].iter().collect();
+    }
+
+    fn starts_with(&self, str: &str) -> bool {
+        return str.chars().enumerate().all(|(i, ch)| self.chars.get(self.i + i) == Some(&ch));
+    }
+
+    /// Returns true if the next command is exactly `\name` (not `\name` followed by more letters)
+    fn starts_with_command(&self, name: &str) -> bool {
+        if !self.starts_with("\\") || !self.starts_with(&format!("\\{name}")) {
+            return false;
+        }
+        let next = self.chars.get(self.i + 1 + name.chars().count());
+        return !next.is_some_and(|ch| ch.is_ascii_alphabetic());
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.peek() {
+            if ch.is_whitespace() {
+                self.i += 1;
+            } else if ch == '%' {
+                // comment to the end of the line
+                while self.peek().is_some_and(|ch| ch != '\n') {
+                    self.i += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(ch) {
+            bail!("LaTeX error: expected '{}' but found '{}'", ch, self.next_token());
+        }
+        self.i += 1;
+        return Ok(());
+    }
+
+    /// Is the next token one that ends an expression (`}`, `&`, `\\`, `\end`, `\right`)?
+    fn at_expression_end(&self) -> bool {
+        return match self.peek() {
+            None | Some('}') | Some('&') => true,
+            Some('\\') => self.starts_with("\\\\") || self.starts_with_command("end") || self.starts_with_command("right"),
+            _ => false,
+        };
+    }
+
+    /// Parse a sequence of atoms up to the end of the input or something that ends an expression
+    fn parse_expression(&mut self) -> Result<Vec<String>> {
+        let mut children = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.at_expression_end() {
+                return Ok(children);
+            }
+            if self.skip_ignored_command()? {
+                continue;
+            }
+            if let Some(variant) = self.font_switch() {
+                self.variant = Some(variant);
+                continue;
+            }
+            children.append(&mut self.parse_scripted()?);
+        }
+    }
+
+    /// Skip over commands that don't matter for the math (e.g., `\displaystyle`) -- returns true if one was skipped
+    fn skip_ignored_command(&mut self) -> Result<bool> {
+        if self.peek() != Some('\\') {
+            return Ok(false);
+        }
+        let start = self.i;
+        let name = self.read_command_name();
+        if let Some(&n_args) = IGNORED_COMMANDS.get(name.as_str()) {
+            for _ in 0..n_args {
+                self.read_raw_arg()?;
+            }
+            return Ok(true);
+        }
+        self.i = start;
+        return Ok(false);
+    }
+
+    /// If the next command is an old-style font switch (e.g., `\rm`), consume it and return the variant
+    fn font_switch(&mut self) -> Option<&'static str> {
+        if self.peek() != Some('\\') {
+            return None;
+        }
+        let start = self.i;
+        let name = self.read_command_name();
+        if let Some(&variant) = FONT_SWITCHES.get(name.as_str()) {
+            return Some(variant);
+        }
+        self.i = start;
+        return None;
+    }
+
+    /// Parse an atom along with any following scripts and primes
+    /// The result is two elements if the atom is a function name (the scripted name and U+2061)
+    fn parse_scripted(&mut self) -> Result<Vec<String>> {
+        let mut base = self.parse_atom()?;
+        let mut sub: Option<String> = None;
+        let mut sup: Option<String> = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.i += 1;
+                    sub = Some(self.parse_arg()?);
+                },
+                Some('^') if sup.is_none() => {
+                    self.i += 1;
+                    sup = Some(self.parse_arg()?);
+                },
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        primes.push('′');
+                        self.i += 1;
+                    }
+                    self.skip_whitespace();
+                    sup = Some(if self.peek() == Some('^') {
+                        self.i += 1;
+                        format!("<mrow>{}{}</mrow>", mo(&primes), self.parse_arg()?)
+                    } else {
+                        mo(&primes)
+                    });
+                },
+                Some('\\') if self.starts_with_command("limits") => {
+                    self.i += "\\limits".len();
+                    base.limits = true;
+                },
+                Some('\\') if self.starts_with_command("nolimits") => {
+                    self.i += "\\nolimits".len();
+                    base.limits = false;
+                },
+                _ => break,
+            }
+        }
+        let (under, over) = if base.limits { ("munder", "mover") } else { ("msub", "msup") };
+        let both = if base.limits { "munderover" } else { "msubsup" };
+        let scripted = match (sub, sup) {
+            (None, None) => base.mathml,
+            (Some(sub), None) => format!("<{under}>{}{sub}</{under}>", base.mathml),
+            (None, Some(sup)) => format!("<{over}>{}{sup}</{over}>", base.mathml),
+            (Some(sub), Some(sup)) => format!("<{both}>{}{sub}{sup}</{both}>", base.mathml),
+        };
+        return Ok( if base.is_function { vec![scripted, mo("\u{2061}")] } else { vec![scripted] } );
+    }
+
+    /// Parse an argument to a command or script: a group, a command, or a single character
+    fn parse_arg(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        return match self.peek() {
+            None => bail!("LaTeX error: missing argument at end of input"),
+            Some(ch) if ch == '^' || ch == '_' => bail!("LaTeX error: '{}' can't be the argument of a command or script", ch),
+            Some('{') => self.parse_group(),
+            Some('\\') => Ok(self.parse_atom()?.mathml),
+            Some(ch) if ch.is_ascii_digit() => {
+                self.i += 1;
+                Ok(format!("<mn>{ch}</mn>"))
+            },
+            Some(_) => Ok(self.parse_atom()?.mathml),
+        };
+    }
+
+    /// Parse `{...}`, returning a single element
+    fn parse_group(&mut self) -> Result<String> {
+        self.expect('{')?;
+        let variant = self.variant;
+        let children = self.parse_expression()?;
+        self.variant = variant;
+        self.expect('}')?;
+        return Ok(wrap_in_mrow(children));
+    }
+
+    /// Parse `{...}` using the font `variant`
+    fn parse_group_with_variant(&mut self, variant: &'static str) -> Result<String> {
+        let old_variant = self.variant.replace(variant);
+        let result = self.parse_arg();
+        self.variant = old_variant;
+        return result;
+    }
+
+    /// Read the text of a `{...}` argument without parsing it (a single character if there are no braces)
+    fn read_raw_arg(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => bail!("LaTeX error: missing argument at end of input"),
+            Some('{') => (),
+            Some(ch) => {
+                self.i += 1;
+                return Ok(ch.to_string());
+            },
+        }
+        self.i += 1;
+        let start = self.i;
+        let mut depth = 1;
+        while let Some(ch) = self.peek() {
+            match ch {
+                '\\' => self.i += 1,  // skip escaped char
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let text: String = self.chars[start..self.i].iter().collect();
+                        self.i += 1;
+                        return Ok(text);
+                    }
+                },
+                _ => (),
+            }
+            self.i += 1;
+        }
+        bail!("LaTeX error: missing '}}'");
+    }
+
+    /// Read an optional `[...]` argument
+    fn read_optional_arg(&mut self) -> Result<Option<String>> {
+        self.skip_whitespace();
+        if self.peek() != Some('[') {
+            return Ok(None);
+        }
+        self.i += 1;
+        let start = self.i;
+        let mut depth = 0;
+        while let Some(ch) = self.peek() {
+            match ch {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ']' if depth == 0 => {
+                    let text: String = self.chars[start..self.i].iter().collect();
+                    self.i += 1;
+                    return Ok(Some(text));
+                },
+                _ => (),
+            }
+            self.i += 1;
+        }
+        bail!("LaTeX error: missing ']'");
+    }
+
+    /// Read the name of the command (the leading '\' has not been consumed)
+    fn read_command_name(&mut self) -> String {
+        self.i += 1;  // '\'
+        let start = self.i;
+        while self.peek().is_some_and(|ch| ch.is_ascii_alphabetic()) {
+            self.i += 1;
+        }
+        if self.i == start && !self.at_end() {
+            self.i += 1;  // single char command such as '\,'
+        }
+        return self.chars[start..self.i].iter().collect();
+    }
+
+    fn mathvariant_attr(&self) -> String {
+        return match self.variant {
+            None => "".to_string(),
+            Some(variant) => format!(" mathvariant='{variant}'"),
+        };
+    }
+
+    fn parse_atom(&mut self) -> Result<Atom> {
+        self.skip_whitespace();
+        let ch = match self.peek() {
+            None => bail!("LaTeX error: unexpected end of input"),
+            Some(ch) => ch,
+        };
+        if ch.is_ascii_digit() || (ch == '.' && self.chars.get(self.i + 1).is_some_and(|ch| ch.is_ascii_digit())) {
+            let start = self.i;
+            let mut seen_decimal_point = false;
+            while let Some(ch) = self.peek() {
+                if ch.is_ascii_digit() {
+                    self.i += 1;
+                } else if ch == '.' && !seen_decimal_point && self.chars.get(self.i + 1).is_some_and(|ch| ch.is_ascii_digit()) {
+                    seen_decimal_point = true;
+                    self.i += 1;
+                } else {
+                    break;
+                }
+            }
+            let number: String = self.chars[start..self.i].iter().collect();
+            return Ok(Atom::new(format!("<mn{}>{}</mn>", self.mathvariant_attr(), number)));
+        }
+        if ch.is_alphabetic() {
+            // with \mathrm and the like, runs of letters are a single (multi-letter) identifier
+            let start = self.i;
+            self.i += 1;
+            if self.variant == Some("normal") {
+                while self.peek().is_some_and(|ch| ch.is_alphabetic()) {
+                    self.i += 1;
+                }
+            }
+            let name: String = self.chars[start..self.i].iter().collect();
+            return Ok(Atom::new(format!("<mi{}>{}</mi>", self.mathvariant_attr(), name)));
+        }
+        return match ch {
+            '{' => Ok(Atom::new(self.parse_group()?)),
+            '\\' => self.parse_command(),
+            '^' | '_' => Ok(Atom::new("<mrow></mrow>".to_string())),   // script with an empty base
+            '}' | '&' | '#' | '$' => bail!("LaTeX error: unexpected '{}'", ch),
+            '~' => {
+                self.i += 1;
+                Ok(Atom::new("<mspace width='0.333em'/>".to_string()))
+            },
+            _ => {
+                self.i += 1;
+                let op = match ch {
+                    '-' => "−".to_string(),
+                    '*' => "∗".to_string(),
+                    _ => ch.to_string(),
+                };
+                Ok(Atom::new(mo(&op)))
+            },
+        };
+    }
+
+    fn parse_command(&mut self) -> Result<Atom> {
+        let name = self.read_command_name();
+        let name = name.as_str();
+        if let Some(&ch) = IDENTIFIERS.get(name) {
+            return Ok(Atom::new(format!("<mi{}>{}</mi>", self.mathvariant_attr(), ch)));
+        }
+        if let Some(&ch) = OPERATORS.get(name) {
+            return Ok(Atom::new(mo(ch)));
+        }
+        if let Some(&(ch, limits)) = LARGE_OPERATORS.get(name) {
+            return Ok(Atom { mathml: mo(ch), limits, is_function: false });
+        }
+        if FUNCTIONS.contains(name) {
+            return Ok(Atom { mathml: format!("<mi>{name}</mi>"), limits: FUNCTIONS_WITH_LIMITS.contains(name), is_function: false });
+        }
+        if let Some(&variant) = MATH_VARIANTS.get(name) {
+            return Ok(Atom::new(self.parse_group_with_variant(variant)?));
+        }
+        if let Some(&(accent, is_over)) = ACCENTS.get(name) {
+            let base = self.parse_arg()?;
+            let is_brace = name == "overbrace" || name == "underbrace";
+            let mathml = if is_over {
+                format!("<mover accent='true'>{}{}</mover>", base, mo(accent))
+            } else {
+                format!("<munder accentunder='true'>{}{}</munder>", base, mo(accent))
+            };
+            return Ok(Atom { mathml, limits: is_brace, is_function: false });
+        }
+        if let Some(&width) = SPACES.get(name) {
+            return Ok(Atom::new(format!("<mspace width='{width}'/>")));
+        }
+        return Ok( match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_arg()?;
+                let denominator = self.parse_arg()?;
+                Atom::new(format!("<mfrac>{numerator}{denominator}</mfrac>"))
+            },
+            "binom" | "dbinom" | "tbinom" => {
+                let n = self.parse_arg()?;
+                let k = self.parse_arg()?;
+                Atom::new(format!("<mrow><mo>(</mo><mfrac linethickness='0'>{n}{k}</mfrac><mo>)</mo></mrow>"))
+            },
+            "sqrt" => {
+                let index = self.read_optional_arg()?;
+                let radicand = self.parse_arg()?;
+                match index {
+                    None => Atom::new(format!("<msqrt>{radicand}</msqrt>")),
+                    Some(index) => {
+                        let index = wrap_in_mrow(LaTeXParser::new(&index).parse_expression()?);
+                        Atom::new(format!("<mroot>{radicand}{index}</mroot>"))
+                    },
+                }
+            },
+            "left" => {
+                let open = self.read_delimiter()?;
+                let mut children = vec![open];
+                children.append(&mut self.parse_expression()?);
+                self.skip_whitespace();
+                if !self.starts_with_command("right") {
+                    bail!("LaTeX error: '\\left' without a matching '\\right'");
+                }
+                self.i += "\\right".len();
+                children.push(self.read_delimiter()?);
+                Atom::new(format!("<mrow>{}</mrow>", children.join("")))
+            },
+            "right" => bail!("LaTeX error: '\\right' without a matching '\\left'"),
+            "middle" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "bigm" | "Bigl" | "Bigr" | "Bigm" |
+            "biggl" | "biggr" | "biggm" | "Biggl" | "Biggr" | "Biggm" => Atom::new(self.read_delimiter()?),
+            "text" | "textrm" | "textnormal" | "mbox" | "hbox" | "textit" | "textbf" => {
+                let text = self.read_raw_arg()?;
+                let variant = match name {
+                    "textit" => " mathvariant='italic'",
+                    "textbf" => " mathvariant='bold'",
+                    _ => "",
+                };
+                Atom::new(format!("<mtext{}>{}</mtext>", variant, escape_xml(&text)))
+            },
+            "operatorname" => {
+                let limits = self.peek() == Some('*');
+                if limits {
+                    self.i += 1;
+                }
+                let text = self.read_raw_arg()?;
+                // unlike '\sin', the name isn't one that canonicalization knows is a function, so say so explicitly
+                Atom { mathml: format!("<mi>{}</mi>", escape_xml(text.trim())), limits, is_function: true }
+            },
+            "mathop" => Atom { mathml: self.parse_arg()?, limits: true, is_function: false },
+            "overset" | "stackrel" | "underset" => {
+                let script = self.parse_arg()?;
+                let base = self.parse_arg()?;
+                let element = if name == "underset" { "munder" } else { "mover" };
+                Atom::new(format!("<{element}>{base}{script}</{element}>"))
+            },
+            "not" => {
+                let negated = self.parse_atom()?.mathml;
+                let negated = match negated.strip_prefix("<mo>").and_then(|str| str.strip_suffix("</mo>")) {
+                    Some(op) => {
+                        let op = op.replace("&lt;", "<").replace("&gt;", ">");
+                        let mut chars = op.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(ch), None) if NEGATIONS.contains_key(&ch) => mo(&NEGATIONS[&ch].to_string()),
+                            _ => mo(&format!("{op}\u{0338}")),
+                        }
+                    },
+                    None => format!("<mrow><mo>\u{0338}</mo>{negated}</mrow>"),
+                };
+                Atom::new(negated)
+            },
+            "bmod" | "mod" => Atom::new("<mo>mod</mo>".to_string()),
+            "pmod" => Atom::new(format!("<mrow><mo>(</mo><mo>mod</mo>{}<mo>)</mo></mrow>", self.parse_arg()?)),
+            "boxed" => Atom::new(format!("<menclose notation='box'>{}</menclose>", self.parse_arg()?)),
+            "cancel" => Atom::new(format!("<menclose notation='updiagonalstrike'>{}</menclose>", self.parse_arg()?)),
+            "phantom" => Atom::new(format!("<mphantom>{}</mphantom>", self.parse_arg()?)),
+            "textcolor" => {
+                self.read_raw_arg()?;
+                Atom::new(self.parse_arg()?)
+            },
+            "hspace" => Atom::new(format!("<mspace width='{}'/>", escape_xml(self.read_raw_arg()?.trim()))),
+            "ce" => Atom::new(chemistry_to_mathml(&self.read_raw_arg()?)?),
+            "begin" => Atom::new(self.parse_environment()?),
+            "end" => bail!("LaTeX error: '\\end' without a matching '\\begin'"),
+            "\\" => bail!("LaTeX error: '\\\\' is only allowed inside an environment"),
+            _ => bail!("LaTeX error: unknown command '\\{}'", name),
+        });
+    }
+
+    /// Read the delimiter after `\left`, `\right`, `\big`, etc. ('.' is an empty delimiter)
+    fn read_delimiter(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        let delimiter = match self.peek() {
+            None => bail!("LaTeX error: missing delimiter at end of input"),
+            Some('.') => {
+                self.i += 1;
+                return Ok("".to_string());
+            },
+            Some('\\') => {
+                let name = self.read_command_name();
+                match OPERATORS.get(name.as_str()) {
+                    Some(&ch) => ch.to_string(),
+                    None => bail!("LaTeX error: '\\{}' is not a delimiter", name),
+                }
+            },
+            Some(ch) => {
+                self.i += 1;
+                ch.to_string()
+            },
+        };
+        return Ok(mo(&delimiter));
+    }
+
+    /// Parse `\begin{name}...\end{name}` (the `\begin` has been consumed)
+    fn parse_environment(&mut self) -> Result<String> {
+        let name = self.read_raw_arg()?;
+        let &(open, close, alignment) = match ENVIRONMENTS.get(name.as_str()) {
+            None => bail!("LaTeX error: unknown environment '{}'", name),
+            Some(env) => env,
+        };
+        let mut column_spec = String::new();
+        if matches!(alignment, ColumnAlignment::Spec) {
+            column_spec = self.read_raw_arg()?;
+        } else if name.starts_with("alignat") {
+            self.read_raw_arg()?;     // number of columns
+        }
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut row = Vec::new();
+        loop {
+            row.push(wrap_in_mrow(self.parse_expression()?));
+            self.skip_whitespace();
+            if self.peek() == Some('&') {
+                self.i += 1;
+            } else if self.starts_with("\\\\") {
+                self.i += 2;
+                self.read_optional_arg()?;     // extra vertical space
+                rows.push(std::mem::take(&mut row));
+            } else if self.starts_with_command("end") {
+                self.i += "\\end".len();
+                let end_name = self.read_raw_arg()?;
+                if end_name != name {
+                    bail!("LaTeX error: '\\begin{{{}}}' ended with '\\end{{{}}}'", name, end_name);
+                }
+                // a trailing '\\' results in an empty row
+                if !(row.len() == 1 && row[0] == "<mrow></mrow>") {
+                    rows.push(row);
+                }
+                break;
+            } else {
+                bail!("LaTeX error: missing '\\end{{{}}}'", name);
+            }
+        }
+
+        let n_columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let column_align = match alignment {
+            ColumnAlignment::Center => "".to_string(),
+            ColumnAlignment::Left => " columnalign='left'".to_string(),
+            ColumnAlignment::RightLeft => {
+                let align = (0..n_columns).map(|i| if i % 2 == 0 {"right"} else {"left"}).collect::<Vec<&str>>();
+                format!(" columnalign='{}'", align.join(" "))
+            },
+            ColumnAlignment::Spec => {
+                let align = column_spec.chars()
+                    .filter_map(|ch| match ch {
+                        'l' => Some("left"),
+                        'c' => Some("center"),
+                        'r' => Some("right"),
+                        _ => None,
+                    })
+                    .collect::<Vec<&str>>();
+                if align.is_empty() {"".to_string()} else {format!(" columnalign='{}'", align.join(" "))}
+            },
+        };
+        let rows = rows.iter()
+            .map(|row| format!("<mtr>{}</mtr>", row.iter().map(|cell| format!("<mtd>{cell}</mtd>")).collect::<String>()))
+            .collect::<String>();
+        let table = format!("<mtable{column_align}>{rows}</mtable>");
+        if open.is_empty() && close.is_empty() {
+            return Ok(table);
+        }
+        let open = if open.is_empty() {"".to_string()} else {mo(open)};
+        let close = if close.is_empty() {"".to_string()} else {mo(close)};
+        return Ok(format!("<mrow>{open}{table}{close}</mrow>"));
+    }
+}
+
+/// The mhchem arrows, longest first so that prefixes don't match first
+static CHEMISTRY_ARROWS: &[(&str, &str)] = &[
+    ("<=>>", "⇌"), ("<<=>", "⇌"), ("<-->", "⇄"), ("<=>", "⇌"), ("<->", "↔"), ("->", "→"), ("<-", "←"),
+];
+
+/// The states of matter that can follow a species in parens
+static CHEMISTRY_STATES: phf::Set<&str> = phf_set! { "s", "l", "g", "aq" };
+
+/// A piece of a chemical formula along with its subscript (count) and superscript (charge)
+///   and any leading scripts (the atomic number and mass number of an isotope as in `^{14}_{6}C`)
+struct ChemPart {
+    base: String,
+    sub: Option<String>,
+    sup: Option<String>,
+    pre_sub: Option<String>,
+    pre_sup: Option<String>,
+}
+
+impl ChemPart {
+    fn new(base: String) -> ChemPart {
+        return ChemPart { base, sub: None, sup: None, pre_sub: None, pre_sup: None };
+    }
+
+    fn to_mathml(&self) -> String {
+        if self.pre_sub.is_some() || self.pre_sup.is_some() {
+            let script = |script: &Option<String>| script.clone().unwrap_or_else(|| "<none/>".to_string());
+            return format!("<mmultiscripts>{}{}{}<mprescripts/>{}{}</mmultiscripts>",
+                    self.base, script(&self.sub), script(&self.sup), script(&self.pre_sub), script(&self.pre_sup));
+        }
+        return match (&self.sub, &self.sup) {
+            (None, None) => self.base.clone(),
+            (Some(sub), None) => format!("<msub>{}{}</msub>", self.base, sub),
+            (None, Some(sup)) => format!("<msup>{}{}</msup>", self.base, sup),
+            (Some(sub), Some(sup)) => format!("<msubsup>{}{}{}</msubsup>", self.base, sub, sup),
+        };
+    }
+}
+
+/// Convert the contents of mhchem's `\ce{...}` to MathML
+fn chemistry_to_mathml(formula: &str) -> Result<String> {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut i = 0;
+    let children = parse_chemistry(&chars, &mut i, None)?;
+    return Ok(wrap_in_mrow(children));
+}
+
+/// Parse the chemistry starting at `i` up to the end or the `close` char (which is not consumed)
+fn parse_chemistry(chars: &[char], i: &mut usize, close: Option<char>) -> Result<Vec<String>> {
+    let mut parts: Vec<ChemPart> = Vec::new();
+    let mut children: Vec<String> = Vec::new();
+    let mut at_species_start = true;   // a number at the start of a species is a coefficient, not a subscript
+    let mut prescripts: (Option<String>, Option<String>) = (None, None);   // leading sub/superscript for the next part
+    while *i < chars.len() {
+        let ch = chars[*i];
+        if Some(ch) == close {
+            break;
+        }
+        if ch.is_whitespace() {
+            *i += 1;
+            at_species_start = true;
+            continue;
+        }
+        let rest: String = chars[*i..].iter().collect();
+        if let Some(&(arrow, ch)) = CHEMISTRY_ARROWS.iter().find(|(arrow, _)| rest.starts_with(arrow)) {
+            *i += arrow.chars().count();
+            let mut arrow = mo(ch);
+            if chars.get(*i) == Some(&'[') {
+                let end = chars[*i..].iter().position(|&ch| ch == ']')
+                    .ok_or_else(|| anyhow!("LaTeX error: missing ']' in '\\ce{{{}}}'", formula_text(chars)))?;
+                let above: String = chars[*i + 1..*i + end].iter().collect();
+                *i += end + 1;
+                arrow = format!("<mover>{}<mtext>{}</mtext></mover>", arrow, escape_xml(&above));
+            }
+            flush_chem_parts(&mut parts, &mut children);
+            children.push(arrow);
+            at_species_start = true;
+            continue;
+        }
+        let after_whitespace = *i > 0 && chars[*i - 1].is_whitespace();
+        *i += 1;
+        if ch.is_ascii_digit() {
+            let start = *i - 1;
+            while *i < chars.len() && chars[*i].is_ascii_digit() {
+                *i += 1;
+            }
+            let number: String = chars[start..*i].iter().collect();
+            match parts.last_mut() {
+                Some(part) if !at_species_start && part.sub.is_none() => part.sub = Some(format!("<mn>{number}</mn>")),
+                _ => {
+                    flush_chem_parts(&mut parts, &mut children);
+                    children.push(format!("<mn>{number}</mn>"));
+                },
+            }
+            continue;
+        }
+        if (ch == '^' || ch == '_') && (at_species_start || parts.is_empty()) {
+            // an isotope's mass number or atomic number (e.g., "^{14}C")
+            let script = format!("<mn>{}</mn>", escape_xml(read_chem_script(chars, i)?.trim()));
+            let prescript = if ch == '^' { &mut prescripts.1 } else { &mut prescripts.0 };
+            if prescript.replace(script).is_some() {
+                bail!("LaTeX error: more than one leading '{}' in '\\ce{{{}}}'", ch, formula_text(chars));
+            }
+            continue;
+        }
+        at_species_start = false;
+        let n_parts = parts.len();
+        if ch.is_ascii_uppercase() {
+            let start = *i - 1;
+            while *i < chars.len() && chars[*i].is_ascii_lowercase() {
+                *i += 1;
+            }
+            let element: String = chars[start..*i].iter().collect();
+            parts.push(ChemPart::new(format!("<mi>{element}</mi>")));
+        } else if ch.is_alphabetic() {
+            parts.push(ChemPart::new(format!("<mi>{ch}</mi>")));
+        } else if ch == '(' || ch == '[' {
+            let close_ch = if ch == '(' { ')' } else { ']' };
+            let end = chars[*i..].iter().position(|&ch| ch == close_ch);
+            let contents: String = end.map(|end| chars[*i..*i + end].iter().collect()).unwrap_or_default();
+            let base = if ch == '(' && CHEMISTRY_STATES.contains(contents.as_str()) {
+                *i += contents.chars().count();
+                format!("<mi>{contents}</mi>")
+            } else {
+                wrap_in_mrow(parse_chemistry(chars, i, Some(close_ch))?)
+            };
+            if chars.get(*i) != Some(&close_ch) {
+                bail!("LaTeX error: missing '{}' in '\\ce{{{}}}'", close_ch, formula_text(chars));
+            }
+            *i += 1;
+            parts.push(ChemPart::new(format!("<mrow>{}{}{}</mrow>", mo(&ch.to_string()), base, mo(&close_ch.to_string()))));
+        } else if ch == '^' {
+            let charge = read_chem_script(chars, i)?;
+            parts.last_mut().unwrap().sup = Some(charge_to_mathml(&charge));   // not empty -- tested above
+        } else if (ch == '+' || ch == '-') && !parts.is_empty() && !after_whitespace &&
+                  chars.get(*i).is_none_or(|&next| next.is_whitespace() || Some(next) == close) {
+            // a charge directly after a species (e.g., "Na+")
+            parts.last_mut().unwrap().sup = Some(charge_to_mathml(&ch.to_string()));
+        } else {
+            let op = match ch {
+                '-' => "−".to_string(),     // single bond
+                '#' => "≡".to_string(),
+                '.' | '*' => "⋅".to_string(),
+                _ => ch.to_string(),
+            };
+            flush_chem_parts(&mut parts, &mut children);
+            children.push(mo(&op));
+            at_species_start = ch == '+' || ch == '.' || ch == '*';
+        }
+        if parts.len() > n_parts {
+            let part = parts.last_mut().unwrap();
+            (part.pre_sub, part.pre_sup) = std::mem::take(&mut prescripts);
+        }
+    }
+    if prescripts.0.is_some() || prescripts.1.is_some() {
+        bail!("LaTeX error: a leading script without a base in '\\ce{{{}}}'", formula_text(chars));
+    }
+    flush_chem_parts(&mut parts, &mut children);
+    return Ok(children);
+}
+
+/// Read the script after a '^' or '_' (already consumed): either `{...}` or a run of digits and signs
+fn read_chem_script(chars: &[char], i: &mut usize) -> Result<String> {
+    if chars.get(*i) == Some(&'{') {
+        let end = chars[*i..].iter().position(|&ch| ch == '}')
+            .ok_or_else(|| anyhow!("LaTeX error: missing '}}' in '\\ce{{{}}}'", formula_text(chars)))?;
+        let script: String = chars[*i + 1..*i + end].iter().collect();
+        *i += end + 1;
+        return Ok(script);
+    }
+    let start = *i;
+    while *i < chars.len() && (chars[*i].is_ascii_digit() || chars[*i] == '+' || chars[*i] == '-') {
+        *i += 1;
+    }
+    return Ok(chars[start..*i].iter().collect());
+}
+
+fn flush_chem_parts(parts: &mut Vec<ChemPart>, children: &mut Vec<String>) {
+    children.extend(parts.drain(..).map(|part| part.to_mathml()));
+}
+
+fn formula_text(chars: &[char]) -> String {
+    return chars.iter().collect();
+}
+
+/// Convert a charge such as "2+" or "-" to MathML
+fn charge_to_mathml(charge: &str) -> String {
+    let digits: String = charge.chars().take_while(|ch| ch.is_ascii_digit()).collect();
+    let sign = match &charge[digits.len()..] {
+        "-" => "−",
+        sign => sign,
+    };
+    return match (digits.is_empty(), sign.is_empty()) {
+        (true, _) => mo(sign),
+        (false, true) => format!("<mn>{digits}</mn>"),
+        (false, false) => format!("<mrow><mn>{}</mn>{}</mrow>", digits, mo(sign)),
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(latex: &str, mathml: &str) {
+        match latex_to_mathml(latex) {
+            Ok(result) => assert_eq!(result, format!("<math>{mathml}</math>"), "\nLaTeX: {latex}"),
+            Err(e) => panic!("{}", crate::interface::errors_to_string(&e)),
+        }
+    }
+
+    #[test]
+    fn simple() {
+        test("x+2.5", "<mrow><mi>x</mi><mo>+</mo><mn>2.5</mn></mrow>");
+        test("$a-b$", "<mrow><mi>a</mi><mo>−</mo><mi>b</mi></mrow>");
+        test("\\alpha \\leq \\infty", "<mrow><mi>α</mi><mo>≤</mo><mi>∞</mi></mrow>");
+        test("a \\not= b", "<mrow><mi>a</mi><mo>≠</mo><mi>b</mi></mrow>");
+    }
+
+    #[test]
+    fn scripts() {
+        test("x_1^2", "<msubsup><mi>x</mi><mn>1</mn><mn>2</mn></msubsup>");
+        test("x^{n+1}", "<msup><mi>x</mi><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msup>");
+        test("f''", "<msup><mi>f</mi><mo>′′</mo></msup>");
+        test("\\sum_{i=1}^n i",
+            "<mrow><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi></mrow>");
+        test("\\int_0^1", "<msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup>");
+        test("\\lim_{x\\to 0}", "<munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>");
+    }
+
+    #[test]
+    fn fractions_and_roots() {
+        test("\\frac12", "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+        test("\\dfrac{a}{b+1}", "<mfrac><mi>a</mi><mrow><mi>b</mi><mo>+</mo><mn>1</mn></mrow></mfrac>");
+        test("\\sqrt[3]{x}", "<mroot><mi>x</mi><mn>3</mn></mroot>");
+        test("\\binom{n}{k}", "<mrow><mo>(</mo><mfrac linethickness='0'><mi>n</mi><mi>k</mi></mfrac><mo>)</mo></mrow>");
+    }
+
+    #[test]
+    fn fonts_and_text() {
+        test("\\mathrm{d}x", "<mrow><mi mathvariant='normal'>d</mi><mi>x</mi></mrow>");
+        test("\\mathbb{R}", "<mi mathvariant='double-struck'>R</mi>");
+        test("x \\text{ if } y", "<mrow><mi>x</mi><mtext> if </mtext><mi>y</mi></mrow>");
+        test("\\operatorname{Tr} A", "<mrow><mi>Tr</mi><mo>\u{2061}</mo><mi>A</mi></mrow>");
+        test("\\operatorname*{argmax}_x f",
+            "<mrow><munder><mi>argmax</mi><mi>x</mi></munder><mo>\u{2061}</mo><mi>f</mi></mrow>");
+        test("\\vec{v}", "<mover accent='true'><mi>v</mi><mo>→</mo></mover>");
+    }
+
+    #[test]
+    fn fences_and_environments() {
+        test("\\left( x \\right]", "<mrow><mo>(</mo><mi>x</mi><mo>]</mo></mrow>");
+        test("\\begin{pmatrix} 1 & 2 \\\\ 3 & 4 \\end{pmatrix}",
+            "<mrow><mo>(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>\
+                                     <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr></mtable><mo>)</mo></mrow>"
+                .replace(' ', "").as_str());
+        test("\\begin{cases} 1 & x>0 \\\\ 0 & \\text{otherwise} \\end{cases}",
+            "<mrow><mo>{</mo><mtable columnalign='left'>\
+                <mtr><mtd><mn>1</mn></mtd><mtd><mrow><mi>x</mi><mo>&gt;</mo><mn>0</mn></mrow></mtd></mtr>\
+                <mtr><mtd><mn>0</mn></mtd><mtd><mtext>otherwise</mtext></mtd></mtr>\
+            </mtable></mrow>");
+        test("\\begin{aligned} x &= 1 \\\\ \\end{aligned}",
+            "<mtable columnalign='right left'><mtr><mtd><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mn>1</mn></mrow></mtd></mtr></mtable>");
+    }
+
+    #[test]
+    fn chemistry() {
+        test("\\ce{H2O}", "<mrow><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></mrow>");
+        test("\\ce{2H2 + O2 -> 2H2O}",
+            "<mrow><mn>2</mn><msub><mi>H</mi><mn>2</mn></msub><mo>+</mo><msub><mi>O</mi><mn>2</mn></msub>\
+             <mo>→</mo><mn>2</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></mrow>".replace(' ', "").as_str());
+        test("\\ce{SO4^2-}", "<msubsup><mi>O</mi><mn>4</mn><mrow><mn>2</mn><mo>−</mo></mrow></msubsup>"
+            .replace("<msubsup>", "<mrow><mi>S</mi><msubsup>").replace("</msubsup>", "</msubsup></mrow>").as_str());
+        test("\\ce{Na+}", "<msup><mi>Na</mi><mo>+</mo></msup>");
+        test("\\ce{^{14}C}", "<mmultiscripts><mi>C</mi><none/><none/><mprescripts/><none/><mn>14</mn></mmultiscripts>");
+        test("\\ce{^{235}_{92}U}", "<mmultiscripts><mi>U</mi><none/><none/><mprescripts/><mn>92</mn><mn>235</mn></mmultiscripts>");
+        test("\\ce{FeCl3(aq)}", "<mrow><mi>Fe</mi><msub><mi>Cl</mi><mn>3</mn></msub><mrow><mo>(</mo><mi>aq</mi><mo>)</mo></mrow></mrow>");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(latex_to_mathml("\\frac{1}").is_err());
+        assert!(latex_to_mathml("\\foo").is_err());
+        assert!(latex_to_mathml("x}").is_err());
+        assert!(latex_to_mathml("\\left( x").is_err());
+        assert!(latex_to_mathml("\\begin{matrix} 1 \\end{pmatrix}").is_err());
+        assert!(latex_to_mathml("x^^2").is_err());
+        assert!(latex_to_mathml("x_^2").is_err());
+        assert!(latex_to_mathml("\\ce{^{14}}").is_err());
+    }
+
+    #[test]
+    fn error_messages_show_whole_token() {
+        let error = |latex: &str| crate::interface::errors_to_string(&latex_to_mathml(latex).unwrap_err());
+        assert!(error("x \\end{matrix} y").contains("unexpected '\\end{matrix}'"));
+        assert!(error("x } abcdefghijkl").contains("unexpected '}'"));
+        assert!(error("\\sqrt[3]{x} \\right)").contains("unexpected '\\right'"));
+        assert!(error("\\frac{1 abcdefghijkl").contains("found 'end of input'"));
+    }
+}
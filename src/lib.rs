@@ -38,6 +38,12 @@ mod xpath_functions;
 mod definitions;
 pub mod pretty_print;
 mod chemistry;
+mod latex;
+mod asciimath;
+mod unicodemath;
+mod content_mathml;
+mod omml;
+mod lint;
 
 pub mod shim_filesystem; // really just for override_file_for_debugging_rules, but the config seems to throw it off
 pub use interface::*;
@@ -0,0 +1,310 @@
+//! Checks MathML for markup that degrades speech and braille (see [`crate::interface::lint_mathml`]).
+//!
+//! The checks are built on the heuristics used by canonicalization:
+//! some are done on the author's MathML (e.g., `mtext` used for an operator), others look at what canonicalization had to repair
+//!   (e.g., merging `mi`s into a function name) or infer (e.g., adding an invisible function application).
+//! To map what canonicalization did back to the author's MathML, every element is given an `id` before canonicalization.
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use regex::Regex;
+use sxd_document::dom::*;
+use crate::canonicalize::{as_element, as_text, name, CHANGED_ATTR, ADDED_ATTR_VALUE};
+use crate::canonicalize::{canonicalize_with_repairs, is_known_function_name_or_word, is_operator_text, is_ambiguous_fence_operator};
+use crate::interface::trim_element;
+use crate::xpath_functions::is_leaf;
+use crate::errors::*;
+
+/// A problem found by [`crate::interface::lint_mathml`] in MathML that likely degrades the speech or braille
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintDiagnostic {
+	/// "warning" if MathCAT has to guess (and might guess wrong), "info" if MathCAT can fix it but the markup should be improved
+	pub severity: String,
+	/// The kind of problem (see [`crate::interface::lint_mathml`] for the values)
+	pub kind: String,
+	/// A description of the problem and how to fix it
+	pub message: String,
+	/// The `id` of the element in the MathML that was linted (empty if the element doesn't have an `id`)
+	pub id: String,
+	/// The location of the element in the MathML that was linted as an XPath (e.g., "/math/mrow[1]/mi[2]")
+	pub location: String,
+}
+
+/// Prefix for 'id's added to elements that don't have one
+const LINT_ID_PREFIX: &str = "mathcat-lint-";
+
+/// Where an element is in the author's MathML
+struct Location {
+	/// position in document order (used to sort the diagnostics)
+	order: usize,
+	/// XPath to the element
+	path: String,
+	/// the author's 'id' (empty if it didn't have one)
+	id: String,
+}
+
+/// Find the problems in 'mathml' (which is modified).
+pub fn lint_mathml(mathml: Element) -> Result<Vec<LintDiagnostic>> {
+	trim_element(mathml, false);
+	let mut locations = HashMap::new();
+	add_locations(mathml, format!("/{}", name(mathml)), &mut locations);
+
+	let mut diagnostics = vec![];
+	check_input(mathml, false, &locations, &mut diagnostics);
+
+	let (canonical, repairs) = canonicalize_with_repairs(mathml)?;
+	for repair in repairs {
+		let (severity, kind, message) = match repair.kind.as_str() {
+			"merged-mi" => ("warning", "split-mi",
+				"the letters of a function name or word are in separate 'mi's; use a single 'mi'".to_string()),
+			"merged-number" => ("warning", "split-number",
+				"the number is split across several elements; use a single 'mn'".to_string()),
+			"split-number" => ("info", "mn-with-operator",
+				"the 'mn' includes something that isn't part of the number (e.g., a minus sign); use an 'mo' for it".to_string()),
+			_ => continue,
+		};
+		// the first element in the original MathML is the author's element (ids were added to all of them)
+		if let Some(location) = first_id(&repair.original).and_then(|id| locations.get(id.as_str())) {
+			diagnostics.push( (location.order, diagnostic(severity, kind, message, location)) );
+		}
+	}
+	check_added_operators(canonical, &locations, &mut diagnostics);
+
+	diagnostics.sort_by_key(|(order, _)| *order);	// stable, so the order of diagnostics for the same element is kept
+	return Ok( diagnostics.into_iter().map(|(_, diagnostic)| diagnostic).collect() );
+}
+
+/// Add an 'id' to every element that doesn't have one and record the location of every element
+fn add_locations(mathml: Element, path: String, locations: &mut HashMap<String, Location>) {
+	let id = match mathml.attribute_value("id") {
+		Some(id) => id.to_string(),
+		None => {
+			let id = format!("{}{}", LINT_ID_PREFIX, locations.len());
+			mathml.set_attribute_value("id", &id);
+			id
+		},
+	};
+	let author_id = if id.starts_with(LINT_ID_PREFIX) {String::new()} else {id.clone()};
+	locations.insert(id, Location{ order: locations.len(), path: path.clone(), id: author_id });
+
+	if is_leaf(mathml) {
+		return;
+	}
+	let mut counts: HashMap<&str, usize> = HashMap::new();
+	for child in mathml.children() {
+		let child = as_element(child);
+		let count = counts.entry(name(child)).or_insert(0);
+		*count += 1;
+		add_locations(child, format!("{}/{}[{}]", path, name(child), count), locations);
+	}
+}
+
+fn diagnostic(severity: &str, kind: &str, message: String, location: &Location) -> LintDiagnostic {
+	return LintDiagnostic {
+		severity: severity.to_string(),
+		kind: kind.to_string(),
+		message,
+		id: location.id.clone(),
+		location: location.path.clone(),
+	};
+}
+
+/// Returns the first 'id' in the MathML string
+fn first_id(mathml: &str) -> Option<String> {
+	static ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bid=['"]([^'"]*)['"]"#).unwrap());
+	return ID.captures(mathml).map(|cap| cap[1].to_string());
+}
+
+fn location_of<'a>(element: Element, locations: &'a HashMap<String, Location>) -> Option<&'a Location> {
+	return element.attribute_value("id").and_then(|id| locations.get(id));
+}
+
+/// Checks that are done on the author's MathML.
+/// 'has_intent' is true if 'mathml' or an ancestor has an 'intent' (which resolves ambiguities for speech)
+fn check_input(mathml: Element, has_intent: bool, locations: &HashMap<String, Location>, diagnostics: &mut Vec<(usize, LintDiagnostic)>) {
+	let has_intent = has_intent || mathml.attribute("intent").is_some();
+	let Some(location) = location_of(mathml, locations) else {
+		return;
+	};
+	let mut add = |severity: &str, kind: &str, message: String| {
+		diagnostics.push( (location.order, diagnostic(severity, kind, message, location)) );
+	};
+	match name(mathml) {
+		"mi" => {
+			let text = as_text(mathml);
+			if !has_intent && text.chars().count() > 1 && text.chars().all(char::is_alphabetic) &&
+			   !is_known_function_name_or_word(text) {
+				add("warning", "unknown-multi-letter-mi",
+					format!("'{text}' is not a known function name, so it might be spoken as a word or as separate letters; \
+					         use separate 'mi's for a product or add an 'intent'"));
+			}
+		},
+		"mtext" => {
+			let text = as_text(mathml).trim();
+			if !text.is_empty() && !text.chars().any(char::is_alphanumeric) && is_operator_text(text) {
+				add("warning", "mtext-operator", format!("'{text}' is an operator; use an 'mo' instead of an 'mtext'"));
+			}
+		},
+		"mfenced" => {
+			add("warning", "deprecated-mfenced",
+				"'mfenced' is deprecated; use an 'mrow' with 'mo's for the fences and separators".to_string());
+		},
+		_ => (),
+	}
+	if is_leaf(mathml) {
+		return;
+	}
+
+	if !has_intent {
+		// more than two vertical bars in a row means the pairing has to be guessed (e.g., |x|y|z|)
+		let children = mathml.children();
+		let mut bar_counts: HashMap<&str, usize> = HashMap::new();
+		for child in &children {
+			let child = as_element(*child);
+			if name(child) == "mo" && is_ambiguous_fence_operator(as_text(child)) {
+				*bar_counts.entry(as_text(child)).or_insert(0) += 1;
+			}
+		}
+		for (bar, _) in bar_counts.into_iter().filter(|(_, count)| *count > 2) {
+			let first_bar = children.iter()
+				.map(|child| as_element(*child))
+				.find(|&child| name(child) == "mo" && as_text(child) == bar)
+				.unwrap();
+			if let Some(location) = location_of(first_bar, locations) {
+				diagnostics.push( (location.order, diagnostic("warning", "ambiguous-vertical-bar",
+					format!("it isn't clear how the '{bar}'s are paired; use 'mrow's to group them or add an 'intent'"), location)) );
+			}
+		}
+	}
+
+	for child in mathml.children() {
+		check_input(as_element(child), has_intent, locations, diagnostics);
+	}
+}
+
+/// Find the invisible function applications and times that canonicalization added.
+/// The diagnostic is put on the author's element (or the last one inside it) that is before the added operator.
+/// Nothing is reported inside of an element with an 'intent' because the author has said what it means.
+fn check_added_operators(mathml: Element, locations: &HashMap<String, Location>, diagnostics: &mut Vec<(usize, LintDiagnostic)>) {
+	if is_leaf(mathml) || mathml.attribute("intent").is_some() {
+		return;
+	}
+	let children = mathml.children();
+	for (i, child) in children.iter().enumerate() {
+		let child = as_element(*child);
+		if i > 0 && name(child) == "mo" && child.attribute_value(CHANGED_ATTR) == Some(ADDED_ATTR_VALUE) {
+			let previous = as_element(children[i-1]);
+			let diagnostic_info = match as_text(child) {
+				"\u{2061}" => {
+					let function_name = if name(previous) == "mi" {as_text(previous)} else {""};
+					let (severity, how) = if is_known_function_name_or_word(function_name) {
+						("info", "")
+					} else {
+						("warning", ", so it was guessed to be a function")
+					};
+					Some( (severity, "missing-function-application",
+						   format!("the function is not followed by an invisible function application (U+2061){how}")) )
+				},
+				"\u{2062}" => Some( ("info", "missing-invisible-times",
+						"there is no invisible times (U+2062) between the operands".to_string()) ),
+				_ => None,
+			};
+			if let Some((severity, kind, message)) = diagnostic_info &&
+			   let Some(location) = last_location(previous, locations) {
+				diagnostics.push( (location.order, diagnostic(severity, kind, message, location)) );
+			}
+		}
+		check_added_operators(child, locations, diagnostics);
+	}
+
+	/// The location of 'element' or the last element inside of it that is from the author's MathML
+	fn last_location<'a>(element: Element, locations: &'a HashMap<String, Location>) -> Option<&'a Location> {
+		if let Some(location) = location_of(element, locations) {
+			return Some(location);
+		}
+		if is_leaf(element) {
+			return None;
+		}
+		return element.children().iter().rev().find_map(|&child| last_location(as_element(child), locations));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::interface::*;
+	use crate::abs_rules_dir_path;
+
+	/// Returns (severity, kind, location) for each diagnostic
+	fn lint(mathml: &str) -> Vec<(String, String, String)> {
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		return lint_mathml(mathml).unwrap().into_iter()
+			.map(|diagnostic| (diagnostic.severity, diagnostic.kind, diagnostic.location))
+			.collect();
+	}
+
+	fn expected(diagnostics: &[(&str, &str, &str)]) -> Vec<(String, String, String)> {
+		return diagnostics.iter()
+			.map(|(severity, kind, location)| (severity.to_string(), kind.to_string(), location.to_string()))
+			.collect();
+	}
+
+	#[test]
+	fn clean_mathml() {
+		let mathml = "<math><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi><mo>+</mo><mn>2</mn><mo>&#x2062;</mo><mi>y</mi></math>";
+		assert!(lint(mathml).is_empty());
+	}
+
+	#[test]
+	fn function_application_and_times() {
+		let mathml = "<math><mi>f</mi><mo>(</mo><mi>x</mi><mo>)</mo><mo>+</mo><mn>2</mn><mi>y</mi><mo>+</mo><mi>cos</mi><mi>x</mi></math>";
+		assert_eq!(lint(mathml), expected(&[
+			("warning", "missing-function-application", "/math/mi[1]"),
+			("info", "missing-invisible-times", "/math/mn[1]"),
+			("info", "missing-function-application", "/math/mi[4]"),
+		]));
+	}
+
+	#[test]
+	fn identifiers() {
+		let mathml = "<math><mrow><mi>xyz</mi><mo>=</mo><mi>l</mi><mi>o</mi><mi>g</mi><mo>&#x2061;</mo><mi>x</mi></mrow>
+					  <mo>+</mo><mi intent='speed'>spd</mi></math>";
+		assert_eq!(lint(mathml), expected(&[
+			("warning", "unknown-multi-letter-mi", "/math/mrow[1]/mi[1]"),
+			("warning", "split-mi", "/math/mrow[1]/mi[2]"),
+		]));
+	}
+
+	#[test]
+	fn numbers_and_mtext() {
+		let mathml = "<math><mn>1</mn><mo>,</mo><mn>234</mn><mtext>+</mtext><mn id='neg'>-2</mn></math>";
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		let diagnostics = lint_mathml(mathml).unwrap();
+		assert_eq!(diagnostics.iter().map(|diagnostic| diagnostic.kind.as_str()).collect::<Vec<&str>>(),
+				   vec!["split-number", "mtext-operator", "mn-with-operator"]);
+		assert_eq!(diagnostics[2].id, "neg");
+		assert_eq!(diagnostics[2].location, "/math/mn[3]");
+	}
+
+	#[test]
+	fn vertical_bars_and_mfenced() {
+		let mathml = "<math>
+				<mrow><mo>|</mo><mi>x</mi><mo>|</mo><mo>&#x2062;</mo><mi>y</mi><mo>|</mo><mi>z</mi><mo>|</mo></mrow>
+				<mo>+</mo><mfenced><mi>a</mi><mi>b</mi></mfenced>
+				<mo>+</mo><mrow intent='foo'><mo>|</mo><mi>x</mi><mo>|</mo><mi>y</mi><mo>|</mo></mrow>
+			</math>";
+		assert_eq!(lint(mathml), expected(&[
+			("warning", "ambiguous-vertical-bar", "/math/mrow[1]/mo[1]"),
+			("info", "missing-invisible-times", "/math/mrow[1]/mi[2]"),
+			("warning", "deprecated-mfenced", "/math/mfenced[1]"),
+		]));
+	}
+
+	#[test]
+	fn math_not_changed() {
+		set_rules_dir(abs_rules_dir_path()).unwrap();
+		let canonical_mathml = set_mathml("<math><mi>x</mi></math>").unwrap();
+		lint_mathml("<math><mfenced><mi>a</mi><mi>b</mi></mfenced></math>").unwrap();
+		assert_eq!(get_navigation_mathml().unwrap().0, canonical_mathml);
+	}
+}
@@ -9,15 +9,21 @@ use sxd_document::dom::Element;
 use sxd_document::Package;
 
 use std::fmt;
-use crate::canonicalize::{name, get_parent};
-use crate::pretty_print::mml_to_string;
-use crate::speech::{NAVIGATION_RULES, CONCAT_INDICATOR, CONCAT_STRING, SpeechRules, SpeechRulesWithContext};
+use std::collections::HashMap;
+use std::path::Path;
+use yaml_rust::{Yaml, YamlLoader, yaml::Hash};
+use crate::canonicalize::{as_text, name, get_parent, MATHML_FROM_NAME_ATTR};
+use crate::xpath_functions::{is_ancestor, is_leaf};
+use crate::pretty_print::{mml_to_string, yaml_to_string};
+use crate::prefs::PreferenceManager;
+use crate::shim_filesystem::{is_file_shim, read_to_string_shim};
+use crate::speech::{NAVIGATION_RULES, CONCAT_INDICATOR, CONCAT_STRING, FileAndTime, SpeechRules, SpeechRulesWithContext};
 use crate::infer_intent::add_fixity_children;
 use crate::interface::copy_mathml;
 #[cfg(not(target_family = "wasm"))]
 use std::time::Instant;
 use crate::errors::*;
-use phf::phf_set;
+use phf::{phf_map, phf_set};
 use log::{debug};
 
 pub const ID_OFFSET: &str = "data-id-offset";
@@ -34,12 +40,18 @@ pub static NAV_COMMANDS: phf::Set<&str> = phf_set! {
     "MovePrevious", "MoveNext", "MoveStart", "MoveEnd", "MoveLineStart", "MoveLineEnd", 
     "MoveCellPrevious", "MoveCellNext", "MoveCellUp", "MoveCellDown", "MoveColumnStart", "MoveColumnEnd", 
     "ZoomIn", "ZoomOut", "ZoomOutAll", "ZoomInAll", 
-    "MoveLastLocation", 
+    "MoveLastLocation", "MoveNextLocation", 
     "ReadPrevious", "ReadNext", "ReadCurrent", "ReadCellCurrent", "ReadStart", "ReadEnd", "ReadLineStart", "ReadLineEnd", 
     "DescribePrevious", "DescribeNext", "DescribeCurrent", 
-    "WhereAmI", "WhereAmIAll", 
+    "WhereAmI", "WhereAmIAll", "ReadHistory", 
     "ToggleZoomLockUp", "ToggleZoomLockDown", "ToggleSpeakMode", 
     "Exit", 
+    "FindNext", "FindPrevious",
+    "ReadRow", "ReadColumn", "ReadRowHeader", "ReadColumnHeader", "MoveToRow", "MoveToColumn",
+    "MoveNextRelation", "MovePreviousRelation", "MoveNextTerm", "MovePreviousTerm",
+    "MoveNextFraction", "MovePreviousFraction", "MoveNextRoot", "MovePreviousRoot",
+    "MoveNextPower", "MovePreviousPower", "MoveNextFunction", "MovePreviousFunction",
+    "MoveNextTable", "MovePreviousTable", "MoveNextChemistry", "MovePreviousChemistry",
     "MoveTo0","MoveTo1","MoveTo2","MoveTo3","MoveTo4","MoveTo5","MoveTo6","MoveTo7","MoveTo8","MoveTo9",
     "Read0","Read1","Read2","Read3","Read4","Read5","Read6","Read7","Read8","Read9",
     "Describe0","Describe1","Describe2","Describe3","Describe4","Describe5","Describe6","Describe7","Describe8","Describe9",
@@ -75,6 +87,7 @@ pub struct NavigationState {
     // these two stacks should be kept in sync.
     position_stack: Vec<NavigationPosition>,    // all positions, so we can go back to them
     command_stack: Vec<&'static str>,           // all commands, so we can undo them
+    redo_stack: Vec<(NavigationPosition, &'static str)>,  // positions/commands undone by MoveLastLocation (for MoveNextLocation)
     place_markers: [NavigationPosition; MAX_PLACE_MARKERS],
     where_am_i: NavigationPosition,             // current 'where am i' location
 
@@ -84,6 +97,9 @@ pub struct NavigationState {
     where_am_i_start_time: Instant,
     mode: String,                               // one of "Character", "Simple", or "Enhanced"
     speak_overview: bool,                       // true => describe after move; false => (standard) speech rules
+    find_target: String,                       // what FindNext/FindPrevious look for (text or intent name)
+    move_to_number: usize,                      // the row/column (1-based) MoveToRow/MoveToColumn move to
+    at_boundary: bool,                          // the last command tried to move, but the position didn't change (e.g., "no next item")
 }
 
 impl fmt::Display for NavigationState {
@@ -99,8 +115,14 @@ impl fmt::Display for NavigationState {
             write!(f, "{}{}", if i==0 {""} else {", "}, *nav_state)?;
         }
         writeln!(f)?;
+        write!(f, "  Redo Stack: ")?;
+        for (i, (position, command)) in self.redo_stack.iter().enumerate() {
+            write!(f, "{}{}/{}", if i==0 {""} else {", "}, position, command)?;
+        }
+        writeln!(f)?;
         writeln!(f, "  where_am_i: {}, start_time: {:?}", self.where_am_i, self.where_am_i_start_time)?;
-        writeln!(f, "  mode: {}, speak_overview: {}", self.mode, self.speak_overview)?;
+        writeln!(f, "  mode: {}, speak_overview: {}, find_target: '{}', move_to_number: {}, at_boundary: {}",
+                 self.mode, self.speak_overview, self.find_target, self.move_to_number, self.at_boundary)?;
         writeln!(f, "}}")?;
         return Ok( () );
     }
@@ -111,6 +133,7 @@ impl NavigationState {
         return NavigationState {
             position_stack: Vec::with_capacity(1024),
             command_stack: Vec::with_capacity(1024),
+            redo_stack: Vec::new(),
             place_markers: Default::default(),
             where_am_i: NavigationPosition::default(),
             // FIX: figure this out for the web
@@ -120,16 +143,26 @@ impl NavigationState {
             where_am_i_start_time: Instant::now(),      // need to give it some value, and "default()" isn't an option
             mode: "".to_string(),                       // set latter when we have some context
             speak_overview: false,                      // set latter when we have some context
+            find_target: "".to_string(),
+            move_to_number: 1,
+            at_boundary: false,
         };
     }
 
     pub fn reset(&mut self) {
         self.position_stack.clear();
         self.command_stack.clear();
+        self.redo_stack.clear();
         self.where_am_i = NavigationPosition::default();
+        self.at_boundary = false;
         self.reset_start_time()
     }
 
+    /// Returns true if the last navigation command tried to move but the position didn't change (e.g., "no next item")
+    pub fn is_at_boundary(&self) -> bool {
+        return self.at_boundary;
+    }
+
 
     // defining reset_start_time because of the following message if done inline
     // attributes on expressions are experimental
@@ -167,6 +200,197 @@ impl NavigationState {
         return Some( (&self.position_stack[last], self.command_stack[last]) );
     }
 
+    /// The positions visited before the current one (most recent first), without duplicates, and at most 'n' of them
+    fn previous_positions(&self, n: usize) -> Vec<&NavigationPosition> {
+        let mut positions: Vec<&NavigationPosition> = Vec::with_capacity(n);
+        if let Some((current, _)) = self.top() {
+            for position in self.position_stack.iter().rev().skip(1) {
+                if positions.len() == n {
+                    break;
+                }
+                if position != current && !positions.contains(&position) {
+                    positions.push(position);
+                }
+            }
+        }
+        return positions;
+    }
+
+    /// The navigation history (oldest first): the id and offset of each position along with the command that moved there
+    pub fn get_history(&self) -> Vec<(String, usize, &'static str)> {
+        return self.position_stack.iter().zip(self.command_stack.iter())
+                .map(|(position, command)| (position.current_node.clone(), position.current_node_offset, *command))
+                .collect();
+    }
+
+    pub fn get_mode(&self) -> String {
+        return self.mode.clone();
+    }
+
+    pub fn get_speak_overview(&self) -> bool {
+        return self.speak_overview;
+    }
+
+    /// Save the navigation state (positions, positions to redo, place markers, 'where am i' position, find target, mode, and overview flag)
+    ///   as a (YAML) string.
+    /// The ids that `set_mathml` adds differ each time it is called, so positions are saved as the path (child indices) from `mathml`.
+    /// That means the state can be restored after calling `set_mathml` again with the same MathML.
+    pub fn serialize(&self, mathml: Element) -> String {
+        let mut elements = HashMap::new();
+        collect_elements_with_ids(mathml, &mut elements);
+
+        let mut state = Hash::new();
+        state.insert(Yaml::String("mode".to_string()), Yaml::String(self.mode.clone()));
+        state.insert(Yaml::String("overview".to_string()), Yaml::Boolean(self.speak_overview));
+        let positions = self.position_stack.iter().zip(self.command_stack.iter())
+                .map(|(position, command)| position_and_command_to_yaml(position, command, mathml, &elements))
+                .collect();
+        state.insert(Yaml::String("positions".to_string()), Yaml::Array(positions));
+        let redo_positions = self.redo_stack.iter()
+                .map(|(position, command)| position_and_command_to_yaml(position, command, mathml, &elements))
+                .collect();
+        state.insert(Yaml::String("redo_positions".to_string()), Yaml::Array(redo_positions));
+        let place_markers = self.place_markers.iter()
+                .map(|position| optional_position_to_yaml(position, mathml, &elements))
+                .collect();
+        state.insert(Yaml::String("place_markers".to_string()), Yaml::Array(place_markers));
+        state.insert(Yaml::String("where_am_i".to_string()), optional_position_to_yaml(&self.where_am_i, mathml, &elements));
+        state.insert(Yaml::String("find_target".to_string()), Yaml::String(self.find_target.clone()));
+        return yaml_to_string(&Yaml::Hash(state), 0);
+
+        /// Returns [path, id-suffix, offset, command]
+        fn position_and_command_to_yaml(position: &NavigationPosition, command: &str, mathml: Element, elements: &HashMap<&str, Element>) -> Yaml {
+            let mut position = position_to_yaml(position, mathml, elements);
+            position.push(Yaml::String(command.to_string()));
+            return Yaml::Array(position);
+        }
+
+        /// Returns [path, id-suffix, offset] or null if the position isn't set
+        fn optional_position_to_yaml(position: &NavigationPosition, mathml: Element, elements: &HashMap<&str, Element>) -> Yaml {
+            return if position.current_node == ILLEGAL_NODE_ID {Yaml::Null} else {Yaml::Array(position_to_yaml(position, mathml, elements))};
+        }
+
+        fn collect_elements_with_ids<'a>(element: Element<'a>, elements: &mut HashMap<&'a str, Element<'a>>) {
+            if let Some(id) = element.attribute_value("id") {
+                elements.insert(id, element);
+            }
+            for child in element.children() {
+                if let Some(child) = child.element() {
+                    collect_elements_with_ids(child, elements);
+                }
+            }
+        }
+
+        /// Returns [path, id-suffix, offset] where the path leads to the MathML element whose id starts the position's id.
+        /// Ids in the intent tree are derived from the MathML ids (e.g., 'id-3-fixity-1'), hence the suffix ('-fixity-1').
+        fn position_to_yaml(position: &NavigationPosition, mathml: Element, elements: &HashMap<&str, Element>) -> Vec<Yaml> {
+            let id = position.current_node.as_str();
+            let i_split = if elements.contains_key(id) {
+                Some(id.len())
+            } else {
+                id.match_indices('-').map(|(i, _)| i).rev().find(|&i| elements.contains_key(&id[..i]))
+            };
+            let (path, suffix) = match i_split {
+                Some(i) => {
+                    let mut path = Vec::new();
+                    let mut element = elements[&id[..i]];
+                    while element != mathml && let Some(parent) = element.parent().and_then(|parent| parent.element()) {
+                        let i_child = parent.children().into_iter()
+                                .filter_map(|child| child.element())
+                                .position(|child| child == element)
+                                .unwrap();      // 'element' is a child of 'parent'
+                        path.push(Yaml::Integer(i_child as i64));
+                        element = parent;
+                    }
+                    path.reverse();
+                    (Yaml::Array(path), &id[i..])
+                },
+                None => (Yaml::Null, id),    // shouldn't happen -- restoring will fail
+            };
+            return vec![path, Yaml::String(suffix.to_string()), Yaml::Integer(position.current_node_offset as i64)];
+        }
+    }
+
+    /// Restore the navigation state from a string created by `serialize`.
+    /// An error is returned if the state has positions that are not in `mathml` (i.e., it was saved for different MathML).
+    pub fn deserialize(mathml: Element, state: &str) -> Result<NavigationState> {
+        let state = match YamlLoader::load_from_str(state) {
+            Ok(mut docs) if docs.len() == 1 => docs.pop().unwrap(),
+            Ok(_) => bail!("Saved navigation state should be a single YAML document"),
+            Err(e) => bail!("Yaml parse error ('{}') in saved navigation state", e),
+        };
+
+        let mut nav_state = NavigationState::new();
+        nav_state.mode = match state["mode"].as_str() {
+            Some(mode) if ["Enhanced", "Simple", "Character", ""].contains(&mode) => mode.to_string(),
+            _ => bail!("Saved navigation state has a missing or illegal 'mode'"),
+        };
+        nav_state.speak_overview = match state["overview"].as_bool() {
+            Some(overview) => overview,
+            None => bail!("Saved navigation state is missing 'overview' (true/false)"),
+        };
+        let positions = match state["positions"].as_vec() {
+            Some(positions) => positions,
+            None => bail!("Saved navigation state is missing 'positions'"),
+        };
+        for position in positions {
+            nav_state.push(yaml_to_position(position, mathml)?, yaml_to_command(position)?);
+        }
+        if let Some(redo_positions) = state["redo_positions"].as_vec() {
+            for position in redo_positions {
+                nav_state.redo_stack.push( (yaml_to_position(position, mathml)?, yaml_to_command(position)?) );
+            }
+        }
+        if let Some(place_markers) = state["place_markers"].as_vec() {
+            for (i, position) in place_markers.iter().enumerate().take(MAX_PLACE_MARKERS) {
+                if !position.is_null() {
+                    nav_state.place_markers[i] = yaml_to_position(position, mathml)?;
+                }
+            }
+        }
+        if !state["where_am_i"].is_badvalue() && !state["where_am_i"].is_null() {
+            nav_state.where_am_i = yaml_to_position(&state["where_am_i"], mathml)?;
+        }
+        if let Some(find_target) = state["find_target"].as_str() {
+            nav_state.find_target = find_target.to_string();
+        }
+        return Ok(nav_state);
+
+        /// Returns the command in [path, id-suffix, offset, command]
+        fn yaml_to_command(position: &Yaml) -> Result<&'static str> {
+            return match position[3].as_str() {
+                Some("None") => Ok("None"),
+                Some(command) => match NAV_COMMANDS.get_key(command) {
+                    Some(command) => Ok(*command),
+                    None => bail!("Saved navigation state has unknown command '{}'", command),
+                },
+                None => bail!("Saved navigation position {} is missing a command", yaml_to_string(position, 0)),
+            };
+        }
+
+        fn yaml_to_position(position: &Yaml, mathml: Element) -> Result<NavigationPosition> {
+            let (path, suffix, offset) = match (position[0].as_vec(), position[1].as_str(), position[2].as_i64()) {
+                (Some(path), Some(suffix), Some(offset)) if offset >= 0 => (path, suffix, offset as usize),
+                _ => bail!("Saved navigation position {} should be [path, id-suffix, offset, ...]", yaml_to_string(position, 0)),
+            };
+            let mut element = mathml;
+            for i_child in path {
+                let child = i_child.as_i64()
+                        .and_then(|i_child| usize::try_from(i_child).ok())
+                        .and_then(|i_child| element.children().into_iter().filter_map(|child| child.element()).nth(i_child));
+                element = match child {
+                    Some(child) => child,
+                    None => bail!("Saved navigation position {} is not in the MathML -- was it saved for different MathML?", yaml_to_string(position, 0)),
+                };
+            }
+            let id = match element.attribute_value("id") {
+                Some(id) => id,
+                None => bail!("Saved navigation position {} leads to an element without an id", yaml_to_string(position, 0)),
+            };
+            return Ok( NavigationPosition{ current_node: id.to_string() + suffix, current_node_offset: offset } );
+        }
+    }
+
     pub fn get_navigation_mathml<'a>(&self, mathml: Element<'a>) -> Result<(Element<'a>, usize)> {
         if self.position_stack.is_empty() {
             return Ok( (mathml, 0) );
@@ -212,6 +436,14 @@ impl NavigationState {
         }
            
         context.set_variable("Overview", self.speak_overview);
+        context.set_variable("FoundNode", "");              // set for FindNext/FindPrevious once the nav tree is known
+        context.set_variable("JumpCategory", *JUMP_COMMANDS.get(command).unwrap_or(&""));
+        context.set_variable("FoundNodeOffset", 0 as f64);
+        context.set_variable("TableNode", "");              // set for the table commands once the nav tree is known
+        context.set_variable("TableTarget", "");
+        context.set_variable("TableTargetOffset", 0 as f64);
+        context.set_variable("TableTargetNumber", 0 as f64);
+        context.set_variable("TableTargetIsRow", false);
         context.set_variable("ReadZoomLevel", (if self.mode == "Enhanced" {-1} else {1}) as f64);
         context.set_variable("MatchCounter", 0 as f64);
 
@@ -221,6 +453,12 @@ impl NavigationState {
                 Some( (_, previous_command) ) => previous_command,
             };
             context.set_variable("PreviousNavCommand", previous_command);
+        } else if command == "MoveNextLocation" {
+            let next_command = match self.redo_stack.last() {
+                None => "None",
+                Some( (_, next_command) ) => next_command,
+            };
+            context.set_variable("NextNavCommand", next_command);
         }
 
         // used by nav rules for speech -- needs an initial value so tests don't fail
@@ -356,12 +594,15 @@ fn context_get_int_variable<'c>(context: &sxd_xpath::Context<'c>, var_name: &str
 /// The spoken text for the new current node is returned.
 pub fn do_mathml_navigate_key_press(mathml: Element,
             key: usize, shift_key: bool, control_key: bool, alt_key: bool, meta_key: bool) -> Result<String> {
-    let (command, param) = key_press_to_command_and_param(key, shift_key, control_key, alt_key, meta_key)?;
-    return do_navigate_command_and_param(mathml, command, param);
+    let command = key_press_to_command(key, shift_key, control_key, alt_key, meta_key)?;
+    return do_navigate_command_string(mathml, command);
 }
 
-fn do_navigate_command_and_param(mathml: Element, command: NavigationCommand, param: NavigationParam) -> Result<String> {
-    return do_navigate_command_string(mathml, navigation_command_string(command, param));
+/// Given a touch gesture (e.g., "SwipeLeft" or "TwoFingerDoubleTap"), the current node is moved accordingly (or value reported in some cases).
+/// The spoken text for the new current node is returned.
+pub fn do_mathml_navigate_gesture(mathml: Element, gesture: &str) -> Result<String> {
+    let command = gesture_to_command(gesture)?;
+    return do_navigate_command_string(mathml, command);
 }
 
 pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) -> Result<String> {   
@@ -394,10 +635,21 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
             nav_state.speak_overview = rules.pref_manager.as_ref().borrow().pref_to_string("Overview") == "true";
 
             nav_state.init_navigation_context(rules_with_context.get_context(), nav_command, nav_state.top());
+            let start_position = nav_state.top().map(|(position, _)| position.clone());
+            nav_state.at_boundary = false;
+            if nav_command == "ReadHistory" {
+                let n_positions = nav_state.previous_positions(history_count(&rules)).len();
+                rules_with_context.get_context().set_variable("HistoryCount", n_positions as f64);
+            }
             
             // start navigation off at the right node
             if nav_command == "MoveLastLocation" {
-                nav_state.pop();
+                if let Some(undone) = nav_state.pop() {
+                    nav_state.redo_stack.push(undone);
+                }
+            } else if nav_command == "MoveNextLocation" &&
+                      let Some( (position, command) ) = nav_state.redo_stack.pop() {
+                nav_state.push(position, command);
             }
 
             // If no speech happened for some calls, we try the call again (e.g, no speech for invisible times).
@@ -409,16 +661,18 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
                     Ok( (speech, done)) => {
                         cumulative_speech = cumulative_speech + if loop_count==0 {""} else {" "} + speech.trim();
                         if done {
+                            nav_state.at_boundary = (is_movement_command(nav_command) || nav_command == "MoveLastLocation") &&
+                                                    nav_state.top().map(|(position, _)| position) == start_position.as_ref();
                             let (tts, rate) = {
                                 let prefs = rules.pref_manager.borrow();
                                 (prefs.pref_to_string("TTS"), prefs.pref_to_string("MathRate"))
                             };
                             if rate != "100" {
                                 match tts.as_str() {
-                                    "SSML" => if !cumulative_speech.starts_with("<prosody rate") {
+                                    "SSML" if !cumulative_speech.starts_with("<prosody rate") => {
                                         cumulative_speech = format!("<prosody rate='{}%'>{}</prosody>", &rate, &cumulative_speech);
                                     }, 
-                                    "SAPI5" => if !cumulative_speech.starts_with("<rate speed") {
+                                    "SAPI5" if !cumulative_speech.starts_with("<rate speed") => {
                                         cumulative_speech = format!("<rate speed='{:.1}'>{}</rate>'>",
                                         10.0*(0.01*rate.parse::<f32>().unwrap_or(100.0)).log(3.0), cumulative_speech);
                                     },
@@ -430,7 +684,7 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
                                                 &cumulative_speech.replace(CONCAT_STRING, "")
                                                                     .replace(CONCAT_INDICATOR, "")                            
                                                             )
-                                            .trim_start().trim_end_matches([' ', ',', ';'])) );
+                                            .trim_matches([' ', ',', ';'])) );     // e.g., a row's speech starts with a pause
                         }
                     },
                     Err(e) => {
@@ -525,6 +779,24 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
         //     }
         //     debug!("parent or grandparent of start_node:\n{}", mml_to_string(parent));
         // }
+        if nav_command.starts_with("Find") || JUMP_COMMANDS.contains_key(nav_command) {
+            let context = rules_with_context.get_context();
+            let found_node = match JUMP_COMMANDS.get(nav_command) {
+                Some(category) => find_node_matching(start_node, nav_command.starts_with("MoveNext"), |node| is_in_category(node, category)),
+                None => find_node(start_node, &nav_state.find_target, nav_command == "FindNext"),
+            };
+            if let Some(found_node) = found_node {
+                context.set_variable("FoundNode", found_node.attribute_value("id").unwrap());
+                context.set_variable("FoundNodeOffset",
+                        found_node.attribute_value(ID_OFFSET).unwrap_or_default().parse::<usize>().unwrap_or_default() as f64);
+            }
+        }
+        let column_positions = if TABLE_COMMANDS.contains(nav_command) {
+            let offset = nav_state.top().unwrap().0.current_node_offset;
+            set_table_variables(rules_with_context.get_context(), start_node, offset, nav_command, nav_state.move_to_number)
+        } else {
+            Vec::new()
+        };
         let offset = context_get_int_variable(rules_with_context.get_context(), "NavNodeOffset", intent)?;
         rules_with_context.set_nav_node_offset(offset);
         debug!("starting nav_position: {}, start node ={}", nav_state.top().unwrap().0, name(start_node));
@@ -570,6 +842,9 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
         // push the new location on the stack
         if nav_position != NavigationPosition::default() && &nav_position != nav_state.top().unwrap().0 {
             nav_state.push(nav_position.clone(), nav_command);
+            if is_movement_command(nav_command) && nav_command != "MoveNextLocation" {
+                nav_state.redo_stack.clear();       // a new move, so there is nothing to redo
+            }
         }
 
         if nav_command.starts_with("SetPlacemarker") {
@@ -585,7 +860,19 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
             // Speak/Overview of where we landed (if we are supposed to speak it) -- use intent, not nav_intent
             // Note: NavMode might have changed, so we need to recheck the mode to see if we use LiteralSpeak
             let literal_speak = nav_state.mode == "Character";
-            let node_speech = match speak(mathml, intent, &nav_position, literal_speak, use_read_rules) {
+            let difference_speech = if use_read_rules && DIFFERENCE_COMMANDS.contains(nav_command) &&
+                                       rules.pref_manager.borrow().pref_to_string("NavDifferenceSpeech") == "true" &&
+                                       nav_state.position_stack.last() == Some(&nav_position) &&
+                                       let Some(new_node) = get_node_by_id(nav_intent, &nav_position) &&
+                                       // compare with where we were, skipping over punctuation, operators, etc (e.g., the ',' in 'x_1, x_2')
+                                       let Some(old_node) = nav_state.position_stack.iter().rev().skip(1).take(3)
+                                                .filter_map(|position| get_node_by_id(nav_intent, position))
+                                                .find(|&node| !is_leaf(node)) {
+                difference_speech(mathml, intent, old_node, new_node, literal_speak)
+            } else {
+                None
+            };
+            let node_speech = match difference_speech.map_or_else(|| speak(mathml, intent, &nav_position, literal_speak, use_read_rules), Ok) {
                 Ok(speech) => speech,
                 Err(e) => {
                     remove_literal_property(mathml, add_literal, properties);
@@ -611,6 +898,26 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
                 return Ok( (speech + " " + &node_speech, true) );
             }
         } else {
+            let speech = if nav_command == "ReadHistory" {
+                let literal_speak = nav_state.mode == "Character";
+                let mut history_speech = Vec::new();
+                for position in nav_state.previous_positions(history_count(rules)) {
+                    if get_node_by_id(if literal_speak {mathml} else {intent}, position).is_some() ||
+                       get_node_by_id(mathml, position).is_some() {
+                        history_speech.push(speak(mathml, intent, position, literal_speak, true)?);
+                    }
+                }
+                speech + " " + &history_speech.join("; ")
+            } else if nav_command == "ReadColumn" && !column_positions.is_empty() {
+                let literal_speak = nav_state.mode == "Character";
+                let mut column_speech = Vec::with_capacity(column_positions.len());
+                for position in &column_positions {
+                    column_speech.push(speak(mathml, intent, position, literal_speak, true)?);
+                }
+                speech + " " + &column_speech.join("; ")
+            } else {
+                speech
+            };
             remove_literal_property(mathml, add_literal, properties);
             pop_stack(nav_state, loop_count, nav_command);
             return Ok( (speech, true) );
@@ -629,9 +936,14 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
     }
 
 
+    /// Number of positions spoken by "ReadHistory"
+    fn history_count(rules: &SpeechRules) -> usize {
+        return rules.pref_manager.borrow().pref_to_string("NavHistoryCount").parse::<f64>().unwrap_or(0.0).max(0.0) as usize;
+    }
+
     fn pop_stack(nav_state: &mut NavigationState, count: usize, nav_command: &'static str) {
         // save the final state and pop the intermediate states that did nothing
-        let push_command_on_stack = (nav_command.starts_with("Move") && nav_command != "MoveLastLocation") || nav_command.starts_with("Zoom");
+        let push_command_on_stack = is_movement_command(nav_command);
         // debug!("pop_stack: nav_command={}, count={}, push? {} stack=\n{}", nav_command, count, push_command_on_stack, nav_state);
         if count == 0 {
             if !push_command_on_stack && nav_command == nav_state.top().unwrap().1 {
@@ -656,6 +968,392 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
     }
 }
 
+/// Commands that change the position (and so are remembered on the stack)
+fn is_movement_command(nav_command: &str) -> bool {
+    return (nav_command.starts_with("Move") && nav_command != "MoveLastLocation") ||
+           nav_command.starts_with("Zoom") || nav_command.starts_with("Find");
+}
+
+/// Set what `FindNext`/`FindPrevious` search for and do the search.
+/// If `target` is empty, the previous target is used.
+pub fn do_navigate_find_string(mathml: Element, nav_command: &'static str, target: &str) -> Result<String> {
+    if !nav_command.starts_with("Find") {
+        bail!("'{}' is not a find command (FindNext or FindPrevious)", nav_command);
+    }
+    let target = target.trim();
+    if !target.is_empty() {
+        NAVIGATION_STATE.with(|nav_state| nav_state.borrow_mut().find_target = target.to_string());
+    }
+    return do_navigate_command_string(mathml, nav_command);
+}
+
+/// Intent names that can be searched for which are not names used in the (navigation) tree
+static FIND_INTENT_NAMES: phf::Map<&str, &str> = phf_map! {
+    "fraction" => "mfrac",
+    "square-root" => "msqrt",
+    "root" => "mroot",
+    "power" => "msup",
+    "subscript" => "msub",
+    "table" => "mtable",
+    "matrix" => "mtable",
+};
+
+/// The commands that jump to the next/previous node of a semantic category (like heading navigation on a web page)
+static JUMP_COMMANDS: phf::Map<&str, &str> = phf_map! {
+    "MoveNextRelation" => "relation", "MovePreviousRelation" => "relation",
+    "MoveNextTerm" => "term", "MovePreviousTerm" => "term",
+    "MoveNextFraction" => "fraction", "MovePreviousFraction" => "fraction",
+    "MoveNextRoot" => "root", "MovePreviousRoot" => "root",
+    "MoveNextPower" => "power", "MovePreviousPower" => "power",
+    "MoveNextFunction" => "function", "MovePreviousFunction" => "function",
+    "MoveNextTable" => "table", "MovePreviousTable" => "table",
+    "MoveNextChemistry" => "chemistry", "MovePreviousChemistry" => "chemistry",
+};
+
+/// Return true if `node` (in the navigation intent tree) is in the semantic `category` (one of the values in `JUMP_COMMANDS`)
+fn is_in_category(node: Element, category: &str) -> bool {
+    let element_name = name(node);
+    let from_mathml = node.attribute_value(MATHML_FROM_NAME_ATTR).unwrap_or(element_name);
+    let child_elements = || node.children().into_iter().filter_map(|child| child.element());
+    return match category {
+        "relation" => element_name == "mrow" &&
+                      child_elements().any(|child| name(child) == "mo" && crate::canonicalize::is_relational_op(child)),
+        "term" => {
+            // a child of an mrow that is a sum/difference (but not the operators)
+            from_mathml != "mo" &&
+                node.parent().and_then(|parent| parent.element()).is_some_and(|parent|
+                    name(parent) == "mrow" &&
+                    parent.children().iter().filter_map(|child| child.element())
+                          .any(|child| name(child) == "mo" && ["+", "-", "\u{2212}", "\u{00B1}", "\u{2213}"].contains(&as_text(child))))
+        },
+        "fraction" => element_name == "fraction" || from_mathml == "mfrac",
+        "root" => element_name == "square-root" || element_name == "root" || from_mathml == "msqrt" || from_mathml == "mroot",
+        "power" => element_name == "power" || element_name == "msup",
+        "function" => child_elements().any(|child| is_leaf(child) && as_text(child) == "\u{2061}"),
+        "table" => from_mathml == "mtable",
+        "chemistry" => element_name.starts_with("chemical-formula") || element_name.starts_with("chemical-equation") ||
+                       node.attribute_value("data-chem-formula").is_some() || node.attribute_value("data-chem-equation").is_some(),
+        _ => false,
+    };
+}
+
+/// Return the next/previous node (in reading order) after 'start_node' that matches `target`.
+/// A leaf matches if its text is `target`; any node matches if its name (e.g., "mfrac" or an intent name) is `target`.
+fn find_node<'a>(start_node: Element<'a>, target: &str, is_forward: bool) -> Option<Element<'a>> {
+    if target.is_empty() {
+        return None;
+    }
+    let target_name = FIND_INTENT_NAMES.get(target).copied().unwrap_or(target);
+    return find_node_matching(start_node, is_forward, |node| is_match(node, target, target_name));
+
+    fn is_match(element: Element, target: &str, target_name: &str) -> bool {
+        let element_name = name(element);
+        if element_name == "math" {
+            return false;
+        }
+        return element_name == target || element_name == target_name ||
+               (is_leaf(element) && as_text(element).trim() == target);
+    }
+}
+
+/// Return the next/previous node (in reading order) after 'start_node' for which `is_match` is true.
+/// When searching backwards, the ancestors of `start_node` are skipped (they are not before it when reading).
+fn find_node_matching<'a>(start_node: Element<'a>, is_forward: bool, is_match: impl Fn(Element<'a>) -> bool) -> Option<Element<'a>> {
+    let mut root = start_node;
+    while name(root) != "math" && let Some(parent) = root.parent().and_then(|parent| parent.element()) {
+        root = parent;
+    }
+    let mut nodes = Vec::with_capacity(64);
+    collect_nodes_with_ids(root, &mut nodes);
+    let i_start = nodes.iter().position(|&node| node == start_node)?;
+    if is_forward {
+        return nodes[i_start+1..].iter().copied().find(|&node| is_match(node));
+    } else {
+        return nodes[..i_start].iter().rev().copied()
+                .find(|&node| is_match(node) && !is_ancestor(node, start_node));
+    }
+
+    fn collect_nodes_with_ids<'a>(element: Element<'a>, nodes: &mut Vec<Element<'a>>) {
+        if element.attribute_value("id").is_some() {
+            nodes.push(element);
+        }
+        if !is_leaf(element) {
+            for child in element.children() {
+                if let Some(child) = child.element() {
+                    collect_nodes_with_ids(child, nodes);
+                }
+            }
+        }
+    }
+}
+
+/// The commands where only the differences are spoken if the "NavDifferenceSpeech" pref is true
+static DIFFERENCE_COMMANDS: phf::Set<&str> = phf_set! {
+    "MoveNext", "MovePrevious", "MoveCellNext", "MoveCellPrevious", "MoveCellUp", "MoveCellDown",
+};
+
+/// If `new_node` has the same structure as `old_node` (e.g., moving from `x_1` to `x_2`), return speech for only the leaves
+/// that differ, each along with the name of its part (e.g., "same structure, subscript 2").
+/// `None` is returned if the nodes are leaves, if the structure differs, or if nothing or everything differs.
+fn difference_speech(mathml: Element, intent: Element, old_node: Element, new_node: Element, literal_speak: bool) -> Option<String> {
+    if is_leaf(new_node) || old_node == new_node {
+        return None;
+    }
+    let mut differences = Vec::new();
+    let mut n_leaves = 0;
+    if !collect_differences(old_node, new_node, &mut differences, &mut n_leaves) || differences.is_empty() || differences.len() == n_leaves {
+        return None;
+    }
+
+    let mut speech = vec![crate::xpath_functions::GetNavigationPartName::navigation_part_name("same", 0)];
+    for leaf in differences {
+        let leaf_speech = speak(mathml, intent, &element_position(leaf), literal_speak, true).ok()?;
+        let parent = get_parent(leaf);
+        let index = parent.children().iter().filter_map(|child| child.element()).position(|child| child == leaf).unwrap_or(0);
+        let mut part_name = crate::xpath_functions::GetNavigationPartName::navigation_part_name(name(parent), index);
+        if part_name.is_empty() && let Some(from_mathml) = parent.attribute_value(MATHML_FROM_NAME_ATTR) {
+            part_name = crate::xpath_functions::GetNavigationPartName::navigation_part_name(from_mathml, index);
+        }
+        speech.push(if part_name.is_empty() {leaf_speech} else {part_name + " " + &leaf_speech});
+    }
+    return Some(speech.join(", "));
+
+    /// Returns false if the structure differs; otherwise the leaves in 'new' that differ from those in 'old' are added to `differences`
+    fn collect_differences<'a>(old: Element, new: Element<'a>, differences: &mut Vec<Element<'a>>, n_leaves: &mut usize) -> bool {
+        if name(old) != name(new) {
+            return false;
+        }
+        if is_leaf(new) {
+            *n_leaves += 1;
+            if as_text(old) != as_text(new) {
+                differences.push(new);
+            }
+            return is_leaf(old);
+        }
+        let old_children: Vec<Element> = old.children().iter().filter_map(|child| child.element()).collect();
+        let new_children: Vec<Element> = new.children().iter().filter_map(|child| child.element()).collect();
+        return old_children.len() == new_children.len() &&
+               old_children.iter().zip(new_children).all(|(&old, new)| collect_differences(old, new, differences, n_leaves));
+    }
+}
+
+/// Set `MoveToRow`/`MoveToColumn`'s row/column number (1-based) and do the move.
+pub fn do_navigate_command_with_number(mathml: Element, nav_command: &'static str, number: usize) -> Result<String> {
+    if nav_command != "MoveToRow" && nav_command != "MoveToColumn" {
+        bail!("'{}' does not take a number (only MoveToRow and MoveToColumn do)", nav_command);
+    }
+    NAVIGATION_STATE.with(|nav_state| nav_state.borrow_mut().move_to_number = number);
+    return do_navigate_command_string(mathml, nav_command);
+}
+
+/// Commands that work on the rows and columns of a table or of columnar math (`mstack`, `mlongdiv`)
+static TABLE_COMMANDS: phf::Set<&str> = phf_set! {
+    "ReadRow", "ReadColumn", "ReadRowHeader", "ReadColumnHeader", "MoveToRow", "MoveToColumn",
+};
+
+/// A cell of a `TableLayout`
+struct TableCell<'a> {
+    element: Element<'a>,           // the element containing the cell (for a digit, the 'mn')
+    position: NavigationPosition,   // where navigation goes for this cell
+}
+
+/// The rows and columns of an `mtable` or of columnar math (`mstack`/`mlongdiv`).
+/// For columnar math, each digit is a cell and the rows are right aligned.
+struct TableLayout<'a> {
+    rows: Vec<Element<'a>>,
+    cells: Vec<Vec<Option<TableCell<'a>>>>,     // every row has the same number of columns (missing cells are None)
+}
+
+impl<'a> TableLayout<'a> {
+    /// Return the layout of the closest table/columnar math containing `node` (including `node` itself)
+    fn new(node: Element<'a>) -> Option<(Element<'a>, TableLayout<'a>)> {
+        let mut element = node;
+        loop {
+            let element_name = name(element);
+            if element_name == "mstack" || element_name == "mlongdiv" {
+                return Some( (element, TableLayout::new_columnar(element)) );
+            }
+            if is_table_row(element) && let Some(table) = element.parent().and_then(|parent| parent.element()) {
+                return Some( (table, TableLayout::new_table(table)) );
+            }
+            if element.children().iter().any(|&child| child.element().is_some_and(is_table_row)) {
+                return Some( (element, TableLayout::new_table(element)) );      // the mtable (which might have an intent name)
+            }
+            if element_name == "math" {
+                return None;
+            }
+            element = element.parent()?.element()?;
+        }
+
+        fn is_table_row(element: Element) -> bool {
+            let element_name = name(element);
+            return element_name == "mtr" || element_name == "mlabeledtr";
+        }
+    }
+
+    fn new_table(table: Element<'a>) -> TableLayout<'a> {
+        let rows: Vec<Element> = table.children().iter().filter_map(|child| child.element()).collect();
+        let mut cells: Vec<Vec<Option<TableCell>>> = rows.iter()
+            .map(|&row| {
+                let skip = if name(row) == "mlabeledtr" {1} else {0};       // the label isn't part of the table
+                row.children().iter()
+                    .filter_map(|child| child.element())
+                    .skip(skip)
+                    .map(|cell| {
+                        // navigation is on the contents of an 'mtd' (it might have been converted to an 'mrow' by intent)
+                        let target = if name(cell) == "mtd" {
+                            cell.children().iter().find_map(|child| child.element()).unwrap_or(cell)
+                        } else {
+                            cell
+                        };
+                        Some( TableCell{ element: cell, position: element_position(target) } )
+                    })
+                    .collect()
+            })
+            .collect();
+        let n_columns = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut cells {
+            row.resize_with(n_columns, || None);
+        }
+        return TableLayout { rows, cells };
+    }
+
+    fn new_columnar(stack: Element<'a>) -> TableLayout<'a> {
+        let skip = if name(stack) == "mlongdiv" {2} else {0};    // the divisor and the result aren't part of the columns
+        let rows: Vec<Element> = stack.children().iter().filter_map(|child| child.element()).skip(skip).collect();
+        let mut cells: Vec<Vec<Option<TableCell>>> = rows.iter()
+            .map(|&row| {
+                let mut row_cells = Vec::new();
+                if name(row) != "msline" {
+                    add_columnar_cells(row, &mut row_cells);
+                }
+                row_cells
+            })
+            .collect();
+        let n_columns = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut cells {
+            let mut padding: Vec<Option<TableCell>> = (row.len()..n_columns).map(|_| None).collect();
+            padding.append(row);
+            *row = padding;
+        }
+        return TableLayout { rows, cells };
+
+        fn add_columnar_cells<'a>(element: Element<'a>, cells: &mut Vec<Option<TableCell<'a>>>) {
+            match name(element) {
+                "mn" => {
+                    let id = element.attribute_value("id").unwrap_or_default();
+                    for i in 0..as_text(element).chars().count() {
+                        let position = NavigationPosition{ current_node: id.to_string(), current_node_offset: i+1 };
+                        cells.push( Some(TableCell{ element, position }) );
+                    }
+                },
+                "msrow" | "mrow" => {
+                    for child in element.children() {
+                        if let Some(child) = child.element() {
+                            add_columnar_cells(child, cells);
+                        }
+                    }
+                },
+                "mscarries" => {
+                    for child in element.children() {
+                        if let Some(child) = child.element() {
+                            cells.push( Some(TableCell{ element: child, position: element_position(child) }) );
+                        }
+                    }
+                },
+                _ => cells.push( Some(TableCell{ element, position: element_position(element) }) ),
+            }
+        }
+    }
+
+    fn n_columns(&self) -> usize {
+        return self.cells.first().map(|row| row.len()).unwrap_or(0);
+    }
+
+    /// Return the (0-based) row and column of `node` ('offset' is the char offset in a leaf) if it is in a row/cell
+    fn location(&self, node: Element, offset: usize) -> (Option<usize>, Option<usize>) {
+        let i_row = self.rows.iter().position(|&row| row == node || is_ancestor(row, node));
+        let i_column = i_row.and_then(|i_row| {
+            let row = &self.cells[i_row];
+            let is_at = |cell: &Option<TableCell>, exact: bool| cell.as_ref().is_some_and(|cell|
+                if exact {
+                    cell.element == node && cell.position.current_node_offset == offset
+                } else {
+                    cell.element == node || is_ancestor(cell.element, node)
+                });
+            return row.iter().position(|cell| is_at(cell, true))
+                    .or_else(|| row.iter().position(|cell| is_at(cell, false)));
+        });
+        return (i_row, i_column);
+    }
+
+    fn position(&self, i_row: usize, i_column: usize) -> Option<&NavigationPosition> {
+        return self.cells.get(i_row)?.get(i_column)?.as_ref().map(|cell| &cell.position);
+    }
+}
+
+fn element_position(element: Element) -> NavigationPosition {
+    return NavigationPosition {
+        current_node: element.attribute_value("id").unwrap_or_default().to_string(),
+        current_node_offset: element.attribute_value(ID_OFFSET).unwrap_or_default().parse::<usize>().unwrap_or_default(),
+    };
+}
+
+/// Set the context variables used by the table commands:
+/// * `TableNode` -- the id of the table/columnar math ('' if not in one)
+/// * `TableTarget`/`TableTargetOffset` -- where the command goes (or what is read); '' if the row/column doesn't exist
+/// * `TableTargetNumber` -- the (1-based) row or column number of the target
+/// * `TableTargetIsRow` -- true if the target is a table row (`mtr`/`mlabeledtr`), whose speech already includes the row number
+///
+/// For `ReadColumn`, the positions of the cells in the column are returned.
+fn set_table_variables(context: &mut sxd_xpath::Context, start_node: Element, offset: usize, nav_command: &str, number: usize) -> Vec<NavigationPosition> {
+    let Some( (table, layout) ) = TableLayout::new(start_node) else {
+        return Vec::new();
+    };
+    context.set_variable("TableNode", table.attribute_value("id").unwrap_or_default());
+    let (i_row, i_column) = layout.location(start_node, offset);
+    let (i_row, i_column) = (i_row.unwrap_or(0), i_column.unwrap_or(0));
+    let mut column_positions = Vec::new();
+    let (target, target_number) = match nav_command {
+        "ReadRow" => (layout.rows.get(i_row).map(|&row| element_position(row)), i_row + 1),
+        "ReadRowHeader" => (
+            layout.cells.get(i_row).and_then(|row| row.iter().flatten().next()).map(|cell| cell.position.clone()),
+            i_row + 1
+        ),
+        "ReadColumnHeader" => (
+            (0..layout.rows.len()).find_map(|i| layout.position(i, i_column)).cloned(),
+            i_column + 1
+        ),
+        "ReadColumn" => {
+            column_positions = (0..layout.rows.len()).filter_map(|i| layout.position(i, i_column)).cloned().collect();
+            (column_positions.first().cloned(), i_column + 1)
+        },
+        "MoveToRow" => (
+            if number == 0 || number > layout.rows.len() {
+                None
+            } else {
+                // move to the same column if there is a cell there, otherwise to the row
+                layout.position(number-1, i_column).cloned().or_else(|| Some(element_position(layout.rows[number-1])))
+            },
+            number
+        ),
+        "MoveToColumn" => (
+            if number == 0 || number > layout.n_columns() {None} else {layout.position(i_row, number-1).cloned()},
+            number
+        ),
+        _ => (None, 0),
+    };
+    if let Some(target) = target {
+        let is_row = layout.rows.iter()
+                .any(|&row| matches!(name(row), "mtr" | "mlabeledtr") && element_position(row) == target);
+        context.set_variable("TableTargetIsRow", is_row);
+        context.set_variable("TableTarget", target.current_node.as_str());
+        context.set_variable("TableTargetOffset", target.current_node_offset as f64);
+    }
+    context.set_variable("TableTargetNumber", target_number as f64);
+    return column_positions;
+}
+
 /// Speak the intent tree at the nav_node_id if that id exists in the intent tree; otherwise use the mathml tree.
 /// If full_read is true, we speak the tree, otherwise we use the overview rules.
 /// If literal_speak is true, we use the literal speak rules (and use the mathml tree).
@@ -696,292 +1394,292 @@ const VK_LEFT: usize = 0x25;
 const VK_RIGHT: usize = 0x27;
 const VK_UP: usize = 0x26;
 const VK_DOWN: usize = 0x28;
-const VK_RETURN: usize = 0x0D;
-const VK_SPACE: usize = 0x20;
-const VK_HOME: usize = 0x24;
-const VK_END: usize = 0x23;
-const VK_BACK: usize = 0x08;
-const VK_ESCAPE: usize = 0x1B;
-
-// Utilities that returns one of four commands/params based on shift/control key combinations
-
-enum NavigationCommand {
-    Move,
-    Zoom,
-    MoveLastLocation,
-    Read,
-    Describe,
-    ReadTo,
-    Locate,
-    ChangeNavMode,
-    ToggleSpeakMode,
-    SetPlacemarker,
-    Exit,
-    Last,
+
+const KEY_BINDINGS_FILE_NAME: &str = "keybindings.yaml";
+
+/// A key press: the key code along with the modifier keys that are down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyPress {
+    key: usize,
+    shift: bool,
+    control: bool,
+    alt: bool,
+    meta: bool,
 }
 
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
-enum NavigationParam {
-    Placemarker0,
-    Placemarker1,
-    Placemarker2,
-    Placemarker3,
-    Placemarker4,
-    Placemarker5,
-    Placemarker6,
-    Placemarker7,
-    Placemarker8,
-    Placemarker9,
-    Previous,
-    Current,
-    Next,
-    Start,
-    End,
-    LineStart,
-    LineEnd,
-    CellPrevious,
-    CellCurrent,
-    CellNext,
-    ColStart,
-    ColEnd,
-    CellUp,
-    CellDown,
-    Last 
+impl KeyPress {
+    /// Parse a key binding such as "Control+Shift+Left" (modifiers are case insensitive and can be in any order)
+    fn from_key_binding(binding: &str) -> Result<KeyPress> {
+        let mut key_press = KeyPress{ key: 0, shift: false, control: false, alt: false, meta: false };
+        let parts = binding.split('+').map(str::trim).collect::<Vec<&str>>();
+        let (key_name, modifiers) = parts.split_last().unwrap();    // split always returns at least one part
+        for modifier in modifiers {
+            match modifier.to_ascii_lowercase().as_str() {
+                "shift" => key_press.shift = true,
+                "control" | "ctrl" => key_press.control = true,
+                "alt" => key_press.alt = true,
+                "meta" => key_press.meta = true,
+                _ => bail!("unknown modifier '{}' in key binding '{}'", modifier, binding),
+            }
+        }
+        key_press.key = match key_name_to_key_code(key_name) {
+            Some(key) => key,
+            None => bail!("unknown key name '{}' in key binding '{}'", key_name, binding),
+        };
+        return Ok(key_press);
+
+        fn key_name_to_key_code(key_name: &str) -> Option<usize> {
+            let key_code = match key_name.to_ascii_lowercase().as_str() {
+                "left" => VK_LEFT,
+                "right" => VK_RIGHT,
+                "up" => VK_UP,
+                "down" => VK_DOWN,
+                "enter" | "return" => 0x0D,
+                "space" => 0x20,
+                "home" => 0x24,
+                "end" => 0x23,
+                "pageup" => 0x21,
+                "pagedown" => 0x22,
+                "backspace" => 0x08,
+                "escape" | "esc" => 0x1B,
+                "tab" => 0x09,
+                "insert" => 0x2D,
+                "delete" => 0x2E,
+                name => {
+                    let mut chars = name.chars();
+                    let ch = chars.next()?;
+                    if chars.as_str().is_empty() && (ch.is_ascii_digit() || ch.is_ascii_lowercase()) {
+                        ch.to_ascii_uppercase() as usize       // '0'...'9' and 'A'...'Z' are their own key codes
+                    } else if ch == 'f' && let Ok(n) = chars.as_str().parse::<usize>() && (1..=12).contains(&n) {
+                        0x70 + n - 1
+                    } else {
+                        return None;
+                    }
+                },
+            };
+            return Some(key_code);
+        }
+    }
 }
 
+/// A binding (key press or gesture) for a navigation command, read from a bindings file such as "keybindings.yaml"
+trait Binding: Sized + Eq + std::hash::Hash {
+    /// The name of the file with the bindings (in the Rules dir and optionally the user's config dir)
+    const FILE_NAME: &'static str;
+    /// What the bindings are called in error messages (e.g., "key bindings")
+    const DESCRIPTION: &'static str;
+    fn from_binding(binding: &str) -> Result<Self>;
+}
 
-fn choose_command(
-	shift_key: bool,
-	control_key: bool,
-	none: NavigationCommand,
-	shift: NavigationCommand,
-	control: NavigationCommand,
-	shift_control: NavigationCommand
-) -> NavigationCommand {
-	   if shift_key && control_key {
-		return shift_control;
-    } else if control_key {
-        return control;
-    } else if shift_key {
-		return shift;
-	} else {
-		return none;
+impl Binding for KeyPress {
+    const FILE_NAME: &'static str = KEY_BINDINGS_FILE_NAME;
+    const DESCRIPTION: &'static str = "key bindings";
+    fn from_binding(binding: &str) -> Result<KeyPress> {
+        return KeyPress::from_key_binding(binding);
     }
 }
 
-fn choose_param(
-	shift_key: bool,
-	control_key: bool,
-	none: NavigationParam,
-	shift: NavigationParam,
-	control: NavigationParam,
-	shift_control: NavigationParam
-) -> NavigationParam {
-    if shift_key && control_key {
-		return shift_control;
-    } else if control_key {
-        return control;
-    } else if shift_key {
-		return shift;
-	} else {
-		return none;
+/// The bindings for the scheme given by a preference ("KeyBindings" or "GestureBindings").
+/// These are read from the Rules dir and (optionally) the user's config dir, and are re-read if the pref or a file changes.
+#[derive(Debug)]
+struct Bindings<B: Binding> {
+    scheme: String,
+    files: Vec<FileAndTime>,
+    bindings: HashMap<B, &'static str>,
+}
+
+impl<B: Binding> Default for Bindings<B> {
+    fn default() -> Self {
+        return Bindings { scheme: String::default(), files: Vec::default(), bindings: HashMap::default() };
     }
 }
 
-fn key_press_to_command_and_param(
-    key: usize,
-	shift_key: bool,
-	control_key: bool,
-	alt_key: bool,
-	meta_key: bool,
-) -> Result<(NavigationCommand, NavigationParam)> {
-	// key press mapping should probably be stored externally (registry) with an app that allows changes
-	// for now, we build in the defaults
-
-    // this is a hack to map alt+ctl+arrow to ctl+arrow to change table mappings (github.com/NSoiffer/MathCAT/issues/105)
-    // if this change sticks, choose_command() needs to be changed and this hack should go away
-    let mut alt_key = alt_key;
-    if alt_key && control_key && [VK_LEFT, VK_RIGHT, VK_UP, VK_DOWN].contains(&key) {
-        alt_key = false;
-    }
-	if alt_key || meta_key {
-        bail!("Invalid argument to key_press_to_command_and_param");
-    }
-
-    let command;
-    let param;
-	match key {
-        VK_LEFT => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Move,   NavigationCommand::Read,	NavigationCommand::Move,	   NavigationCommand::Describe);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Previous, NavigationParam::Previous, NavigationParam::CellPrevious, NavigationParam::Previous);
-            },
-        VK_RIGHT => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Move,	NavigationCommand::Read, NavigationCommand::Move,	  NavigationCommand::Describe);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Next, NavigationParam::Next, NavigationParam::CellNext, NavigationParam::Next);
-            },
-        VK_UP => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Zoom,      NavigationCommand::ChangeNavMode, NavigationCommand::Move,   NavigationCommand::Zoom);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Previous,  NavigationParam::Previous,      NavigationParam::CellUp, NavigationParam::Start);
-            },
-        VK_DOWN => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Zoom, NavigationCommand::ChangeNavMode, NavigationCommand::Move,     NavigationCommand::Zoom);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Next, NavigationParam::Next,          NavigationParam::CellDown, NavigationParam::End);
-            },
-        VK_RETURN => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Locate,  NavigationCommand::Last, NavigationCommand::Locate, NavigationCommand::Last);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Previous,NavigationParam::Last, NavigationParam::Last,    NavigationParam::Last);
-            },
-        VK_SPACE => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Read,		NavigationCommand::ToggleSpeakMode,    NavigationCommand::Read,        NavigationCommand::Describe);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Current, NavigationParam::Last,                NavigationParam::CellCurrent, NavigationParam::Current);
-            },
-    
-        VK_HOME => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Move, NavigationCommand::Move,	   NavigationCommand::Move,      NavigationCommand::ReadTo);
-            param =   choose_param(  shift_key, control_key, NavigationParam::Start,NavigationParam::ColStart, NavigationParam::LineStart, NavigationParam::Start);
-            },
-        VK_END => {
-            command = choose_command(shift_key, control_key, NavigationCommand::Move, NavigationCommand::Move,   NavigationCommand::Move,    NavigationCommand::ReadTo);
-            param =   choose_param(  shift_key, control_key, NavigationParam::End,  NavigationParam::ColEnd, NavigationParam::LineEnd, NavigationParam::End);
-            },
-        VK_BACK => {
-            command = NavigationCommand::MoveLastLocation;
-            param = NavigationParam::Last;
-            },
-        VK_ESCAPE => {
-            command = NavigationCommand::Exit;
-            param = NavigationParam::Last;
-            },
-        0x30..=0x39 => {  // '0' ... '9'
-            command = choose_command(shift_key, control_key, NavigationCommand::Move, NavigationCommand::Read, NavigationCommand::SetPlacemarker, NavigationCommand::Describe);
-            static PLACE_MARKER: &[NavigationParam] = &[
-                NavigationParam::Placemarker0,
-                NavigationParam::Placemarker1,
-                NavigationParam::Placemarker2,
-                NavigationParam::Placemarker3,
-                NavigationParam::Placemarker4,
-                NavigationParam::Placemarker5,
-                NavigationParam::Placemarker6,
-                NavigationParam::Placemarker7,
-                NavigationParam::Placemarker8,
-                NavigationParam::Placemarker9,
-            ];
-            param = PLACE_MARKER[key-0x30];
-        },
-        _ => bail!("Unknown key press/command"),
-    };
-    
-	return Ok( (command, param) );
+type KeyBindings = Bindings<KeyPress>;
+type GestureBindings = Bindings<Gesture>;
+
+thread_local!{
+    static KEY_BINDINGS: RefCell<KeyBindings> = RefCell::new( KeyBindings::default() );
+    static GESTURE_BINDINGS: RefCell<GestureBindings> = RefCell::new( GestureBindings::default() );
 }
 
-// translate the key presses into commands
-
-
-fn navigation_command_string(command: NavigationCommand, param: NavigationParam) -> &'static str {
-	match command {
-	    NavigationCommand::Move => {
-            return match param {
-                NavigationParam::Previous => "MovePrevious",
-                NavigationParam::Next => "MoveNext",
-                NavigationParam::Start => "MoveStart",
-                NavigationParam::End => "MoveEnd",
-                NavigationParam::LineStart => "MoveLineStart",
-                NavigationParam::LineEnd => "MoveLineEnd",
-                NavigationParam::CellPrevious => "MoveCellPrevious",
-                NavigationParam::CellNext => "MoveCellNext",
-                NavigationParam::CellUp => "MoveCellUp",
-                NavigationParam::CellDown => "MoveCellDown",
-                NavigationParam::ColStart => "MoveColumnStart",
-                NavigationParam::ColEnd => "MoveColumnEnd",
-                _ => {
-                    if param < NavigationParam::Placemarker0 || param > NavigationParam::Placemarker9 {
-                        panic!("Internal Error: Found illegal value for param of NavigationCommand::Move");
-                    }
-                    static MOVE_TO: &[&str] = &["MoveTo0","MoveTo1","MoveTo2","MoveTo3","MoveTo4","MoveTo5","MoveTo6","MoveTo7","MoveTo8","MoveTo9"];
-                    return MOVE_TO[(param as usize) - (NavigationParam::Placemarker0 as usize)];
-                }
+impl<B: Binding> Bindings<B> {
+    fn is_up_to_date(&self, scheme: &str) -> bool {
+        return !self.files.is_empty() && self.scheme == scheme && self.files.iter().all(|file| file.is_up_to_date());
+    }
+
+    /// Read the bindings for `scheme` -- the user file (if it exists) overrides the system file.
+    fn read(scheme: &str, rules_dir: &Path) -> Result<Bindings<B>> {
+        let mut files = vec![rules_dir.join(B::FILE_NAME)];
+        if let Some(config_dir) = dirs::config_dir() {
+            files.push(config_dir.join("MathCAT").join(B::FILE_NAME));
+        }
+
+        let mut schemes = Vec::with_capacity(files.len());
+        for file in &files {
+            if is_file_shim(file) {
+                schemes.push( (read_bindings_file::<B>(file)?, file.to_string_lossy().to_string()) );
             }
-        },
-        NavigationCommand::Zoom => {
-            return match param {
-                NavigationParam::Next => "ZoomIn",
-                NavigationParam::Previous => "ZoomOut",
-                NavigationParam::Start => "ZoomOutAll",
-                NavigationParam::End => "ZoomInAll",
-                _  => panic!("Illegal param for NavigationCommand::Zoom"),
+        }
+        if schemes.is_empty() {
+            bail!("Didn't find the {} file '{}'", B::DESCRIPTION, files[0].to_string_lossy());
+        }
+
+        let mut bindings = HashMap::with_capacity(100);
+        add_scheme(&mut bindings, &schemes, scheme, 0)?;
+        return Ok( Bindings {
+            scheme: scheme.to_string(),
+            files: files.into_iter().map(FileAndTime::new_with_time).collect(),
+            bindings,
+        } );
+
+        fn read_bindings_file<B: Binding>(file: &Path) -> Result<Yaml> {
+            let file_contents = read_to_string_shim(file)?;
+            let mut docs = match YamlLoader::load_from_str(&file_contents) {
+                Ok(docs) => docs,
+                Err(e) => bail!("Yaml parse error ('{}') in {} file {}.", e, B::DESCRIPTION, file.to_string_lossy()),
+            };
+            if docs.len() != 1 || docs[0].as_hash().is_none() {
+                bail!("The {} file '{}' should be a single dictionary of schemes", B::DESCRIPTION, file.to_string_lossy());
             }
-        },
-        NavigationCommand::MoveLastLocation => {
-            return "MoveLastLocation";
-        },
-        NavigationCommand::Read => {
-            return match param {
-                NavigationParam::Previous => "ReadPrevious",
-                NavigationParam::Next => "ReadNext",
-                NavigationParam::Current => "ReadCurrent",
-                NavigationParam::CellCurrent => "ReadCellCurrent",
-                NavigationParam::Start => "ReadStart",
-                NavigationParam::End => "ReadEnd",
-                NavigationParam::LineStart => "ReadLineStart",
-                NavigationParam::LineEnd => "ReadLineEnd",
-                _ => {
-                    if param < NavigationParam::Placemarker0 || param > NavigationParam::Placemarker9 {
-                        panic!("Internal Error: Found illegal value for param of NavigationCommand::Move");
-                    }
-                    static READ_PLACE_MARKERS: &[&str] = &["Read0","Read1","Read2","Read3","Read4","Read5","Read6","Read7","Read8","Read9"];
-                    return READ_PLACE_MARKERS[(param as usize) - (NavigationParam::Placemarker0 as usize)];
-                },
+            return Ok(docs.pop().unwrap());
+        }
+
+        /// Add the bindings for 'scheme' from all the files (later files override earlier ones).
+        fn add_scheme<B: Binding>(bindings: &mut HashMap<B, &'static str>, schemes: &[(Yaml, String)], scheme: &str, depth: usize) -> Result<()> {
+            let description = B::DESCRIPTION;
+            if depth > 10 {
+                bail!("The {} scheme '{}' has a circular 'Extends'", description, scheme);
             }
-        },
-        NavigationCommand::Describe => {
-            return match param {
-                NavigationParam::Previous => "DescribePrevious",
-                NavigationParam::Next => "DescribeNext",
-                NavigationParam::Current => "DescribeCurrent",
-                _ => {
-                    if param < NavigationParam::Placemarker0 || param > NavigationParam::Placemarker9 {
-                        panic!("Internal Error: Found illegal value for param of NavigationCommand::Describe");
+            let mut found_scheme = false;
+            for (all_schemes, file_name) in schemes {
+                let scheme_bindings = &all_schemes[scheme];
+                if scheme_bindings.is_badvalue() {
+                    continue;
+                }
+                found_scheme = true;
+                let scheme_bindings = match scheme_bindings.as_hash() {
+                    Some(hash) => hash,
+                    None => bail!("The {} scheme '{}' in '{}' should be a dictionary", description, scheme, file_name),
+                };
+                if let Some(base_scheme) = scheme_bindings.get(&Yaml::String("Extends".to_string())) {
+                    match base_scheme.as_str() {
+                        Some(base_scheme) => add_scheme(bindings, schemes, base_scheme, depth + 1)?,
+                        None => bail!("'Extends' in {} scheme '{}' in '{}' should be a scheme name", description, scheme, file_name),
+                    }
+                }
+                for (binding_name, command) in scheme_bindings {
+                    let binding_name = match binding_name {
+                        Yaml::String(str) if str == "Extends" => continue,
+                        Yaml::String(str) => str.clone(),
+                        Yaml::Integer(i) => i.to_string(),        // e.g., 1: MoveTo1
+                        _ => bail!("Binding '{}' in '{}' should be a string", yaml_to_string(binding_name, 0), file_name),
+                    };
+                    let binding = B::from_binding(&binding_name)
+                            .with_context(|| format!("in {description} scheme '{scheme}' in '{file_name}'"))?;
+                    match command {
+                        Yaml::Null => { bindings.remove(&binding); },
+                        Yaml::String(command) => match NAV_COMMANDS.get_key(command.as_str()) {
+                            Some(command) => { bindings.insert(binding, *command); },
+                            None => bail!("'{}' (bound to '{}') in {} scheme '{}' in '{}' is not a navigation command",
+                                          command, binding_name, description, scheme, file_name),
+                        },
+                        _ => bail!("The value for binding '{}' in '{}' should be a navigation command", binding_name, file_name),
                     }
-                    static DESCRIBE_PLACE_MARKERS: &[&str] = &["Describe0","Describe1","Describe2","Describe3","Describe4","Describe5","Describe6","Describe7","Describe8","Describe9"];
-                    return DESCRIBE_PLACE_MARKERS[(param as usize) - (NavigationParam::Placemarker0 as usize)];
                 }
             }
-        },
-        NavigationCommand::ReadTo => {
-            todo!("ReadTo navigation command")
-        },
-        NavigationCommand::Locate => {
-            if param ==NavigationParam::Previous {
-                return "WhereAmI";
-            } else if param ==NavigationParam::Last {
-                return "WhereAmIAll";
-            }
-        },
-        NavigationCommand::ChangeNavMode => {
-            if param ==NavigationParam::Previous {
-                return "ToggleZoomLockUp";
-            } else if param ==NavigationParam::Next {
-                return "ToggleZoomLockDown";
-            }
-        },
-        NavigationCommand::ToggleSpeakMode => {
-            return "ToggleSpeakMode";
-        },
-        NavigationCommand::SetPlacemarker => {
-            if param < NavigationParam::Placemarker0 || param > NavigationParam::Placemarker9 {
-                panic!("Internal Error: Found illegal value for param of NavigationCommand::SetPlacemarker");
+            if !found_scheme {
+                bail!("The {} scheme '{}' was not found", description, scheme);
             }
-            static SET_PLACE_MARKER: &[&str] = &["SetPlacemarker0","SetPlacemarker1","SetPlacemarker2","SetPlacemarker3","SetPlacemarker4","SetPlacemarker5","SetPlacemarker6","SetPlacemarker7","SetPlacemarker8","SetPlacemarker9"];
-            return SET_PLACE_MARKER[(param as usize) - (NavigationParam::Placemarker0 as usize)];
-        },
-        NavigationCommand::Exit => {
-            return "Exit";
-        },
-        NavigationCommand::Last => {
-            return "Error";
+            return Ok( () );
+        }
+    }
+}
+
+/// Look up the navigation command for the key press in the key bindings given by the "KeyBindings" preference
+fn key_press_to_command(
+    key: usize,
+	shift_key: bool,
+	control_key: bool,
+	alt_key: bool,
+	meta_key: bool,
+) -> Result<&'static str> {
+    let (scheme, rules_dir) = {
+        let pref_manager = PreferenceManager::get();
+        let pref_manager = pref_manager.borrow();
+        (pref_manager.pref_to_string("KeyBindings"), pref_manager.get_rules_dir())
+    };
+    return KEY_BINDINGS.with(|key_bindings| {
+        if !key_bindings.borrow().is_up_to_date(&scheme) {
+            key_bindings.replace(KeyBindings::read(&scheme, &rules_dir)?);
+        }
+        let key_bindings = key_bindings.borrow();
+        let key_press = KeyPress{ key, shift: shift_key, control: control_key, alt: alt_key, meta: meta_key };
+        if let Some(command) = key_bindings.bindings.get(&key_press) {
+            return Ok(*command);
         }
+
+        // this is a hack to map alt+ctl+arrow to ctl+arrow to change table mappings (github.com/NSoiffer/MathCAT/issues/105)
+        if alt_key && control_key && [VK_LEFT, VK_RIGHT, VK_UP, VK_DOWN].contains(&key) &&
+           let Some(command) = key_bindings.bindings.get(&KeyPress{ alt: false, ..key_press }) {
+            return Ok(*command);
+        }
+        bail!("Unknown key press/command");
+    });
+}
+
+const GESTURE_BINDINGS_FILE_NAME: &str = "gestures.yaml";
+
+/// The gestures (possibly with more than one finger) that can be bound to navigation commands
+static GESTURE_NAMES: phf::Set<&str> = phf_set! {
+    "swipeleft", "swiperight", "swipeup", "swipedown", "tap", "doubletap", "tripletap", "longpress",
+};
+
+/// A touch gesture such as "SwipeLeft" or "TwoFingerDoubleTap"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Gesture {
+    fingers: usize,
+    gesture: &'static str,      // one of GESTURE_NAMES
+}
+
+impl Gesture {
+    /// Parse a gesture name such as "SwipeLeft" or "TwoFingerSwipeUp" (case insensitive)
+    fn from_name(gesture_name: &str) -> Result<Gesture> {
+        let lower_case_name = gesture_name.trim().to_ascii_lowercase();
+        let (fingers, name) = [("onefinger", 1), ("twofinger", 2), ("threefinger", 3), ("fourfinger", 4)].iter()
+                .find_map(|&(prefix, fingers)| lower_case_name.strip_prefix(prefix).map(|name| (fingers, name)))
+                .unwrap_or((1, lower_case_name.as_str()));
+        return match GESTURE_NAMES.get_key(name) {
+            Some(gesture) => Ok( Gesture{ fingers, gesture } ),
+            None => bail!("unknown gesture '{}' (should be something like 'SwipeLeft' or 'TwoFingerDoubleTap')", gesture_name),
+        };
+    }
+}
+
+impl Binding for Gesture {
+    const FILE_NAME: &'static str = GESTURE_BINDINGS_FILE_NAME;
+    const DESCRIPTION: &'static str = "gesture bindings";
+    fn from_binding(binding: &str) -> Result<Gesture> {
+        return Gesture::from_name(binding);
+    }
+}
+
+/// Look up the navigation command for the gesture in the gesture bindings given by the "GestureBindings" preference
+fn gesture_to_command(gesture: &str) -> Result<&'static str> {
+    let gesture = Gesture::from_name(gesture)?;
+    let (scheme, rules_dir) = {
+        let pref_manager = PreferenceManager::get();
+        let pref_manager = pref_manager.borrow();
+        (pref_manager.pref_to_string("GestureBindings"), pref_manager.get_rules_dir())
     };
-    return "Error";
+    return GESTURE_BINDINGS.with(|gesture_bindings| {
+        if !gesture_bindings.borrow().is_up_to_date(&scheme) {
+            gesture_bindings.replace(GestureBindings::read(&scheme, &rules_dir)?);
+        }
+        return match gesture_bindings.borrow().bindings.get(&gesture) {
+            Some(command) => Ok(*command),
+            None => bail!("No navigation command is bound to the gesture"),
+        };
+    });
 }
 
 #[cfg(test)]
@@ -1170,7 +1868,7 @@ mod tests {
             });
             test_command("ZoomOut", mathml, "msup");
 
-            let _nav_speech = do_navigate_command_and_param(mathml, NavigationCommand::Zoom, NavigationParam::Previous)?;
+            let _nav_speech = do_navigate_command_string(mathml, "ZoomOut")?;
             NAVIGATION_STATE.with(|nav_stack| {
                 let (id, _) = nav_stack.borrow().get_navigation_mathml_id(mathml);
                 assert_eq!(id, "mfrac");
@@ -1337,7 +2035,7 @@ mod tests {
             });
             test_command("MoveLineStart", mathml, "msup");
 
-            let _nav_speech = do_navigate_command_and_param(mathml, NavigationCommand::Move, NavigationParam::Start)?;
+            let _nav_speech = do_navigate_command_string(mathml, "MoveStart")?;
             NAVIGATION_STATE.with(|nav_stack| {
                 let (id, _) = nav_stack.borrow().get_navigation_mathml_id(mathml);
                 assert_eq!(id, "num");
@@ -2020,6 +2718,326 @@ mod tests {
         });
     }
 
+    #[test]
+    fn redo_and_history() -> Result<()> {
+        let mathml_str = "<math display='block' id='math'>
+        <mrow displaystyle='true' id='mrow'>
+          <mi id='a'>a</mi>
+          <mo id='plus-1'>+</mo>
+          <mi id='b'>b</mi>
+          <mo id='plus-2'>+</mo>
+          <mi id='c'>c</mi>
+        </mrow>
+        </math>";
+        init_default_prefs(mathml_str, "Character");
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("MoveStart", mathml, "a");
+            test_command("MoveNext", mathml, "plus-1");
+            test_command("MoveNext", mathml, "b");
+            assert_eq!(test_command("ReadHistory", mathml, "b"), "previous positions; plus; eigh; eigh plus b plus c");
+            set_preference("NavHistoryCount", "1").unwrap();
+            assert_eq!(test_command("ReadHistory", mathml, "b"), "previous positions; plus");
+            test_command("MoveLastLocation", mathml, "plus-1");
+            test_command("MoveLastLocation", mathml, "a");
+            test_command("MoveNextLocation", mathml, "plus-1");
+            test_command("MoveNextLocation", mathml, "b");
+            assert_eq!(test_command("MoveNextLocation", mathml, "b"), "nothing to redo; b");
+            test_command("MoveLastLocation", mathml, "plus-1");
+            test_command("MoveNext", mathml, "b");
+            test_command("MoveNext", mathml, "plus-2");
+            test_command("MoveNextLocation", mathml, "plus-2");       // the move cleared the redo stack
+            let history = NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_history());
+            let ids = history.iter().map(|(id, _, _)| id.as_str()).collect::<Vec<&str>>();
+            assert_eq!(ids.last(), Some(&"plus-2"));
+            assert_eq!(history.last().unwrap().2, "MoveNext");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn save_and_restore_state() -> Result<()> {
+        let mathml_str = "<math display='block' id='math'>
+        <mrow displaystyle='true' id='mrow'>
+          <mi id='a'>a</mi>
+          <mo id='plus-1'>+</mo>
+          <mfrac id='frac'><mi id='b'>b</mi><mn id='two'>2</mn></mfrac>
+        </mrow>
+        </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        let saved = MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomIn", mathml, "a");
+            test_command("SetPlacemarker3", mathml, "a");
+            test_command("MoveNext", mathml, "plus-1");
+            test_command("MoveNext", mathml, "frac");
+            test_command("ZoomIn", mathml, "b");
+            return get_navigation_state();
+        })?;
+
+        // "reopen" the document
+        set_preference("NavMode", "Simple")?;
+        set_mathml(mathml_str)?;
+        set_navigation_state(&saved)?;
+        assert_eq!(get_preference("NavMode")?, "Enhanced");
+        assert_eq!(get_navigation_mathml_id()?, ("b".to_string(), 0));
+        assert_eq!(get_navigation_state()?, saved);
+        MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("MoveTo3", mathml, "a");
+            test_command("MoveLastLocation", mathml, "b");
+            test_command("MoveLastLocation", mathml, "frac");
+        });
+
+        set_mathml("<math id='other'><mi id='x'>x</mi></math>")?;
+        assert!(set_navigation_state(&saved).is_err());
+        assert!(set_navigation_state("mode: Sideways").is_err());
+        return Ok( () );
+    }
+
+    #[test]
+    fn save_and_restore_state_without_ids() -> Result<()> {
+        // set_mathml adds different ids each time, so the saved state can't depend on them
+        let mathml_str = "<math><mi>a</mi><mo>+</mo><mfrac><mi>b</mi><mn>2</mn></mfrac></math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        do_navigate_command("ZoomIn")?;
+        do_navigate_command("SetPlacemarker1")?;
+        do_navigate_command("MoveNext")?;
+        do_navigate_command("MoveNext")?;
+        do_navigate_command("ZoomIn")?;
+        let (saved_id, _) = get_navigation_mathml_id()?;
+        let saved = get_navigation_state()?;
+
+        set_mathml(mathml_str)?;
+        set_navigation_state(&saved)?;
+        let (id, offset) = get_navigation_mathml_id()?;
+        assert_ne!(id, saved_id);
+        assert_eq!(offset, 0);
+        assert_eq!(do_navigate_command("ReadCurrent")?, "read current; b");
+        assert_eq!(get_navigation_state()?, saved);
+        assert_eq!(do_navigate_command("MoveLastLocation")?, "undo zoom in; b over 2");
+        assert_eq!(do_navigate_command("MoveTo1")?, "move to  placeholder 1; eigh");
+        return Ok( () );
+    }
+
+    #[test]
+    fn save_and_restore_find_target_and_redo() -> Result<()> {
+        let mathml_str = "<math><mi>x</mi><mo>=</mo><mfrac><mi>x</mi><mn>2</mn></mfrac><mo>+</mo><mi>x</mi></math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        do_navigate_command("ZoomInAll")?;
+        do_navigate_find("FindNext", "x")?;                 // the 'x' in the fraction
+        do_navigate_command("MoveNext")?;                   // '2'
+        do_navigate_command("MoveLastLocation")?;           // back to the 'x' in the fraction
+        let saved = get_navigation_state()?;
+
+        set_mathml(mathml_str)?;
+        set_navigation_state(&saved)?;
+        assert_eq!(get_navigation_state()?, saved);
+        assert_eq!(do_navigate_command("MoveNextLocation")?, "redo; 2");
+        do_navigate_command("MoveLastLocation")?;
+        assert_eq!(do_navigate_command("FindNext")?, "found; x");
+        assert_eq!(do_navigate_command("FindNext")?, "not found");
+        return Ok( () );
+    }
+
+    #[test]
+    fn find_next_and_previous() -> Result<()> {
+        let mathml_str = "<math display='block' id='math'>
+        <mrow id='mrow'>
+          <mi id='x1'>x</mi>
+          <mo id='eq'>=</mo>
+          <mfrac id='frac'><mi id='x2'>x</mi><mn id='two'>2</mn></mfrac>
+          <mo id='plus'>+</mo>
+          <mi id='x3'>x</mi>
+        </mrow>
+        </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomInAll", mathml, "x1");
+            let speech = do_navigate_find_string(mathml, "FindNext", "x")?;
+            assert_eq!(speech, "found; x");
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml).0), "x2");
+            test_command("FindNext", mathml, "x3");
+            assert_eq!(test_command("FindNext", mathml, "x3"), "not found");
+            test_command("FindPrevious", mathml, "x2");
+            do_navigate_find_string(mathml, "FindPrevious", "=")?;
+            test_command("FindNext", mathml, "eq");     // no more '='
+            do_navigate_find_string(mathml, "FindNext", "+")?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml).0), "plus");
+            do_navigate_find_string(mathml, "FindPrevious", "fraction")?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml).0), "frac");
+            test_command("MoveLastLocation", mathml, "plus");
+            assert!(do_navigate_find_string(mathml, "MoveNext", "x").is_err());
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn difference_speech() -> Result<()> {
+        let mathml_str = "<math id='math'>
+        <mrow id='list'>
+          <msub id='x1'><mi id='x-1'>x</mi><mn id='one'>1</mn></msub>
+          <mo id='comma1'>,</mo>
+          <msub id='x2'><mi id='x-2'>x</mi><mn id='two'>2</mn></msub>
+          <mo id='comma2'>,</mo>
+          <msub id='y3'><mi id='y-3'>y</mi><mn id='three'>3</mn></msub>
+          <mo id='comma3'>,</mo>
+          <msup id='z2'><mi id='z'>z</mi><mn id='sq'>2</mn></msup>
+        </mrow>
+        </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        set_preference("NavVerbosity", "Terse")?;
+        set_preference("NavDifferenceSpeech", "true")?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomIn", mathml, "x1");
+            test_command("MoveNext", mathml, "comma1");
+            assert_eq!(test_command("MoveNext", mathml, "x2"), "same structure, subscript 2");
+            test_command("MoveNext", mathml, "comma2");
+            assert_eq!(test_command("MoveNext", mathml, "y3"), "y sub 3");       // everything differs
+            test_command("MoveNext", mathml, "comma3");
+            assert_eq!(test_command("MoveNext", mathml, "z2"), "z squared");     // different structure
+            set_preference("NavDifferenceSpeech", "false")?;
+            test_command("MovePrevious", mathml, "comma3");
+            test_command("MovePrevious", mathml, "y3");
+            test_command("MovePrevious", mathml, "comma2");
+            assert_eq!(test_command("MovePrevious", mathml, "x2"), "x sub 2");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn jump_to_category() -> Result<()> {
+        // y = 1/2 + x^2 - sin(x) + sqrt(3)
+        let mathml_str = "<math id='math'>
+        <mrow id='eq'>
+          <mi id='y'>y</mi>
+          <mo id='equals'>=</mo>
+          <mrow id='sum'>
+            <mfrac id='frac'><mn id='one'>1</mn><mn id='two'>2</mn></mfrac>
+            <mo id='plus'>+</mo>
+            <msup id='power'><mi id='x1'>x</mi><mn id='exp'>2</mn></msup>
+            <mo id='minus'>-</mo>
+            <mrow id='sin'><mi id='sin-name'>sin</mi><mo id='apply'>&#x2061;</mo><mi id='x2'>x</mi></mrow>
+            <mo id='plus2'>+</mo>
+            <msqrt id='sqrt'><mn id='three'>3</mn></msqrt>
+          </mrow>
+        </mrow>
+        </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("MoveNextRelation", mathml, "eq");
+            test_command("MoveNextFraction", mathml, "frac");
+            test_command("MoveNextTerm", mathml, "power");
+            test_command("MoveNextFunction", mathml, "sin");
+            assert_eq!(test_command("MoveNextFraction", mathml, "sin"), "not found");
+            test_command("MoveNextRoot", mathml, "sqrt");
+            test_command("MovePreviousPower", mathml, "power");
+            test_command("MovePreviousTerm", mathml, "frac");
+            test_command("MovePreviousRelation", mathml, "frac");      // the relation contains 'frac'
+            test_command("MoveNextTable", mathml, "frac");
+            test_command("MoveLastLocation", mathml, "power");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn jump_commands_are_nav_commands() -> Result<()> {
+        let mathml_str = "<math id='math'><mrow id='sum'>
+            <mi id='x'>x</mi><mo id='plus'>+</mo><mfrac id='frac'><mn id='one'>1</mn><mn id='two'>2</mn></mfrac>
+        </mrow></math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        set_preference("NavVerbosity", "Terse")?;
+        for command in JUMP_COMMANDS.keys() {
+            assert!(NAV_COMMANDS.contains(command), "'{}' is not in NAV_COMMANDS", command);
+        }
+        assert_eq!(crate::interface::do_navigate_command("MoveNextFraction")?, "1 half");
+        assert_eq!(crate::interface::get_navigation_mathml_id()?.0, "frac");
+        assert_eq!(crate::interface::do_navigate_command("MovePreviousFraction")?, "not found");
+
+        let dir = std::env::temp_dir().join("mathcat-jump-key-bindings-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(KEY_BINDINGS_FILE_NAME), "Jump:\n  Control+F: MoveNextFraction\n  Control+Shift+F: MovePreviousFraction\n").unwrap();
+        let key_bindings = KeyBindings::read("Jump", &dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let key_bindings = key_bindings?;
+        assert_eq!(key_bindings.bindings.get(&KeyPress::from_key_binding("Control+F")?), Some(&"MoveNextFraction"));
+        assert_eq!(key_bindings.bindings.get(&KeyPress::from_key_binding("Control+Shift+F")?), Some(&"MovePreviousFraction"));
+        return Ok( () );
+    }
+
+    #[test]
+    fn table_rows_and_columns() -> Result<()> {
+        let mathml_str = "<math id='math'>
+        <mrow id='mrow'><mo id='open'>(</mo>
+        <mtable id='table'>
+          <mtr id='row1'>
+            <mtd id='c11'><mn id='a'>1</mn></mtd>
+            <mtd id='c12'><mn id='b'>2</mn></mtd>
+          </mtr>
+          <mtr id='row2'>
+            <mtd id='c21'><mn id='c'>3</mn></mtd>
+            <mtd id='c22'><mfrac id='frac'><mn id='one'>1</mn><mn id='two'>2</mn></mfrac></mtd>
+          </mtr>
+        </mtable>
+        <mo id='close'>)</mo></mrow>
+       </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            assert_eq!(test_command("ReadRow", mathml, "math"), "not in table");
+            test_command("ZoomInAll", mathml, "a");
+            assert_eq!(do_navigate_command_with_number(mathml, "MoveToRow", 2)?, "row 2; 3");
+            assert_eq!(do_navigate_command_with_number(mathml, "MoveToColumn", 2)?, "column 2; 1 half");
+            assert_eq!(test_command("ReadColumn", mathml, "frac"), "column 2; 2; 1 half");
+            assert_eq!(test_command("ReadColumnHeader", mathml, "frac"), "column header 2; 2");
+            assert_eq!(test_command("ReadRowHeader", mathml, "frac"), "row header 2; 3");
+            assert_eq!(test_command("ReadRow", mathml, "frac"), "row 2; 3, 1 half");
+            assert_eq!(do_navigate_command_with_number(mathml, "MoveToRow", 3)?, "no row 3");
+            do_navigate_command_with_number(mathml, "MoveToRow", 1)?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml).0), "b");
+            test_command("MoveToColumn", mathml, "a");      // uses the last number given
+            test_command("MoveLastLocation", mathml, "b");
+            assert!(do_navigate_command_with_number(mathml, "MoveNext", 1).is_err());
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn stack_rows_and_columns() -> Result<()> {
+        let mathml_str = "<math id='math'>
+        <mstack id='stack'>
+          <mn id='top'>123</mn>
+          <msrow id='bottom'><mo id='plus'>+</mo><mn id='n45'>45</mn></msrow>
+          <msline id='line'/>
+          <mn id='sum'>168</mn>
+        </mstack>
+       </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            set_navigation_node_from_id(mathml, "n45", 2)?;
+            assert_eq!(test_command("ReadColumn", mathml, "n45"), "column 3; 3; 5; 8");
+            test_command("ReadColumnHeader", mathml, "n45");
+            do_navigate_command_with_number(mathml, "MoveToRow", 4)?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml)), ("sum".to_string(), 3));
+            do_navigate_command_with_number(mathml, "MoveToColumn", 1)?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml)), ("sum".to_string(), 1));
+            assert_eq!(do_navigate_command_with_number(mathml, "MoveToColumn", 6)?, "no column 6");
+            return Ok( () );
+        });
+    }
+
     #[test]
     fn where_am_i_all() -> Result<()> {
         let mathml_str = "<math id='math'><mfrac id='mfrac'>
@@ -2643,4 +3661,89 @@ mod tests {
             });
         }
     }
+
+    #[test]
+    fn key_bindings_default() -> Result<()> {
+        init_default_prefs("<math><mi>x</mi></math>", "Enhanced");
+        assert_eq!(key_press_to_command(VK_LEFT, false, false, false, false)?, "MovePrevious");
+        assert_eq!(key_press_to_command(VK_LEFT, true, true, false, false)?, "DescribePrevious");
+        assert_eq!(key_press_to_command(VK_DOWN, false, true, false, false)?, "MoveCellDown");
+        assert_eq!(key_press_to_command(VK_DOWN, false, true, true, false)?, "MoveCellDown");    // alt+ctrl+arrow hack
+        assert_eq!(key_press_to_command(0x20, true, false, false, false)?, "ToggleSpeakMode");
+        assert_eq!(key_press_to_command(0x33, false, true, false, false)?, "SetPlacemarker3");
+        assert_eq!(key_press_to_command(0x1B, true, false, false, false)?, "Exit");
+        assert!(key_press_to_command(VK_LEFT, false, false, true, false).is_err());
+        assert!(key_press_to_command(0x48, false, false, false, false).is_err());   // 'H'
+        return Ok( () );
+    }
+
+    #[test]
+    fn key_bindings_vim() -> Result<()> {
+        init_default_prefs("<math><mi>x</mi></math>", "Enhanced");
+        set_preference("KeyBindings", "Vim")?;
+        assert_eq!(key_press_to_command(0x48, false, false, false, false)?, "MovePrevious");   // 'H'
+        assert_eq!(key_press_to_command(0x4B, true, true, false, false)?, "ZoomOutAll");      // 'K'
+        assert_eq!(key_press_to_command(VK_LEFT, false, false, false, false)?, "MovePrevious");
+        set_preference("KeyBindings", "MathPlayer")?;
+        assert!(key_press_to_command(0x48, false, false, false, false).is_err());
+        return Ok( () );
+    }
+
+    #[test]
+    fn key_bindings_errors() {
+        assert!(KeyPress::from_key_binding("Ctrl+Shift+F12").is_ok());
+        assert!(KeyPress::from_key_binding("Hyper+Left").is_err());
+        assert!(KeyPress::from_key_binding("Shift+LeftArrow").is_err());
+
+        let dir = std::env::temp_dir().join("mathcat-key-bindings-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(KEY_BINDINGS_FILE_NAME), "Bad:\n  Left: MoveLeft\nLoop:\n  Extends: Loop\n").unwrap();
+        let err = KeyBindings::read("Bad", &dir).unwrap_err();
+        assert!(err.to_string().contains("'MoveLeft' (bound to 'Left')"), "{}", err);
+        assert!(KeyBindings::read("Loop", &dir).is_err());
+        assert!(KeyBindings::read("NoSuchScheme", &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn gesture_bindings() -> Result<()> {
+        assert_eq!(Gesture::from_name("TwoFingerSwipeUp")?, Gesture{ fingers: 2, gesture: "swipeup" });
+        assert_eq!(Gesture::from_name("doubletap")?, Gesture::from_name("OneFingerDoubleTap")?);
+        assert!(Gesture::from_name("SwipeSideways").is_err());
+        assert!(Gesture::from_name("FiveFingerTap").is_err());
+
+        let mathml_str = "<math id='math'><mrow id='mrow'><mi id='a'>a</mi><mo id='plus'>+</mo><mi id='b'>b</mi></mrow></math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        assert_eq!(gesture_to_command("SwipeRight")?, "MoveNext");
+        assert_eq!(gesture_to_command("ThreeFingerSwipeDown")?, "ZoomInAll");
+        assert!(gesture_to_command("FourFingerTap").is_err());      // not bound
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            do_mathml_navigate_gesture(mathml, "SwipeDown")?;
+            do_mathml_navigate_gesture(mathml, "SwipeRight")?;
+            assert_eq!(NAVIGATION_STATE.with(|nav_state| nav_state.borrow().get_navigation_mathml_id(mathml).0), "plus");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn overview_word_budget() -> Result<()> {
+        let mathml_str = "<math display='block'>
+            <mfrac><mi>x</mi><mi>y</mi></mfrac><mo>+</mo>
+            <mfrac><mi>p</mi><mi>q</mi></mfrac><mo>+</mo>
+            <mfrac><mi>m</mi><mi>n</mi></mfrac>
+        </math>";
+        init_default_prefs(mathml_str, "Enhanced");
+        set_preference("OverviewWordBudget", "0").unwrap();
+        let unlimited = crate::interface::get_overview_text()?;
+        set_preference("OverviewWordBudget", "7").unwrap();
+        let budgeted = crate::interface::get_overview_text()?;
+        set_preference("OverviewWordBudget", "2").unwrap();
+        let tiny = crate::interface::get_overview_text()?;
+        assert_eq!(unlimited, "x over y plus p over q plus m over n");
+        assert_eq!(budgeted, "x over y plus fraction plus fraction");
+        assert_eq!(tiny, "fraction plus fraction plus fraction");
+        return Ok( () );
+    }
 }
@@ -0,0 +1,392 @@
+//! Converts Office Math Markup Language (OMML, the `m:oMath` XML inside of `.docx` files) to MathML
+//!   so equations from Word can be handed to MathCAT directly (see [`crate::interface::set_omml`]).
+//!
+//! As with the LaTeX conversion, the MathML produced is simple (e.g., runs of text are split into tokens but not structured).
+//! It is meant to be passed to `set_mathml` so that it goes through the same cleanup as any other MathML.
+//!
+//! Namespace prefixes are removed before parsing, so the OMML can be cut out of a `.docx` without its namespace declarations.
+#![allow(clippy::needless_return)]
+
+use std::sync::LazyLock;
+use regex::Regex;
+use sxd_document::dom::*;
+use sxd_document::parser;
+use crate::canonicalize::name;
+use crate::errors::*;
+use crate::latex::{escape_xml, mo, wrap_in_mrow};
+
+/// Convert an OMML string (`m:oMathPara` or one or more `m:oMath` elements) to MathML.
+pub fn omml_to_mathml(omml: &str) -> Result<String> {
+	static XML_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"<\?[^>]*\?>"#).unwrap());
+	static NAMESPACE_DECL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\s+xmlns(:[[:alnum:]]+)?\s*=\s*("[^"]*"|'[^']*')"#).unwrap());
+	static ELEMENT_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(</?)[[:alnum:]]+:"#).unwrap());
+	static ATTR_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(\s)[[:alnum:]]+:([[:alnum:]-]+\s*=)"#).unwrap());
+
+	let omml = XML_DECL.replace_all(omml, "");
+	let omml = NAMESPACE_DECL.replace_all(&omml, "");
+	let omml = ELEMENT_PREFIX.replace_all(&omml, "$1");
+	let omml = ATTR_PREFIX.replace_all(&omml, "$1$2");
+	// wrap it so that multiple 'oMath' elements can be given
+	let omml = format!("<omml>{omml}</omml>");
+	let package = match parser::parse(&omml) {
+		Ok(package) => package,
+		Err(e) => bail!("Invalid OMML input:\n{}\nError is: {}", omml, e),
+	};
+	let doc = package.as_document();
+	let root = doc.root().children()[0].element().unwrap();
+
+	let mut equations = Vec::new();
+	find_equations(root, &mut equations);
+	let equations = equations.into_iter()
+		.map(|equation| convert_children(equation).map(wrap_in_mrow))
+		.collect::<Result<Vec<String>>>()?;
+	return Ok( match equations.len() {
+		0 => bail!("OMML error: no 'oMath' element found"),
+		1 => format!("<math>{}</math>", equations[0]),
+		_ => format!("<math><mtable>{}</mtable></math>",
+				equations.iter().map(|equation| format!("<mtr><mtd>{equation}</mtd></mtr>")).collect::<String>()),
+	} );
+
+	fn find_equations<'a>(element: Element<'a>, equations: &mut Vec<Element<'a>>) {
+		if name(element) == "oMath" {
+			equations.push(element);
+			return;
+		}
+		for child in element.children() {
+			if let Some(child) = child.element() {
+				find_equations(child, equations);
+			}
+		}
+	}
+}
+
+/// The (non-property) children of 'element'
+fn children(element: Element) -> Vec<Element> {
+	return element.children().iter()
+		.filter_map(|child| child.element())
+		.filter(|&child| !name(child).ends_with("Pr"))
+		.collect();
+}
+
+/// The child named 'child_name'
+fn child<'a>(element: Element<'a>, child_name: &str) -> Option<Element<'a>> {
+	return element.children().iter()
+		.filter_map(|child| child.element())
+		.find(|&child| name(child) == child_name);
+}
+
+/// The value of the 'val' attr on the 'property_name' child of the properties element (e.g., "m:fPr/m:type/@m:val")
+fn property<'a>(element: Element<'a>, property_name: &str) -> Option<&'a str> {
+	let properties = child(element, &(name(element).to_string() + "Pr"))?;
+	return property_value(properties, property_name);
+}
+
+/// Like `property`, but 'properties' is the properties element
+fn property_value<'a>(properties: Element<'a>, property_name: &str) -> Option<&'a str> {
+	let property = child(properties, property_name)?;
+	// an "on/off" property with no 'val' means "on"
+	return Some( property.attribute_value("val").unwrap_or("on") );
+}
+
+fn is_on(value: Option<&str>) -> bool {
+	return matches!(value, Some("1" | "on" | "true"));
+}
+
+/// Convert the children of an element to MathML
+fn convert_children(element: Element) -> Result<Vec<String>> {
+	let mut result = Vec::new();
+	for child in children(element) {
+		result.append(&mut convert(child)?);
+	}
+	return Ok(result);
+}
+
+/// Convert an argument (e.g, the numerator of a fraction) into a single MathML element
+fn convert_arg(element: Option<Element>) -> Result<String> {
+	return match element {
+		None => Ok( "<mrow/>".to_string() ),
+		Some(element) => {
+			let children = convert_children(element)?;
+			Ok( if children.is_empty() {"<mrow/>".to_string()} else {wrap_in_mrow(children)} )
+		},
+	};
+}
+
+fn arg<'a>(element: Element<'a>, arg_name: &str) -> Option<Element<'a>> {
+	return child(element, arg_name);
+}
+
+/// Convert an OMML element to (possibly several) MathML elements
+fn convert(element: Element) -> Result<Vec<String>> {
+	let result = match name(element) {
+		"r" => return convert_run(element),
+		"f" => {
+			let num = convert_arg(arg(element, "num"))?;
+			let den = convert_arg(arg(element, "den"))?;
+			match property(element, "type") {
+				Some("lin") => format!("<mrow>{num}<mo>/</mo>{den}</mrow>"),
+				Some("skw") => format!("<mfrac bevelled='true'>{num}{den}</mfrac>"),
+				Some("noBar") => format!("<mfrac linethickness='0'>{num}{den}</mfrac>"),
+				_ => format!("<mfrac>{num}{den}</mfrac>"),
+			}
+		},
+		"sSup" => format!("<msup>{}{}</msup>", convert_arg(arg(element, "e"))?, convert_arg(arg(element, "sup"))?),
+		"sSub" => format!("<msub>{}{}</msub>", convert_arg(arg(element, "e"))?, convert_arg(arg(element, "sub"))?),
+		"sSubSup" => format!("<msubsup>{}{}{}</msubsup>",
+				convert_arg(arg(element, "e"))?, convert_arg(arg(element, "sub"))?, convert_arg(arg(element, "sup"))?),
+		"sPre" => format!("<mmultiscripts>{}<mprescripts/>{}{}</mmultiscripts>",
+				convert_arg(arg(element, "e"))?, convert_arg(arg(element, "sub"))?, convert_arg(arg(element, "sup"))?),
+		"rad" => {
+			let base = convert_arg(arg(element, "e"))?;
+			let degree = arg(element, "deg").filter(|deg| !children(*deg).is_empty());
+			match degree {
+				Some(degree) if !is_on(property(element, "degHide")) => format!("<mroot>{}{}</mroot>", base, convert_arg(Some(degree))?),
+				_ => format!("<msqrt>{base}</msqrt>"),
+			}
+		},
+		"nary" => return convert_nary(element),
+		"d" => {
+			let open = property(element, "begChr").unwrap_or("(");
+			let close = property(element, "endChr").unwrap_or(")");
+			let separator = property(element, "sepChr").unwrap_or("|");
+			let mut result = Vec::new();
+			if !open.is_empty() {
+				result.push(mo(open));
+			}
+			for (i, e) in children(element).into_iter().filter(|&e| name(e) == "e").enumerate() {
+				if i > 0 && !separator.is_empty() {
+					result.push(mo(separator));
+				}
+				result.push(convert_arg(Some(e))?);
+			}
+			if !close.is_empty() {
+				result.push(mo(close));
+			}
+			format!("<mrow>{}</mrow>", result.join(""))
+		},
+		"m" => {
+			let rows = children(element).into_iter()
+				.filter(|&row| name(row) == "mr")
+				.map(|row| {
+					let cells = children(row).into_iter()
+						.map(|cell| convert_arg(Some(cell)).map(|cell| format!("<mtd>{cell}</mtd>")))
+						.collect::<Result<String>>()?;
+					Ok( format!("<mtr>{cells}</mtr>") )
+				})
+				.collect::<Result<String>>()?;
+			format!("<mtable>{rows}</mtable>")
+		},
+		"eqArr" => {
+			let rows = children(element).into_iter()
+				.map(|row| convert_arg(Some(row)).map(|row| format!("<mtr><mtd>{row}</mtd></mtr>")))
+				.collect::<Result<String>>()?;
+			format!("<mtable columnalign='left'>{rows}</mtable>")
+		},
+		"acc" => {
+			let accent = property(element, "chr").unwrap_or("\u{0302}");
+			format!("<mover accent='true'>{}{}</mover>", convert_arg(arg(element, "e"))?, mo(&spacing_accent(accent)))
+		},
+		"bar" => {
+			let base = convert_arg(arg(element, "e"))?;
+			match property(element, "pos") {
+				Some("top") => format!("<mover accent='true'>{}{}</mover>", base, mo("¯")),
+				_ => format!("<munder accentunder='true'>{}{}</munder>", base, mo("_")),
+			}
+		},
+		"groupChr" => {
+			let base = convert_arg(arg(element, "e"))?;
+			let ch = property(element, "chr").unwrap_or("⏟");
+			match property(element, "pos") {
+				Some("top") => format!("<mover>{}{}</mover>", base, mo(ch)),
+				_ => format!("<munder>{}{}</munder>", base, mo(ch)),
+			}
+		},
+		"limLow" => format!("<munder>{}{}</munder>", convert_arg(arg(element, "e"))?, convert_arg(arg(element, "lim"))?),
+		"limUpp" => format!("<mover>{}{}</mover>", convert_arg(arg(element, "e"))?, convert_arg(arg(element, "lim"))?),
+		"func" => format!("<mrow>{}<mo>&#x2061;</mo>{}</mrow>", convert_arg(arg(element, "fName"))?, convert_arg(arg(element, "e"))?),
+		"borderBox" => format!("<menclose notation='box'>{}</menclose>", convert_arg(arg(element, "e"))?),
+		"box" => convert_arg(arg(element, "e"))?,
+		"phant" => {
+			let base = convert_arg(arg(element, "e"))?;
+			match property(element, "show") {
+				Some("0" | "off" | "false") => format!("<mphantom>{base}</mphantom>"),
+				_ => base,
+			}
+		},
+		// these are OMML containers that just hold other content
+		"oMath" | "e" | "num" | "den" | "sub" | "sup" | "deg" | "lim" | "fName" => return convert_children(element),
+		"bookmarkStart" | "bookmarkEnd" | "proofErr" => return Ok( vec![] ),
+		element_name => bail!("OMML error: unknown element '{}'", element_name),
+	};
+	return Ok( vec![result] );
+}
+
+/// n-ary operators such as sums and integrals
+fn convert_nary(element: Element) -> Result<Vec<String>> {
+	let op = property(element, "chr").unwrap_or("∫");
+	let is_integral = matches!(op, "∫" | "∬" | "∭" | "∮" | "∯" | "∰" | "∱" | "∲" | "∳");
+	let under_over = match property(element, "limLoc") {
+		Some(location) => location == "undOvr",
+		None => !is_integral,
+	};
+	let sub = if is_on(property(element, "subHide")) {None} else {Some(convert_arg(arg(element, "sub"))?)};
+	let sup = if is_on(property(element, "supHide")) {None} else {Some(convert_arg(arg(element, "sup"))?)};
+	let op = mo(op);
+	let (with_both, with_sub, with_sup) = if under_over {("munderover", "munder", "mover")} else {("msubsup", "msub", "msup")};
+	let large_op = match (sub, sup) {
+		(Some(sub), Some(sup)) => format!("<{with_both}>{op}{sub}{sup}</{with_both}>"),
+		(Some(sub), None) => format!("<{with_sub}>{op}{sub}</{with_sub}>"),
+		(None, Some(sup)) => format!("<{with_sup}>{op}{sup}</{with_sup}>"),
+		(None, None) => op,
+	};
+	return Ok( vec![large_op, convert_arg(arg(element, "e"))?] );
+}
+
+/// Word uses combining characters for accents -- MathML uses the spacing versions
+fn spacing_accent(accent: &str) -> String {
+	return match accent {
+		"\u{0300}" => "`",
+		"\u{0301}" => "´",
+		"\u{0302}" => "^",
+		"\u{0303}" => "~",
+		"\u{0304}" | "\u{0305}" => "¯",
+		"\u{0306}" => "˘",
+		"\u{0307}" => "˙",
+		"\u{0308}" => "¨",
+		"\u{030C}" => "ˇ",
+		"\u{20D6}" => "←",
+		"\u{20D7}" => "→",
+		"\u{20E1}" => "↔",
+		_ => accent,
+	}.to_string();
+}
+
+/// A run of text is split into 'mi', 'mn', and 'mo' elements
+fn convert_run(run: Element) -> Result<Vec<String>> {
+	let text = children(run).into_iter()
+		.filter(|&child| name(child) == "t")
+		.map(|t| t.children().iter().filter_map(|child| child.text().map(|text| text.text().to_string())).collect::<String>())
+		.collect::<String>();
+	// both "m:rPr" and "w:rPr" become "rPr" when the prefixes are removed
+	let properties = run.children().iter()
+		.filter_map(|child| child.element())
+		.filter(|&child| name(child) == "rPr")
+		.collect::<Vec<Element>>();
+	let run_property = |property_name: &str| properties.iter().find_map(|&properties| property_value(properties, property_name));
+	if is_on(run_property("nor")) {
+		return Ok( vec![format!("<mtext>{}</mtext>", escape_xml(&text))] );
+	}
+	let style = run_property("sty").unwrap_or("i");
+	let script = run_property("scr").unwrap_or("roman");
+	let is_bold = style.starts_with('b');
+	let mathvariant = match script {
+		"script" | "fraktur" | "sans-serif" if is_bold => Some(format!("bold-{script}")),
+		"script" | "fraktur" | "sans-serif" | "double-struck" | "monospace" => Some(script.to_string()),
+		_ => match style {
+			"p" => Some("normal".to_string()),
+			"b" => Some("bold".to_string()),
+			"bi" => Some("bold-italic".to_string()),
+			_ => None,
+		},
+	};
+	let mi = |text: &str| -> String {
+		// multi-char identifiers are upright by default, so only single chars need to say 'normal'
+		return match &mathvariant {
+			Some(variant) if variant != "normal" || text.chars().count() == 1 => format!("<mi mathvariant='{}'>{}</mi>", variant, escape_xml(text)),
+			_ => format!("<mi>{}</mi>", escape_xml(text)),
+		};
+	};
+
+	let mut result = Vec::new();
+	let chars = text.chars().collect::<Vec<char>>();
+	let mut i = 0;
+	while i < chars.len() {
+		let ch = chars[i];
+		let start = i;
+		i += 1;
+		if ch.is_whitespace() || ch == '&' {		// '&' is an alignment point in 'eqArr'
+			continue;
+		} else if ch.is_ascii_digit() || (ch == '.' && chars.get(i).is_some_and(|ch| ch.is_ascii_digit())) {
+			while i < chars.len() &&
+				  (chars[i].is_ascii_digit() || (chars[i] == '.' && chars.get(i+1).is_some_and(|ch| ch.is_ascii_digit()))) {
+				i += 1;
+			}
+			result.push(format!("<mn>{}</mn>", chars[start..i].iter().collect::<String>()));
+		} else if ch.is_alphabetic() {
+			// upright text (e.g., "sin") is a single identifier; italic letters are separate identifiers
+			if style == "p" {
+				while i < chars.len() && chars[i].is_alphabetic() {
+					i += 1;
+				}
+			}
+			result.push(mi(&chars[start..i].iter().collect::<String>()));
+		} else if crate::latex::IDENTIFIERS.values().any(|&identifier| identifier.starts_with(ch) && identifier.chars().count() == 1) {
+			result.push(mi(&ch.to_string()));
+		} else {
+			result.push(mo(&ch.to_string()));
+		}
+	}
+	return Ok(result);
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fraction() {
+		let omml = r#"<m:oMath xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">
+				<m:f><m:num><m:r><m:t>1</m:t></m:r></m:num><m:den><m:r><m:t>2</m:t></m:r></m:den></m:f>
+			</m:oMath>"#;
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>");
+	}
+
+	#[test]
+	fn run_tokens() {
+		let omml = "<m:oMath><m:r><m:t>2x+10.5</m:t></m:r></m:oMath>";
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mrow><mn>2</mn><mi>x</mi><mo>+</mo><mn>10.5</mn></mrow></math>");
+	}
+
+	#[test]
+	fn function_name() {
+		let omml = "<m:oMath><m:func>
+				<m:fName><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>sin</m:t></m:r></m:fName>
+				<m:e><m:r><m:t>x</m:t></m:r></m:e>
+			</m:func></m:oMath>";
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mrow><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi></mrow></math>");
+	}
+
+	#[test]
+	fn delimiter_with_separators() {
+		let omml = "<m:oMath><m:d><m:dPr><m:begChr m:val='{'/><m:endChr m:val='}'/><m:sepChr m:val=','/></m:dPr>
+				<m:e><m:r><m:t>a</m:t></m:r></m:e><m:e><m:r><m:t>b</m:t></m:r></m:e>
+			</m:d></m:oMath>";
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mrow><mo>{</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>}</mo></mrow></math>");
+	}
+
+	#[test]
+	fn nary_hidden_sup() {
+		let omml = "<m:oMath><m:nary><m:naryPr><m:chr m:val='∑'/><m:supHide m:val='1'/></m:naryPr>
+				<m:sub><m:r><m:t>i</m:t></m:r></m:sub><m:sup/><m:e><m:r><m:t>i</m:t></m:r></m:e>
+			</m:nary></m:oMath>";
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mrow><munder><mo>∑</mo><mi>i</mi></munder><mi>i</mi></mrow></math>");
+	}
+
+	#[test]
+	fn equation_array() {
+		let omml = "<m:oMathPara><m:oMath><m:eqArr>
+				<m:e><m:r><m:t>x&amp;=1</m:t></m:r></m:e>
+				<m:e><m:r><m:t>y&amp;=2</m:t></m:r></m:e>
+			</m:eqArr></m:oMath></m:oMathPara>";
+		assert_eq!(omml_to_mathml(omml).unwrap(), "<math><mtable columnalign='left'>\
+				<mtr><mtd><mrow><mi>x</mi><mo>=</mo><mn>1</mn></mrow></mtd></mtr>\
+				<mtr><mtd><mrow><mi>y</mi><mo>=</mo><mn>2</mn></mrow></mtd></mtr>\
+			</mtable></math>");
+	}
+
+	#[test]
+	fn unknown_element() {
+		assert!(omml_to_mathml("<m:oMath><m:foo/></m:oMath>").is_err());
+	}
+}
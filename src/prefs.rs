@@ -75,12 +75,20 @@ impl Preferences{
         prefs.insert("Overview".to_string(), Yaml::Boolean(false));
         prefs.insert("ResetOverView".to_string(), Yaml::Boolean(true));
         prefs.insert("NavVerbosity".to_string(), Yaml::String("Verbose".to_string()));
+        prefs.insert("NavDifferenceSpeech".to_string(), Yaml::Boolean(false));
         prefs.insert("AutoZoomOut".to_string(), Yaml::Boolean(true));
+        prefs.insert("OverviewWordBudget".to_string(), Yaml::Integer(7));
+        prefs.insert("NavHistoryCount".to_string(), Yaml::Integer(3));
+        prefs.insert("KeyBindings".to_string(), Yaml::String("MathPlayer".to_string()));
+        prefs.insert("GestureBindings".to_string(), Yaml::String("Default".to_string()));
         prefs.insert("BrailleCode".to_string(), Yaml::String("Nemeth".to_string()));
         prefs.insert("BrailleNavHighlight".to_string(), Yaml::String("EndPoints".to_string()));
         prefs.insert("UEB_START_MODE".to_string(), Yaml::String("Grade2".to_string()));
+        prefs.insert("EightDot_Table".to_string(), Yaml::String("None".to_string()));
+        prefs.insert("BrailleEmphasis".to_string(), Yaml::String("None".to_string()));
         prefs.insert("DecimalSeparators".to_string(), Yaml::String(".".to_string()));
         prefs.insert("BlockSeparators".to_string(), Yaml::String(", \u{00A0}\u{202F}".to_string()));
+        prefs.insert("DisabledHeuristics".to_string(), Yaml::String("".to_string()));
     
         return Preferences{ prefs };
     }
@@ -95,6 +103,7 @@ impl Preferences{
         prefs.insert("Voice".to_string(), Yaml::String("none".to_string()));
         prefs.insert("Gender".to_string(), Yaml::String("none".to_string()));
         prefs.insert("Bookmark".to_string(), Yaml::Boolean(false));
+        prefs.insert("CanonicalizationReport".to_string(), Yaml::Boolean(false));
         prefs.insert("CapitalLetters_UseWord".to_string(), Yaml::Boolean(true));
         prefs.insert("CapitalLetters_Pitch".to_string(), Yaml::Real("0.0".to_string()));
         prefs.insert("CapitalLetters_Beep".to_string(), Yaml::Boolean(false));
@@ -138,6 +147,18 @@ impl Preferences{
         add_prefs(prefs, &doc["Navigation"], "", file_name);
         add_prefs(prefs, &doc["Braille"], "", file_name);
         add_prefs(prefs, &doc["Other"], "", file_name);
+        if let Some(disabled_heuristics) = prefs.get("DisabledHeuristics").and_then(|value| value.as_str()) {
+            // a bad name shouldn't keep the rest of the file from being used, so it is logged and dropped
+            let (known_names, unknown_names): (Vec<&str>, Vec<&str>) = disabled_heuristics.split([',', ' '])
+                .filter(|name| !name.is_empty())
+                .partition(|name| crate::canonicalize::is_heuristic_name(name));
+            if !unknown_names.is_empty() {
+                error!("Unknown heuristic(s) '{}' in 'DisabledHeuristics' in preference file {} are ignored",
+                       unknown_names.join(", "), file_name);
+                let known_names = known_names.join(", ");
+                prefs.insert("DisabledHeuristics".to_string(), Yaml::String(known_names));
+            }
+        }
         return Ok( Preferences{ prefs: prefs.to_owned() } );
 
 
@@ -523,11 +544,13 @@ impl PreferenceManager {
         };
         
         let decimal_separator = self.pref_to_string("DecimalSeparator");
-        if !["Auto", ",", "."].contains(&decimal_separator.as_str()) {
+        if !["Auto", "Detect", ",", "."].contains(&decimal_separator.as_str()) {
             return Ok( () );
         }
+        // "Detect" uses the language's separators unless the numbers in the expression say otherwise (see `detected_separators`)
+        let is_auto = decimal_separator == "Auto" || decimal_separator == "Detect";
 
-        if language_country == "Auto" && decimal_separator == "Auto" {
+        if language_country == "Auto" && is_auto {
             return Ok( () );        // "Auto" doesn't tell us anything -- we will get called again when Language is set
         }
 
@@ -537,18 +560,36 @@ impl PreferenceManager {
         let language = lang_country_split.next().unwrap_or("");
         let country = lang_country_split.next().unwrap_or("");
         let mut use_period = decimal_separator == ".";
-        if decimal_separator == "Auto" {
+        if is_auto {
             // if we don't have a match for the lang-country, then just try lang
             use_period = USE_DECIMAL_SEPARATOR.contains(language_country) || USE_DECIMAL_SEPARATOR.contains(language);
         }
         // debug!("set_separators: use_period: {}", use_period);
-        self.user_prefs.prefs.insert("DecimalSeparators".to_string(), Yaml::String((if use_period {"."} else {","}).to_string()));
+        self.insert_separators(use_period, country);
+        return Ok( () );
+    }
+
+    fn insert_separators(&mut self, use_period: bool, country: &str) {
+        let (decimal_separators, block_separators) = PreferenceManager::separators(use_period, country);
+        self.user_prefs.prefs.insert("DecimalSeparators".to_string(), Yaml::String(decimal_separators));
+        self.user_prefs.prefs.insert("BlockSeparators".to_string(), Yaml::String(block_separators));
+    }
+
+    /// The values of DecimalSeparators and BlockSeparators when the decimal separator is "." ('use_period') or "," in 'country'
+    fn separators(use_period: bool, country: &str) -> (String, String) {
         let mut block_separators =  (if use_period {", \u{00A0}\u{202F}"} else {". \u{00A0}\u{202F}"}).to_string();
         if country == "ch" || country == "li" { // Switzerland and Liechtenstein also use ` as a block separator, at least in some cases
             block_separators.push('\'');
         }
-        self.user_prefs.prefs.insert("BlockSeparators".to_string(), Yaml::String(block_separators));
-        return Ok( () );
+        return ( (if use_period {"."} else {","}).to_string(), block_separators );
+    }
+
+    /// Used when DecimalSeparator is "Detect": the (DecimalSeparators, BlockSeparators) to use for an expression whose numbers
+    ///   indicate the decimal separator is "." ('use_period') or ",".
+    /// The prefs aren't changed -- the detected values are only used for that expression (see `canonicalize::get_separators`).
+    pub fn detected_separators(&self, use_period: bool) -> (String, String) {
+        let language_country = self.pref_to_string("Language").to_ascii_lowercase();
+        return PreferenceManager::separators(use_period, language_country.split('-').nth(1).unwrap_or(""));
     }
 
 
@@ -755,7 +796,7 @@ impl PreferenceManager {
             let is_decimal_separators_changed = key == "DecimalSeparator" && current_decimal_separator != value;
             let is_language_changed = key == "Language" && self.user_prefs.prefs.get("Language").unwrap().as_str().unwrap() != value;
             self.user_prefs.prefs.insert(key.to_string(), Yaml::String(value.to_string()));
-            if is_decimal_separators_changed || (current_decimal_separator == "Auto" && is_language_changed) {
+            if is_decimal_separators_changed || (["Auto", "Detect"].contains(&current_decimal_separator) && is_language_changed) {
                 // a little messy about the language due immutable and mutable borrows)
                 let language = self.user_prefs.prefs.get("Language").unwrap_or(&DEFAULT_LANG).clone();
                 let language = language.as_str().unwrap();
@@ -1052,6 +1093,21 @@ cfg_if::cfg_if! {if #[cfg(not(feature = "include-zip"))] {
         })
     }
 
+    #[test]
+    fn disabled_heuristics_checked_in_prefs_file() {
+        let prefs_file = std::env::temp_dir().join(format!("mathcat-test-prefs-{}.yaml", std::process::id()));
+        // the unknown name is dropped, but the rest of the file is used
+        std::fs::write(&prefs_file, "Speech: {}\nNavigation: {}\nBraille: {}\nOther:\n  DisabledHeuristics: \"chemistry, xyz\"\n  DecimalSeparator: ','\n").unwrap();
+        let prefs = Preferences::read_prefs_file(&prefs_file, Preferences::user_defaults()).unwrap();
+        assert_eq!(prefs.prefs["DisabledHeuristics"].as_str(), Some("chemistry"));
+        assert_eq!(prefs.prefs["DecimalSeparator"].as_str(), Some(","));
+
+        std::fs::write(&prefs_file, "Speech: {}\nNavigation: {}\nBraille: {}\nOther:\n  DisabledHeuristics: \"chemistry, split-points\"\n").unwrap();
+        let prefs = Preferences::read_prefs_file(&prefs_file, Preferences::user_defaults()).unwrap();
+        assert_eq!(prefs.prefs["DisabledHeuristics"].as_str(), Some("chemistry, split-points"));
+        std::fs::remove_file(&prefs_file).unwrap();
+    }
+
     #[test]
     fn test_prefs() {
         PREF_MANAGER.with(|pref_manager| {
@@ -17,7 +17,7 @@ use std::time::SystemTime;
 use crate::definitions::read_definitions_file;
 use crate::errors::*;
 use crate::prefs::*;
-use crate::xpath_functions::is_leaf;
+use crate::xpath_functions::{is_leaf, is_ancestor, find_element_by_id};
 use yaml_rust::{YamlLoader, Yaml, yaml::Hash};
 use crate::tts::*;
 use crate::infer_intent::*;
@@ -40,6 +40,9 @@ const NO_EVAL_QUOTE_CHAR: char = '\u{efff}';            // a private space char
 const NO_EVAL_QUOTE_CHAR_AS_BYTES: [u8;3] = [0xee,0xbf,0xbf];
 const N_BYTES_NO_EVAL_QUOTE_CHAR: usize = NO_EVAL_QUOTE_CHAR.len_utf8();
 
+/// Attribute added (temporarily) by `overview_mathml` to sub-expressions that should be spoken by their category name
+const OVERVIEW_COLLAPSED_ATTR: &str = "data-overview-collapsed";
+
 /// Converts 'string' into a "quoted" string -- use is_quoted_string and unquote_string
 pub fn make_quoted_string(mut string: String) -> String {
     string.push(NO_EVAL_QUOTE_CHAR);
@@ -82,8 +85,75 @@ pub fn speak_mathml(mathml: Element, nav_node_id: &str, nav_node_offset: usize)
     return speak_rules(&SPEECH_RULES, mathml, nav_node_id, nav_node_offset);
 }
 
+/// Speak an overview of `mathml` (or of the node with `nav_node_id` if that is not empty).
+///
+/// The overview aims to stay within the "OverviewWordBudget" preference (0 means no limit).
+/// If it doesn't, the largest sub-expressions are collapsed to their category names (e.g., "fraction")
+///   one at a time until the speech fits or there is nothing left to collapse.
 pub fn overview_mathml(mathml: Element, nav_node_id: &str, nav_node_offset: usize) -> Result<String> {
-    return speak_rules(&OVERVIEW_RULES, mathml, nav_node_id, nav_node_offset);
+    let speech = speak_rules(&OVERVIEW_RULES, mathml, nav_node_id, nav_node_offset)?;
+    let budget = PreferenceManager::get().borrow().pref_to_string("OverviewWordBudget").parse::<f64>().unwrap_or(0.0).max(0.0) as usize;
+    if budget == 0 || count_words(&speech) <= budget {
+        return Ok(speech);
+    }
+
+    let target = if nav_node_id.is_empty() {
+        mathml
+    } else {
+        find_element_by_id(mathml, nav_node_id).unwrap_or(mathml)
+    };
+    let target = if name(target) == "math" && target.children().len() == 1 {as_element(target.children()[0])} else {target};
+
+    // biggest first; for ties, collapse the later one first so the start of the expression remains informative
+    let mut candidates = Vec::new();
+    for child in target.children().into_iter().filter_map(|child| child.element()) {
+        collect_collapse_candidates(child, &mut candidates);
+    }
+    candidates.reverse();
+    candidates.sort_by_key(|&(_, n_leaves)| std::cmp::Reverse(n_leaves));
+
+    let mut collapsed: Vec<Element> = Vec::new();
+    let result = (|| {
+        let mut speech = speech;
+        for (candidate, _) in candidates {
+            if collapsed.iter().any(|&ancestor| is_ancestor(ancestor, candidate)) {
+                continue;
+            }
+            candidate.set_attribute_value(OVERVIEW_COLLAPSED_ATTR, "true");
+            collapsed.push(candidate);
+            speech = speak_rules(&OVERVIEW_RULES, mathml, nav_node_id, nav_node_offset)?;
+            if count_words(&speech) <= budget {
+                return Ok(speech);
+            }
+        }
+        // still too long -- this is as short as it gets (the overview rules shorten long rows themselves)
+        return Ok(speech);
+    })();
+    for element in collapsed {
+        element.remove_attribute(OVERVIEW_COLLAPSED_ATTR);
+    }
+    return result;
+
+    fn count_words(speech: &str) -> usize {
+        return speech.split_whitespace()
+                .filter(|word| word.chars().any(|ch| ch.is_alphanumeric()))
+                .count();
+    }
+
+    /// Adds the non-leaf descendants of `element` (in document order) along with how many leaves they contain
+    fn collect_collapse_candidates<'a>(element: Element<'a>, candidates: &mut Vec<(Element<'a>, usize)>) -> usize {
+        if is_leaf(element) {
+            return 1;
+        }
+        let i_element = candidates.len();
+        candidates.push((element, 0));
+        let n_leaves = element.children().into_iter()
+                .filter_map(|child| child.element())
+                .map(|child| collect_collapse_candidates(child, candidates))
+                .sum();
+        candidates[i_element].1 = n_leaves;
+        return n_leaves;
+    }
 }
 
 
@@ -318,6 +388,7 @@ pub trait TreeOrString<'c, 'm:'c, T> {
     fn replace<'s:'c, 'r>(ra: &ReplacementArray, rules_with_context: &'r mut SpeechRulesWithContext<'c, 's,'m>, mathml: Element<'c>) -> Result<T>;
     fn replace_nodes<'s:'c, 'r>(rules: &'r mut SpeechRulesWithContext<'c, 's,'m>, nodes: Vec<Node<'c>>, mathml: Element<'c>) -> Result<T>;
     fn highlight_braille(braille: T, highlight_style: String) -> T;
+    fn mark_braille_source(braille: T, source_index: usize) -> T;
     fn mark_nav_speech(speech: T) -> T;
 }
 
@@ -346,6 +417,10 @@ impl<'c, 'm:'c> TreeOrString<'c, 'm, String> for String {
         return SpeechRulesWithContext::highlight_braille_string(braille, highlight_style);
     }
 
+    fn mark_braille_source(braille: String, source_index: usize) -> String {
+        return crate::braille::mark_braille_source(braille, source_index);
+    }
+
     fn mark_nav_speech(speech: String) -> String {
         return SpeechRulesWithContext::mark_nav_speech(speech);
     }
@@ -379,6 +454,10 @@ impl<'c, 'm:'c> TreeOrString<'c, 'm, Element<'m>> for Element<'m> {
         panic!("Internal error: highlight_braille called on a tree");
     }
 
+    fn mark_braille_source(_braille: Element<'c>, _source_index: usize) -> Element<'m> {
+        panic!("Internal error: mark_braille_source called on a tree");
+    }
+
     fn mark_nav_speech(_speech: Element<'c>) -> Element<'m> {
         panic!("Internal error: mark_nav_speech called on a tree");
     }
@@ -1167,25 +1246,19 @@ impl MyXPath {
                         i += 1;
                     },
                     '\'' => inside_quote = !inside_quote,
-                    '(' => {
-                        if !inside_quote {
-                            count += 1;
-                        }
-                        // FIX: it would be more efficient to spot "DEBUG" preceding this and recurse rather than matching the whole string and recursing
-                    },
-                    ')' => {
-                        if !inside_quote {
-                            count -= 1;
-                            if count == 0 {
-                                let arg = &chars[1..i].iter().collect::<String>();
-                                let escaped_arg = arg.replace('"', "\\\"");
-                                // DEBUG(...) may be inside 'arg' -- recurse
-                                let processed_arg = MyXPath::add_debug_string_arg(arg)?;
-
-                                // DEBUG(...) may be in the remainder of the string -- recurse
-                                let processed_rest = MyXPath::add_debug_string_arg(&chars[i+1..].iter().collect::<String>())?;
-                                return Ok( format!("({processed_arg}, \"{escaped_arg}\"){processed_rest}") );
-                            }
+                    // FIX: it would be more efficient to spot "DEBUG" preceding this and recurse rather than matching the whole string and recursing
+                    '(' if !inside_quote => count += 1,
+                    ')' if !inside_quote => {
+                        count -= 1;
+                        if count == 0 {
+                            let arg = &chars[1..i].iter().collect::<String>();
+                            let escaped_arg = arg.replace('"', "\\\"");
+                            // DEBUG(...) may be inside 'arg' -- recurse
+                            let processed_arg = MyXPath::add_debug_string_arg(arg)?;
+
+                            // DEBUG(...) may be in the remainder of the string -- recurse
+                            let processed_rest = MyXPath::add_debug_string_arg(&chars[i+1..].iter().collect::<String>())?;
+                            return Ok( format!("({processed_arg}, \"{escaped_arg}\"){processed_rest}") );
                         }
                     },
                     _ => (),
@@ -2094,7 +2167,7 @@ impl fmt::Display for SpeechRules {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "SpeechRules '{}'\n{})", self.name, self.pref_manager.borrow())?;
         let mut rules_vec: Vec<(&String, &Vec<Box<SpeechPattern>>)> = self.rules.iter().collect();
-        rules_vec.sort_by(|(tag_name1, _), (tag_name2, _)| tag_name1.cmp(tag_name2));
+        rules_vec.sort_by_key(|(tag_name, _)| *tag_name);
         for (tag_name, rules) in rules_vec {
             writeln!(f, "   {}: #patterns {}", tag_name, rules.len())?;
         };
@@ -2114,6 +2187,7 @@ pub struct SpeechRulesWithContext<'c, 's:'c, 'm:'c> {
     nav_node_offset: usize,
     pub inside_spell: bool,     // hack to allow 'spell' to avoid infinite loop (see 'spell' implementation in tts.rs)
     pub translate_count: usize, // hack to avoid 'translate' infinite loop (see 'spell' implementation in tts.rs)
+    pub braille_sources: Option<Vec<Element<'c>>>,  // if Some, the braille for each node is marked so cells can be mapped to nodes
 }
 
 impl<'c, 's:'c, 'm:'c> fmt::Display for SpeechRulesWithContext<'c, 's,'m> {
@@ -2365,6 +2439,7 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
             nav_node_offset,
             inside_spell: false,
             translate_count: 0,
+            braille_sources: None,
         }
     }
 
@@ -2446,11 +2521,18 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
                 return match result {
                     Ok(s) => {
                         // for all except braille and navigation, nav_node_id will be an empty string and will not match
-                        if self.nav_node_id.is_empty() {
-                            Ok( Some(s) )
+                        let s = if self.nav_node_id.is_empty() {
+                            s
                         } else {
                             if self.nav_node_id == mathml.attribute_value("id").unwrap_or_default() {debug!("Matched pattern name/tag: {}/{}", pattern.pattern_name, pattern.tag_name)};
-                            Ok ( Some(self.nav_node_adjust(s, mathml)) )
+                            self.nav_node_adjust(s, mathml)
+                        };
+                        match &mut self.braille_sources {
+                            Some(sources) if mathml.attribute_value("id").is_some() => {
+                                sources.push(mathml);
+                                Ok( Some(T::mark_braille_source(s, sources.len() - 1)) )
+                            },
+                            _ => Ok( Some(s) ),
                         }
                     },
                     Err(e) => Err( e.context(
@@ -2512,6 +2594,11 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
         if &highlight_style == "Off" || braille.is_empty() {
             return braille;
         }
+
+        // 8-dot braille can't use dots 7 & 8 -- text-based codes get markers that are turned into a range after conversion to braille
+        if crate::braille::uses_highlight_markers() {
+            return format!("{}{}{}", crate::braille::HIGHLIGHT_START, braille, crate::braille::HIGHLIGHT_END);
+        }
         
         // FIX: this seems needlessly complex. It is much simpler if the char can be changed in place...
         // find first char that can get the dots and add them
@@ -0,0 +1,596 @@
+//! Converts UnicodeMath (the linear format used by Microsoft Office -- Unicode Technical Note #28)
+//!   to MathML (see [`crate::interface::set_unicodemath`]).
+//!
+//! UnicodeMath's conventions for grouping differ from AsciiMath's:
+//! * the operands of `/`, `^`, and `_` are runs of operands that aren't separated by operators or spaces:
+//!   `αβ/γδ` is a fraction with `αβ` as the numerator and `a_ij` has `ij` as the subscript
+//! * the outer brackets of an operand of `/`, `^`, `_`, and `√` are removed: `(a+b)/c`
+//! * `〖...〗` groups without showing brackets, `▒` gives the operand of an n-ary operator (e.g., `∑_(i=1)^n▒i`)
+//!   -- the operand runs up to the next binary operator or relation (e.g., `∫_0^1▒f(x)dx`)
+//! * `■(a&b@c&d)` is a matrix and `█(...)` is an equation array (`&` separates columns, `@` separates rows)
+//!   -- `⒨`, `ⓢ`, `Ⓢ`, `⒱`, and `ⓥ` are matrices in parens, brackets, braces, bars, and double bars
+//! * a control word like `\alpha` stands for its character
+//!
+//! As with LaTeX input, the MathML is meant to be passed to `set_mathml` so that it is cleaned up.
+#![allow(clippy::needless_return)]
+
+use crate::errors::*;
+use crate::latex::{escape_xml, mo, wrap_in_mrow, FUNCTIONS, FUNCTIONS_WITH_LIMITS, IDENTIFIERS, LARGE_OPERATORS, OPERATORS};
+use phf::{phf_map, phf_set};
+
+/// Convert a UnicodeMath string to MathML.
+pub fn unicodemath_to_mathml(unicodemath: &str) -> Result<String> {
+    let mut parser = UnicodeMathParser::new(unicodemath.trim())?;
+    let mut children = Vec::new();
+    loop {
+        children.append(&mut parser.parse_expression(&[], false)?);
+        // unmatched right brackets are just operators
+        match parser.next() {
+            None => break,
+            Some(ch) => children.push(mo(&ch.to_string())),
+        }
+    }
+    return Ok(format!("<math>{}</math>", wrap_in_mrow(children)));
+}
+
+/// UnicodeMath control words that aren't LaTeX names (LaTeX names such as `\alpha` also work)
+static CONTROL_WORDS: phf::Map<&str, char> = phf_map! {
+    "sqrt" => '√', "cbrt" => '∛', "qdrt" => '∜', "matrix" => '■', "eqarray" => '█',
+    "pmatrix" => '⒨', "bmatrix" => 'ⓢ', "Bmatrix" => 'Ⓢ', "vmatrix" => '⒱', "Vmatrix" => 'ⓥ',
+    "naryand" => '▒', "of" => '▒', "below" => '┬', "above" => '┴', "funcapply" => '\u{2061}',
+    "begin" => '〖', "end" => '〗',
+};
+
+/// n-ary operators: whether scripts go above and below them
+static NARY_OPERATORS: phf::Map<char, bool> = phf_map! {
+    '∑' => true, '∏' => true, '∐' => true, '⋃' => true, '⋂' => true, '⋁' => true, '⋀' => true,
+    '⨁' => true, '⨂' => true, '⨆' => true,
+    '∫' => false, '∬' => false, '∭' => false, '∮' => false, '∯' => false, '∰' => false,
+};
+
+/// Matrices: the fences around them
+static MATRICES: phf::Map<char, (&str, &str)> = phf_map! {
+    '■' => ("", ""), '⒨' => ("(", ")"), 'ⓢ' => ("[", "]"), 'Ⓢ' => ("{", "}"), '⒱' => ("|", "|"), 'ⓥ' => ("‖", "‖"),
+};
+
+static OPEN_BRACKETS: phf::Set<char> = phf_set! { '(', '[', '{', '⟨', '⌊', '⌈', '〖' };
+static CLOSE_BRACKETS: phf::Set<char> = phf_set! { ')', ']', '}', '⟩', '⌋', '⌉', '〗' };
+
+/// Operators in the ASCII and Latin-1 range (the Unicode math operator and arrow blocks are also operators)
+static OPERATOR_CHARS: phf::Set<char> = phf_set! {
+    '+', '-', '=', '<', '>', ',', ';', ':', '!', '*', '.', '?', '±', '×', '÷', '¬', '·', '…', '′', '″', '‴',
+};
+
+/// Combining accents and the character used for them in MathML
+static ACCENTS: phf::Map<char, &str> = phf_map! {
+    '\u{0300}' => "`", '\u{0301}' => "´", '\u{0302}' => "^", '\u{0303}' => "~", '\u{0304}' => "¯", '\u{0305}' => "¯",
+    '\u{0306}' => "˘", '\u{0307}' => "˙", '\u{0308}' => "¨", '\u{030C}' => "ˇ", '\u{20D6}' => "←", '\u{20D7}' => "→",
+    '\u{20E1}' => "↔",
+};
+
+fn is_operator(ch: char) -> bool {
+    return OPERATOR_CHARS.contains(&ch) ||
+           (('\u{2190}'..='\u{22FF}').contains(&ch) &&
+                !matches!(ch, '∂' | '∇' | '∞' | '∅' | '√' | '∛' | '∜') && !NARY_OPERATORS.contains_key(&ch)) ||
+           ('\u{27F0}'..='\u{27FF}').contains(&ch) || ('\u{2A00}'..='\u{2AFF}').contains(&ch);
+}
+
+/// A parsed operand: its MathML, the MathML without its outer brackets (if bracketed),
+///   and whether scripts go above/below it
+struct Node {
+    mathml: String,
+    without_brackets: Option<String>,
+    limits: bool,
+}
+
+impl Node {
+    fn new(mathml: String) -> Node {
+        return Node { mathml, without_brackets: None, limits: false };
+    }
+
+    fn without_brackets(self) -> String {
+        return self.without_brackets.unwrap_or(self.mathml);
+    }
+}
+
+/// A character of the input -- escaped chars (e.g. `\(`) are always literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InputChar {
+    ch: char,
+    is_escaped: bool,
+}
+
+struct UnicodeMathParser {
+    chars: Vec<InputChar>,
+    i: usize,
+}
+
+impl UnicodeMathParser {
+    /// Create the parser, replacing control words by their character
+    fn new(unicodemath: &str) -> Result<UnicodeMathParser> {
+        let mut chars = Vec::new();
+        let mut input = unicodemath.chars().peekable();
+        while let Some(ch) = input.next() {
+            if ch != '\\' {
+                chars.push(InputChar { ch, is_escaped: false });
+                continue;
+            }
+            let mut name = String::new();
+            while let Some(&ch) = input.peek() {
+                if !ch.is_ascii_alphabetic() {
+                    break;
+                }
+                name.push(ch);
+                input.next();
+            }
+            if name.is_empty() {
+                match input.next() {
+                    Some(ch) => chars.push(InputChar { ch, is_escaped: true }),
+                    None => bail!("UnicodeMath error: '\\' at end of input"),
+                }
+                continue;
+            }
+            let replacement = CONTROL_WORDS.get(name.as_str()).copied()
+                .or_else(|| IDENTIFIERS.get(name.as_str()).and_then(|str| str.chars().next()))
+                .or_else(|| OPERATORS.get(name.as_str()).and_then(|str| str.chars().next()))
+                .or_else(|| LARGE_OPERATORS.get(name.as_str()).and_then(|(str, _)| str.chars().next()));
+            match replacement {
+                Some(ch) => chars.push(InputChar { ch, is_escaped: false }),
+                None => bail!("UnicodeMath error: unknown control word '\\{}'", name),
+            }
+            // a space after a control word just ends it
+            if input.peek() == Some(&' ') {
+                input.next();
+            }
+        }
+        return Ok(UnicodeMathParser { chars, i: 0 });
+    }
+
+    /// The next (unescaped) char
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.i).filter(|input| !input.is_escaped).map(|input| input.ch);
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.get(self.i)?.ch;
+        self.i += 1;
+        return Some(ch);
+    }
+
+    fn at_end(&self) -> bool {
+        return self.i >= self.chars.len();
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(|ch| ch.is_whitespace() || ch == '\u{200B}') {
+            self.i += 1;
+        }
+    }
+
+    /// Does the current char end an expression?
+    fn at_expression_end(&self, stops: &[char], in_abs: bool) -> bool {
+        return match self.peek() {
+            None => self.at_end(),
+            Some(ch) => stops.contains(&ch) || CLOSE_BRACKETS.contains(&ch) || (in_abs && ch == '|'),
+        };
+    }
+
+    /// Does the current char end a run of operands?
+    fn at_operand_end(&self, stops: &[char], in_abs: bool) -> bool {
+        return self.at_expression_end(stops, in_abs) ||
+            match self.peek() {
+                None => false,  // escaped char
+                Some(ch) => ch.is_whitespace() || ch == '/' || ch == '▒' || (ch != '|' && is_operator(ch)),
+            };
+    }
+
+    /// Parse up to the end, a close bracket, a char in `stops`, or (if `in_abs`) a '|'
+    fn parse_expression(&mut self, stops: &[char], in_abs: bool) -> Result<Vec<String>> {
+        let mut children = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.at_expression_end(stops, in_abs) {
+                return Ok(children);
+            }
+            let ch = self.peek();
+            if ch.is_some_and(|ch| ch != '|' && is_operator(ch)) {
+                self.i += 1;
+                children.push(mo(&ch.map(|ch| if ch == '-' {'−'} else {ch}).unwrap().to_string()));
+                continue;
+            }
+            if ch == Some('/') {
+                // no numerator
+                self.i += 1;
+                children.push(mo("/"));
+                continue;
+            }
+            children.push(self.parse_term(stops, in_abs)?.mathml);
+        }
+    }
+
+    /// Parse a run of operands along with a denominator if it is followed by '/'
+    fn parse_term(&mut self, stops: &[char], in_abs: bool) -> Result<Node> {
+        let operand = self.parse_operand_run(stops, in_abs)?;
+        self.skip_spaces();
+        if self.peek() != Some('/') {
+            return Ok(operand);
+        }
+        self.i += 1;
+        self.skip_spaces();
+        let denominator = self.parse_operand_run(stops, in_abs)?;
+        return Ok(Node::new(format!("<mfrac>{}{}</mfrac>", operand.without_brackets(), denominator.without_brackets())));
+    }
+
+    /// Parse the operand of an n-ary operator (the '▒' has been consumed):
+    ///   everything up to the next binary operator or relation (a leading sign is part of it)
+    fn parse_nary_operand(&mut self, stops: &[char], in_abs: bool) -> Result<String> {
+        let mut terms = Vec::new();
+        self.skip_spaces();
+        if let Some(ch) = self.peek().filter(|&ch| matches!(ch, '-' | '−' | '+')) {
+            self.i += 1;
+            terms.push(Node::new(mo(if ch == '+' {"+"} else {"−"})));
+        }
+        loop {
+            self.skip_spaces();
+            if self.at_expression_end(stops, in_abs) ||
+               self.peek().is_some_and(|ch| ch != '|' && is_operator(ch) && !matches!(ch, '!' | '′' | '″' | '‴')) {
+                break;
+            }
+            terms.push(self.parse_term(stops, in_abs)?);
+        }
+        if terms.is_empty() {
+            bail!("UnicodeMath error: missing operand after '▒'");
+        }
+        if terms.len() == 1 {
+            return Ok(terms.pop().unwrap().without_brackets());
+        }
+        return Ok(wrap_in_mrow(terms.into_iter().map(|term| term.mathml).collect()));
+    }
+
+    /// Parse operands up to a space or operator (e.g., `2πr`)
+    fn parse_operand_run(&mut self, stops: &[char], in_abs: bool) -> Result<Node> {
+        let mut nodes = Vec::new();
+        while !self.at_operand_end(stops, in_abs) || nodes.is_empty() {
+            if self.at_expression_end(stops, in_abs) {
+                bail!("UnicodeMath error: missing operand");
+            }
+            nodes.push(self.parse_scripted(stops, in_abs)?);
+        }
+        if nodes.len() == 1 {
+            return Ok(nodes.pop().unwrap());
+        }
+        return Ok(Node::new(wrap_in_mrow(nodes.into_iter().map(|node| node.mathml).collect())));
+    }
+
+    /// Parse an operand of a script -- it can start with a sign (e.g., `x^-1`), but it doesn't have scripts of its own
+    fn parse_script_operand(&mut self, stops: &[char], in_abs: bool) -> Result<String> {
+        let mut nodes = Vec::new();
+        if let Some(ch) = self.peek().filter(|&ch| matches!(ch, '-' | '−' | '+')) {
+            self.i += 1;
+            nodes.push(Node::new(mo(if ch == '+' {"+"} else {"−"})));
+        }
+        let script_stops = [stops, &['^', '_', '┬', '┴']].concat();
+        while !self.at_operand_end(&script_stops, in_abs) {
+            nodes.push(self.parse_factor(stops, in_abs)?);
+        }
+        if nodes.is_empty() {
+            bail!("UnicodeMath error: missing script");
+        }
+        if nodes.len() == 1 {
+            return Ok(nodes.pop().unwrap().without_brackets());
+        }
+        return Ok(wrap_in_mrow(nodes.into_iter().map(|node| node.mathml).collect()));
+    }
+
+    /// Parse a factor along with any scripts
+    fn parse_scripted(&mut self, stops: &[char], in_abs: bool) -> Result<Node> {
+        let base = self.parse_factor(stops, in_abs)?;
+        let mut sub = None;
+        let mut sup = None;
+        let mut limits = base.limits;
+        loop {
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.i += 1;
+                    sub = Some(self.parse_script_operand(stops, in_abs)?);
+                },
+                Some('^') if sup.is_none() => {
+                    self.i += 1;
+                    sup = Some(self.parse_script_operand(stops, in_abs)?);
+                },
+                Some('┬') if sub.is_none() => {
+                    self.i += 1;
+                    limits = true;
+                    sub = Some(self.parse_script_operand(stops, in_abs)?);
+                },
+                Some('┴') if sup.is_none() => {
+                    self.i += 1;
+                    limits = true;
+                    sup = Some(self.parse_script_operand(stops, in_abs)?);
+                },
+                Some(ch @ ('′' | '″' | '‴')) if sup.is_none() => {
+                    self.i += 1;
+                    sup = Some(mo(&ch.to_string()));
+                },
+                _ => break,
+            }
+        }
+        let (under, over, both) = if limits { ("munder", "mover", "munderover") } else { ("msub", "msup", "msubsup") };
+        let is_nary = base.mathml.starts_with("<mo>") && self.peek() == Some('▒');
+        let scripted = match (sub, sup) {
+            (None, None) if !is_nary => return Ok(base),
+            (None, None) => base.mathml,
+            (Some(sub), None) => format!("<{under}>{}{sub}</{under}>", base.mathml),
+            (None, Some(sup)) => format!("<{over}>{}{sup}</{over}>", base.mathml),
+            (Some(sub), Some(sup)) => format!("<{both}>{}{sub}{sup}</{both}>", base.mathml),
+        };
+        if !is_nary {
+            return Ok(Node::new(scripted));
+        }
+        self.i += 1;
+        let operand = self.parse_nary_operand(stops, in_abs)?;
+        return Ok(Node::new(format!("<mrow>{scripted}{operand}</mrow>")));
+    }
+
+    fn parse_factor(&mut self, stops: &[char], in_abs: bool) -> Result<Node> {
+        let input = match self.chars.get(self.i) {
+            None => bail!("UnicodeMath error: unexpected end of input"),
+            Some(&input) => input,
+        };
+        self.i += 1;
+        let ch = input.ch;
+        if input.is_escaped {
+            return Ok(Node::new(if ch.is_alphanumeric() {format!("<mi>{ch}</mi>")} else {mo(&ch.to_string())}));
+        }
+        let node = if ch.is_ascii_digit() || (ch == '.' && self.peek().is_some_and(|ch| ch.is_ascii_digit())) {
+            let start = self.i - 1;
+            let mut seen_decimal_point = ch == '.';
+            while let Some(ch) = self.peek() {
+                if ch == '.' && !seen_decimal_point && self.chars.get(self.i + 1).is_some_and(|next| next.ch.is_ascii_digit()) {
+                    seen_decimal_point = true;
+                } else if !ch.is_ascii_digit() {
+                    break;
+                }
+                self.i += 1;
+            }
+            Node::new(format!("<mn>{}</mn>", self.text(start, self.i)))
+        } else if ch.is_ascii_alphabetic() {
+            // function names are recognized (longest match), otherwise each letter is a variable
+            let start = self.i - 1;
+            let mut end = start + 1;
+            while self.chars.get(end).is_some_and(|input| !input.is_escaped && input.ch.is_ascii_alphabetic()) {
+                end += 1;
+            }
+            let function = (start + 2..=end).rev()
+                .map(|end| self.text(start, end))
+                .find(|name| FUNCTIONS.contains(name.as_str()));
+            match function {
+                Some(name) => {
+                    self.i = start + name.len();
+                    Node { limits: FUNCTIONS_WITH_LIMITS.contains(name.as_str()), ..Node::new(format!("<mi>{name}</mi>")) }
+                },
+                None => Node::new(format!("<mi>{ch}</mi>")),
+            }
+        } else if let Some(&limits) = NARY_OPERATORS.get(&ch) {
+            Node { limits, ..Node::new(mo(&ch.to_string())) }
+        } else if OPEN_BRACKETS.contains(&ch) || ch == '|' || ch == '‖' {
+            self.parse_bracketed(ch)?
+        } else {
+            match ch {
+                '√' | '∛' | '∜' => {
+                    self.skip_spaces();
+                    if ch == '√' && self.peek() == Some('(') && self.has_index() {
+                        self.i += 1;
+                        let index = wrap_in_mrow(self.parse_expression(&['&'], false)?);
+                        self.i += 1;    // '&'
+                        let radicand = wrap_in_mrow(self.parse_expression(&[], false)?);
+                        if self.next() != Some(')') {
+                            bail!("UnicodeMath error: missing ')' for '√('");
+                        }
+                        Node::new(format!("<mroot>{radicand}{index}</mroot>"))
+                    } else {
+                        let radicand = self.parse_scripted(stops, in_abs)?.without_brackets();
+                        match ch {
+                            '√' => Node::new(format!("<msqrt>{radicand}</msqrt>")),
+                            '∛' => Node::new(format!("<mroot>{radicand}<mn>3</mn></mroot>")),
+                            _ => Node::new(format!("<mroot>{radicand}<mn>4</mn></mroot>")),
+                        }
+                    }
+                },
+                '■' | '█' | '⒨' | 'ⓢ' | 'Ⓢ' | '⒱' | 'ⓥ' => {
+                    self.skip_spaces();
+                    if self.next() != Some('(') {
+                        bail!("UnicodeMath error: '{}' must be followed by '('", ch);
+                    }
+                    let mut rows = String::new();
+                    let mut row = String::new();
+                    loop {
+                        let cell = wrap_in_mrow(self.parse_expression(&['&', '@'], false)?);
+                        row.push_str(&format!("<mtd>{cell}</mtd>"));
+                        match self.next() {
+                            Some('&') => (),
+                            Some('@') => rows.push_str(&format!("<mtr>{}</mtr>", std::mem::take(&mut row))),
+                            Some(')') => break,
+                            _ => bail!("UnicodeMath error: missing ')' for '{}('", ch),
+                        }
+                    }
+                    rows.push_str(&format!("<mtr>{row}</mtr>"));
+                    let align = if ch == '█' {" columnalign='right left'"} else {""};
+                    let table = format!("<mtable{align}>{rows}</mtable>");
+                    match MATRICES.get(&ch) {
+                        Some(&(open, close)) if !open.is_empty() => Node::new(format!("<mrow>{}{}{}</mrow>", mo(open), table, mo(close))),
+                        _ => Node::new(table),
+                    }
+                },
+                '"' => {
+                    let start = self.i;
+                    while self.peek().is_some_and(|ch| ch != '"') {
+                        self.i += 1;
+                    }
+                    let text = self.text(start, self.i);
+                    self.i += 1;
+                    Node::new(format!("<mtext>{}</mtext>", escape_xml(&text)))
+                },
+                '\u{2061}' => Node::new(mo("\u{2061}")),
+                _ if ch.is_alphabetic() || matches!(ch, '∂' | '∇' | '∞' | '∅') => Node::new(format!("<mi>{ch}</mi>")),
+                _ => Node::new(mo(&ch.to_string())),
+            }
+        };
+        // combining accents
+        let mut node = node;
+        while let Some(accent) = self.peek().and_then(|ch| ACCENTS.get(&ch)) {
+            self.i += 1;
+            node = Node::new(format!("<mover accent='true'>{}{}</mover>", node.mathml, mo(accent)));
+        }
+        return Ok(node);
+    }
+
+    /// Parse a bracketed expression (the open bracket has been consumed)
+    fn parse_bracketed(&mut self, open: char) -> Result<Node> {
+        if open == '|' || open == '‖' {
+            // an absolute value/norm if there is a matching bar, otherwise just an operator
+            let start = self.i;
+            let children = self.parse_expression(&[open], open == '|');
+            if let Ok(children) = children && self.peek() == Some(open) {
+                self.i += 1;
+                let contents = wrap_in_mrow(children.clone());
+                let open = mo(&open.to_string());
+                return Ok(Node {
+                    mathml: format!("<mrow>{}{}{}</mrow>", open, children.join(""), open),
+                    without_brackets: Some(contents),
+                    limits: false
+                });
+            }
+            self.i = start;
+            return Ok(Node::new(mo(&open.to_string())));
+        }
+        let children = self.parse_expression(&[], false)?;
+        let close = match self.peek() {
+            Some(ch) if CLOSE_BRACKETS.contains(&ch) => {
+                self.i += 1;
+                Some(ch)
+            },
+            _ => None,
+        };
+        let contents = wrap_in_mrow(children.clone());
+        if open == '〖' {
+            return Ok(Node { mathml: contents.clone(), without_brackets: Some(contents), limits: false });
+        }
+        let close = close.map(|ch| mo(&ch.to_string())).unwrap_or_default();
+        let mathml = format!("<mrow>{}{}{}</mrow>", mo(&open.to_string()), children.join(""), close);
+        // UnicodeMath only removes parens (other brackets are kept)
+        let without_brackets = if open == '(' { Some(contents) } else { None };
+        return Ok(Node { mathml, without_brackets, limits: false });
+    }
+
+    /// Is there a top-level '&' before the ')' that matches the current '('? (e.g, `√(3&x)`)
+    fn has_index(&self) -> bool {
+        let mut depth = 0;
+        for input in &self.chars[self.i..] {
+            if input.is_escaped {
+                continue;
+            }
+            match input.ch {
+                ch if OPEN_BRACKETS.contains(&ch) => depth += 1,
+                ch if CLOSE_BRACKETS.contains(&ch) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return false;
+                    }
+                },
+                '&' if depth == 1 => return true,
+                _ => (),
+            }
+        }
+        return false;
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        return self.chars[start..end].iter().map(|input| input.ch).collect();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(unicodemath: &str, mathml: &str) {
+        match unicodemath_to_mathml(unicodemath) {
+            Ok(result) => assert_eq!(result, format!("<math>{mathml}</math>"), "\nUnicodeMath: {unicodemath}"),
+            Err(e) => panic!("{}", crate::interface::errors_to_string(&e)),
+        }
+    }
+
+    #[test]
+    fn simple() {
+        test("x+2.5", "<mrow><mi>x</mi><mo>+</mo><mn>2.5</mn></mrow>");
+        test("\\alpha ≤∞", "<mrow><mi>α</mi><mo>≤</mo><mi>∞</mi></mrow>");
+        test("sin x", "<mrow><mi>sin</mi><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn fractions() {
+        test("(a+b)/c", "<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac>");
+        test("αβ/γδ", "<mfrac><mrow><mi>α</mi><mi>β</mi></mrow><mrow><mi>γ</mi><mi>δ</mi></mrow></mfrac>");
+        test("a+b/c", "<mrow><mi>a</mi><mo>+</mo><mfrac><mi>b</mi><mi>c</mi></mfrac></mrow>");
+        test("x^2/3", "<mfrac><msup><mi>x</mi><mn>2</mn></msup><mn>3</mn></mfrac>");
+    }
+
+    #[test]
+    fn scripts() {
+        test("a_ij^2", "<msubsup><mi>a</mi><mrow><mi>i</mi><mi>j</mi></mrow><mn>2</mn></msubsup>");
+        test("x^-1", "<msup><mi>x</mi><mrow><mo>−</mo><mn>1</mn></mrow></msup>");
+        test("e^(x+1)", "<msup><mi>e</mi><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></msup>");
+        test("∑_(i=1)^n▒i^2",
+            "<mrow><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>\
+             <msup><mi>i</mi><mn>2</mn></msup></mrow>");
+        test("∫_0^1▒f(x)dx+C",
+            "<mrow><mrow><msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup>\
+             <mrow><mi>f</mi><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow><mi>d</mi><mi>x</mi></mrow></mrow><mo>+</mo><mi>C</mi></mrow>");
+        test("∑▒(a+b) x=y",
+            "<mrow><mrow><mo>∑</mo><mrow><mrow><mo>(</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>)</mo></mrow><mi>x</mi></mrow></mrow>\
+             <mo>=</mo><mi>y</mi></mrow>");
+        test("∏▒(1+x)", "<mrow><mo>∏</mo><mrow><mn>1</mn><mo>+</mo><mi>x</mi></mrow></mrow>");
+        test("lim┬(n→∞)", "<munder><mi>lim</mi><mrow><mi>n</mi><mo>→</mo><mi>∞</mi></mrow></munder>");
+        test("f′", "<msup><mi>f</mi><mo>′</mo></msup>");
+    }
+
+    #[test]
+    fn roots_and_accents() {
+        test("√(x+1)", "<msqrt><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></msqrt>");
+        test("√(3&x)", "<mroot><mi>x</mi><mn>3</mn></mroot>");
+        test("∛8", "<mroot><mn>8</mn><mn>3</mn></mroot>");
+        test("x\u{0302}", "<mover accent='true'><mi>x</mi><mo>^</mo></mover>");
+    }
+
+    #[test]
+    fn brackets() {
+        test("|x|", "<mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>");
+        test("[a,b)", "<mrow><mo>[</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>)</mo></mrow>");
+        test("〖x+1〗^2", "<msup><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mn>2</mn></msup>");
+        test("\\(x", "<mrow><mo>(</mo><mi>x</mi></mrow>");
+    }
+
+    #[test]
+    fn matrix() {
+        test("(■(1&2@3&4))",
+            "<mrow><mo>(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>\
+             <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr></mtable><mo>)</mo></mrow>");
+        test("⒨(a&b@c&d)",
+            "<mrow><mo>(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr>\
+             <mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo>)</mo></mrow>");
+        test("⒱(a&b@c&d)",
+            "<mrow><mo>|</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr>\
+             <mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo>|</mo></mrow>");
+        test("\\bmatrix(1)", "<mrow><mo>[</mo><mtable><mtr><mtd><mn>1</mn></mtd></mtr></mtable><mo>]</mo></mrow>");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(unicodemath_to_mathml("\\foo").is_err());
+        assert!(unicodemath_to_mathml("■(1&2").is_err());
+        assert!(unicodemath_to_mathml("⒨(1&2").is_err());
+        assert!(unicodemath_to_mathml("∑▒=0").is_err());
+    }
+}
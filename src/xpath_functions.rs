@@ -359,6 +359,28 @@ pub fn is_leaf(element: Element) -> bool {
     return MATHML_LEAF_NODES.contains(name(element));
 }
 
+/// Returns true if `ancestor` is a proper ancestor of `element` (i.e., not `element` itself)
+pub fn is_ancestor(ancestor: Element, element: Element) -> bool {
+    let mut element = element;
+    while let Some(parent) = element.parent().and_then(|parent| parent.element()) {
+        if parent == ancestor {
+            return true;
+        }
+        element = parent;
+    }
+    return false;
+}
+
+/// Returns `element` or the first of its descendants (in document order) whose 'id' is `id`
+pub fn find_element_by_id<'a>(element: Element<'a>, id: &str) -> Option<Element<'a>> {
+    if element.attribute_value("id") == Some(id) {
+        return Some(element);
+    }
+    return element.children().into_iter()
+            .filter_map(|child| child.element())
+            .find_map(|child| find_element_by_id(child, id));
+}
+
 impl Function for IsNode {
     // eval function for IsNode
     // errors happen for wrong number/kind of arg
@@ -452,10 +474,7 @@ impl ToOrdinal {
             let definitions = definitions.borrow();
             let numbers_large = definitions.get_vec("NumbersLarge")?;
 
-            let pref_manager = crate::prefs::PreferenceManager::get();
-            let pref_manager = pref_manager.borrow();
-            let block_separators = pref_manager.pref_to_string("BlockSeparators");
-            let decimal_separator = pref_manager.pref_to_string("DecimalSeparators");
+            let (decimal_separator, block_separators) = crate::canonicalize::get_separators();
 
             // check number validity (has digits, not a decimal)
             if number.is_empty() ||  number.contains(&decimal_separator) {
@@ -1305,7 +1324,7 @@ pub struct GetNavigationPartName;
 /// 'index' is 0-based
 /// 
 impl GetNavigationPartName {
-    fn navigation_part_name(intent_name: &str, index: usize) -> String {
+    pub fn navigation_part_name(intent_name: &str, index: usize) -> String {
         crate::definitions::SPEECH_DEFINITIONS.with(|definitions| {
             let definitions = definitions.borrow();
             if let Some(navigation_names) = definitions.get_hashmap("NavigationParts") &&
@@ -1418,6 +1437,7 @@ pub fn add_builtin_functions(context: &mut Context) {
     context.set_function("NestingChars", crate::braille::NemethNestingChars);
     context.set_function("BrailleChars", crate::braille::BrailleChars);
     context.set_function("NeedsToBeGrouped", crate::braille::NeedsToBeGrouped);
+    context.set_function("BrailleEmphasis", crate::braille::BrailleEmphasis);
     context.set_function("IsNode", IsNode);
     context.set_function("ToOrdinal", ToOrdinal);
     context.set_function("ToCommonFraction", ToCommonFraction);
@@ -31,4 +31,9 @@ mod chemistry;
 mod alphabets;
 mod intent;
 mod mtable;
+mod latex;
+mod asciimath;
+mod unicodemath;
+mod content;
+mod omml;
 
@@ -0,0 +1,65 @@
+/// Tests for AsciiMath input: each AsciiMath string should speak the same as the equivalent MathML
+use crate::common::*;
+
+#[test]
+fn fraction_drops_parens() {
+    let expr = "<math><mfrac><msup><mi>x</mi><mn>2</mn></msup><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow></mfrac></math>";
+    let asciimath = "x^2/(a+b)";
+    test("en", "SimpleSpeak", expr, "fraction, x squared, over, eigh plus b, end fraction");
+    test_asciimath("en", "SimpleSpeak", asciimath, "fraction, x squared, over, eigh plus b, end fraction");
+}
+
+#[test]
+fn fraction_binds_tightly() {
+    let expr = "<math><mi>a</mi><mfrac><mi>b</mi><mi>c</mi></mfrac><mi>d</mi></math>";
+    let asciimath = "ab/cd";
+    test("en", "SimpleSpeak", expr, "eigh b over c, d");
+    test_asciimath("en", "SimpleSpeak", asciimath, "eigh b over c, d");
+}
+
+#[test]
+fn quadratic_formula() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mfrac>
+            <mrow><mo>-</mo><mi>b</mi><mo>±</mo><msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>-</mo><mn>4</mn><mi>a</mi><mi>c</mi></msqrt></mrow>
+            <mrow><mn>2</mn><mi>a</mi></mrow>
+        </mfrac></math>";
+    let asciimath = "x = (-b +- sqrt(b^2-4ac))/(2a)";
+    test("en", "SimpleSpeak", expr, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+    test_asciimath("en", "SimpleSpeak", asciimath, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+}
+
+#[test]
+fn sum() {
+    let expr = "<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>
+            <msup><mi>i</mi><mn>2</mn></msup></math>";
+    let asciimath = "sum_(i=1)^n i^2";
+    test("en", "SimpleSpeak", expr, "the sum from i is equal to 1, to n of; i squared");
+    test_asciimath("en", "SimpleSpeak", asciimath, "the sum from i is equal to 1, to n of; i squared");
+}
+
+#[test]
+fn absolute_value() {
+    let expr = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>&lt;</mo><mn>1</mn></math>";
+    let asciimath = "|x| < 1";
+    test("en", "ClearSpeak", expr, "the absolute value of x; is less than 1");
+    test_asciimath("en", "ClearSpeak", asciimath, "the absolute value of x; is less than 1");
+}
+
+#[test]
+fn real_numbers() {
+    let expr = "<math><mi>x</mi><mo>∈</mo><mi mathvariant='double-struck'>R</mi></math>";
+    let asciimath = "x in RR";
+    test("en", "SimpleSpeak", expr, "x is an element of, the real numbers");
+    test_asciimath("en", "SimpleSpeak", asciimath, "x is an element of, the real numbers");
+}
+
+#[test]
+fn matrix() {
+    let expr = "<math><mrow><mo>[</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>]</mo></mrow></math>";
+    let asciimath = "[(1,2),(3,4)]";
+    test("en", "SimpleSpeak", expr, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+    test_asciimath("en", "SimpleSpeak", asciimath, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+}
@@ -0,0 +1,153 @@
+/// Tests for Content MathML input: each content expression should speak the same as the equivalent presentation MathML
+use crate::common::*;
+
+#[test]
+fn polynomial() {
+    let expr = "<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>2</mn><mi>x</mi><mo>+</mo><mn>1</mn></math>";
+    let content = "<math><apply><plus/>
+            <apply><power/><ci>x</ci><cn>2</cn></apply>
+            <apply><times/><cn>2</cn><ci>x</ci></apply>
+            <cn>1</cn>
+        </apply></math>";
+    test("en", "SimpleSpeak", expr, "x squared plus 2 x plus 1");
+    test("en", "SimpleSpeak", content, "x squared plus 2 x plus 1");
+}
+
+#[test]
+fn parenthesized_operands() {
+    let expr = "<math><mrow><mo>(</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>)</mo></mrow>
+            <mrow><mo>(</mo><mi>a</mi><mo>-</mo><mi>b</mi><mo>)</mo></mrow></math>";
+    let content = "<math><apply><times/>
+            <apply><plus/><ci>a</ci><ci>b</ci></apply>
+            <apply><minus/><ci>a</ci><ci>b</ci></apply>
+        </apply></math>";
+    test("en", "SimpleSpeak", expr, "open paren eigh plus b, close paren; times, open paren eigh minus b, close paren");
+    test("en", "SimpleSpeak", content, "open paren eigh plus b, close paren; times, open paren eigh minus b, close paren");
+}
+
+#[test]
+fn strict_fraction() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></math>";
+    let content = "<math><apply><csymbol cd='relation1'>eq</csymbol>
+            <ci>x</ci>
+            <apply><csymbol cd='arith1'>divide</csymbol><cn type='integer'>1</cn><cn type='integer'>2</cn></apply>
+        </apply></math>";
+    test("en", "ClearSpeak", expr, "x is equal to 1 half");
+    test("en", "ClearSpeak", content, "x is equal to 1 half");
+}
+
+#[test]
+fn trig_squared() {
+    let expr = "<math><msup><mi>sin</mi><mn>2</mn></msup><mi>x</mi><mo>+</mo>
+            <msup><mi>cos</mi><mn>2</mn></msup><mi>x</mi></math>";
+    let content = "<math><apply><plus/>
+            <apply><power/><apply><sin/><ci>x</ci></apply><cn>2</cn></apply>
+            <apply><power/><apply><cos/><ci>x</ci></apply><cn>2</cn></apply>
+        </apply></math>";
+    test("en", "ClearSpeak", expr, "sine squared of x, plus cosine squared of x");
+    test("en", "ClearSpeak", content, "sine squared of x, plus cosine squared of x");
+}
+
+#[test]
+fn definite_integral() {
+    let expr = "<math><msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><msup><mi>x</mi><mn>2</mn></msup>
+            <mi mathvariant='normal'>d</mi><mi>x</mi></math>";
+    let content = "<math><apply><int/>
+            <bvar><ci>x</ci></bvar><lowlimit><cn>0</cn></lowlimit><uplimit><cn>1</cn></uplimit>
+            <apply><power/><ci>x</ci><cn>2</cn></apply>
+        </apply></math>";
+    test("en", "SimpleSpeak", expr, "the integral from 0, to 1 of; x squared d x");
+    test("en", "SimpleSpeak", content, "the integral from 0, to 1 of; x squared d x");
+}
+
+#[test]
+fn strict_sum() {
+    let expr = "<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>
+            <msup><mi>i</mi><mn>2</mn></msup></math>";
+    let content = "<math><apply><csymbol cd='arith1'>sum</csymbol>
+            <apply><csymbol cd='interval1'>integer_interval</csymbol><cn>1</cn><ci>n</ci></apply>
+            <bind><csymbol cd='fns1'>lambda</csymbol><bvar><ci>i</ci></bvar>
+                <apply><csymbol cd='arith1'>power</csymbol><ci>i</ci><cn>2</cn></apply>
+            </bind>
+        </apply></math>";
+    test("en", "SimpleSpeak", expr, "the sum from i is equal to 1, to n of; i squared");
+    test("en", "SimpleSpeak", content, "the sum from i is equal to 1, to n of; i squared");
+}
+
+#[test]
+fn limit() {
+    let expr = "<math><munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>
+            <mfrac><mrow><mi>sin</mi><mi>x</mi></mrow><mi>x</mi></mfrac></math>";
+    let content = "<math><apply><limit/>
+            <bvar><ci>x</ci></bvar>
+            <condition><apply><tendsto/><ci>x</ci><cn>0</cn></apply></condition>
+            <apply><divide/><apply><sin/><ci>x</ci></apply><ci>x</ci></apply>
+        </apply></math>";
+    test("en", "SimpleSpeak", expr, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+    test("en", "SimpleSpeak", content, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+}
+
+#[test]
+fn open_interval() {
+    // without the intent, this would be spoken as a point
+    let expr = "<math><mrow intent='open-interval($a,$b)'><mo>(</mo><mn arg='a'>0</mn><mo>,</mo><mn arg='b'>1</mn><mo>)</mo></mrow></math>";
+    let content = "<math><interval closure='open'><cn>0</cn><cn>1</cn></interval></math>";
+    test("en", "SimpleSpeak", expr, "the open interval from 0 to 1");
+    test("en", "SimpleSpeak", content, "the open interval from 0 to 1");
+}
+
+#[test]
+fn set_builder() {
+    let expr = "<math><mrow><mo>{</mo><mi>x</mi><mo>|</mo><mi>x</mi><mo>&gt;</mo><mn>0</mn><mo>}</mo></mrow></math>";
+    let content = "<math><set>
+            <bvar><ci>x</ci></bvar>
+            <condition><apply><gt/><ci>x</ci><cn>0</cn></apply></condition>
+        </set></math>";
+    test("en", "SimpleSpeak", expr, "the set of all x such that x is greater than 0");
+    test("en", "SimpleSpeak", content, "the set of all x such that x is greater than 0");
+}
+
+#[test]
+fn matrix() {
+    let expr = "<math><mrow><mo>(</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>)</mo></mrow></math>";
+    let content = "<math><matrix>
+            <matrixrow><cn>1</cn><cn>2</cn></matrixrow>
+            <matrixrow><cn>3</cn><cn>4</cn></matrixrow>
+        </matrix></math>";
+    test("en", "SimpleSpeak", expr, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+    test("en", "SimpleSpeak", content, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+}
+
+#[test]
+fn transpose() {
+    let expr = "<math><msup intent='transpose($m)'><mi arg='m'>A</mi><mi>T</mi></msup></math>";
+    let content = "<math><apply><transpose/><ci>A</ci></apply></math>";
+    test("en", "SimpleSpeak", expr, "cap eigh transpose");
+    test("en", "SimpleSpeak", content, "cap eigh transpose");
+}
+
+#[test]
+fn semantics_with_only_content() {
+    let expr = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow></math>";
+    let content = "<math><semantics>
+            <apply><abs/><ci>x</ci></apply>
+            <annotation encoding='application/x-tex'>|x|</annotation>
+        </semantics></math>";
+    test("en", "ClearSpeak", expr, "the absolute value of x");
+    test("en", "ClearSpeak", content, "the absolute value of x");
+}
+
+#[test]
+fn semantics_content_annotation() {
+    // the presentation is ambiguous -- the content annotation says it is an interval
+    let expr = "<math><mrow><mo>(</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>)</mo></mrow></math>";
+    let semantics = "<math><semantics>
+            <mrow><mo>(</mo><mn>0</mn><mo>,</mo><mn>1</mn><mo>)</mo></mrow>
+            <annotation-xml encoding='MathML-Content'><interval closure='open'><cn>0</cn><cn>1</cn></interval></annotation-xml>
+        </semantics></math>";
+    test("en", "SimpleSpeak", expr, "open paren 0 comma, 1, close paren");
+    test("en", "SimpleSpeak", semantics, "the open interval from 0 to 1");
+}
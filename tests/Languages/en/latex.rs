@@ -0,0 +1,185 @@
+/// Tests for LaTeX input: each LaTeX string should speak the same as the equivalent MathML
+use crate::common::*;
+
+#[test]
+fn fraction() {
+    let expr = "<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>";
+    let latex = "\\frac{1}{2}";
+    test("en", "ClearSpeak", expr, "1 half");
+    test_latex("en", "ClearSpeak", latex, "1 half");
+}
+
+#[test]
+fn quadratic_formula() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mfrac>
+            <mrow><mo>-</mo><mi>b</mi><mo>±</mo><msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>-</mo><mn>4</mn><mi>a</mi><mi>c</mi></msqrt></mrow>
+            <mrow><mn>2</mn><mi>a</mi></mrow>
+        </mfrac></math>";
+    let latex = "x = \\frac{-b \\pm \\sqrt{b^2-4ac}}{2a}";
+    test("en", "SimpleSpeak", expr, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+    test_latex("en", "SimpleSpeak", latex, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+}
+
+#[test]
+fn cube_root() {
+    let expr = "<math><mroot><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mn>3</mn></mroot></math>";
+    let latex = "\\sqrt[3]{x+1}";
+    test("en", "ClearSpeak", expr, "the cube root of x plus 1");
+    test_latex("en", "ClearSpeak", latex, "the cube root of x plus 1");
+}
+
+#[test]
+fn sum() {
+    let expr = "<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>
+            <msup><mi>i</mi><mn>2</mn></msup></math>";
+    let latex = "\\sum_{i=1}^{n} i^2";
+    test("en", "SimpleSpeak", expr, "the sum from i is equal to 1, to n of; i squared");
+    test_latex("en", "SimpleSpeak", latex, "the sum from i is equal to 1, to n of; i squared");
+}
+
+#[test]
+fn integral() {
+    let expr = "<math><msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><mi>x</mi><mspace width='0.167em'/>
+            <mi mathvariant='normal'>d</mi><mi>x</mi></math>";
+    let latex = "\\int_0^1 x \\, \\mathrm{d}x";
+    test("en", "SimpleSpeak", expr, "the integral from 0, to 1 of, x d x");
+    test_latex("en", "SimpleSpeak", latex, "the integral from 0, to 1 of, x d x");
+}
+
+#[test]
+fn trig_identity() {
+    let expr = "<math><msup><mi>sin</mi><mn>2</mn></msup><mi>x</mi><mo>+</mo>
+            <msup><mi>cos</mi><mn>2</mn></msup><mi>x</mi><mo>=</mo><mn>1</mn></math>";
+    let latex = "\\sin^2 x + \\cos^2 x = 1";
+    test("en", "ClearSpeak", expr, "sine squared of x, plus cosine squared of x; is equal to 1");
+    test_latex("en", "ClearSpeak", latex, "sine squared of x, plus cosine squared of x; is equal to 1");
+}
+
+#[test]
+fn limit() {
+    let expr = "<math><munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>
+            <mfrac><mrow><mi>sin</mi><mi>x</mi></mrow><mi>x</mi></mfrac></math>";
+    let latex = "\\lim_{x \\to 0} \\frac{\\sin x}{x}";
+    test("en", "SimpleSpeak", expr, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+    test_latex("en", "SimpleSpeak", latex, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+}
+
+#[test]
+fn derivative_with_prime() {
+    let expr = "<math><msup><mi>f</mi><mo>′</mo></msup><mo>(</mo><mi>x</mi><mo>)</mo></math>";
+    let latex = "f'(x)";
+    test("en", "SimpleSpeak", expr, "f prime, of x");
+    test_latex("en", "SimpleSpeak", latex, "f prime, of x");
+}
+
+#[test]
+fn binomial() {
+    let expr = "<math><mrow><mo>(</mo><mfrac linethickness='0'><mi>n</mi><mi>k</mi></mfrac><mo>)</mo></mrow></math>";
+    let latex = "\\binom{n}{k}";
+    test("en", "SimpleSpeak", expr, "n choose k");
+    test_latex("en", "SimpleSpeak", latex, "n choose k");
+}
+
+#[test]
+fn absolute_value() {
+    let expr = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>&lt;</mo><mn>1</mn></math>";
+    let latex = "\\left| x \\right| < 1";
+    test("en", "ClearSpeak", expr, "the absolute value of x; is less than 1");
+    test_latex("en", "ClearSpeak", latex, "the absolute value of x; is less than 1");
+}
+
+#[test]
+fn vector_and_bar() {
+    let expr = "<math><mover><mi>v</mi><mo>→</mo></mover><mo>=</mo><mover><mi>x</mi><mo>¯</mo></mover></math>";
+    let latex = "\\vec{v} = \\bar{x}";
+    test("en", "SimpleSpeak", expr, "vector v, is equal to x bar");
+    test_latex("en", "SimpleSpeak", latex, "vector v, is equal to x bar");
+}
+
+#[test]
+fn real_numbers() {
+    let expr = "<math><mi>x</mi><mo>∈</mo><mi mathvariant='double-struck'>R</mi></math>";
+    let latex = "x \\in \\mathbb{R}";
+    test("en", "SimpleSpeak", expr, "x is an element of, the real numbers");
+    test_latex("en", "SimpleSpeak", latex, "x is an element of, the real numbers");
+}
+
+#[test]
+fn text() {
+    let expr = "<math><msup><mi>x</mi><mn>2</mn></msup><mtext> if </mtext><mi>x</mi><mo>&gt;</mo><mn>0</mn></math>";
+    let latex = "x^2 \\text{ if } x > 0";
+    test("en", "SimpleSpeak", expr, "x squared if x, is greater than 0");
+    test_latex("en", "SimpleSpeak", latex, "x squared if x, is greater than 0");
+}
+
+#[test]
+fn matrix() {
+    let expr = "<math><mrow><mo>(</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>)</mo></mrow></math>";
+    let latex = "\\begin{pmatrix} 1 & 2 \\\\ 3 & 4 \\end{pmatrix}";
+    test("en", "SimpleSpeak", expr, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+    test_latex("en", "SimpleSpeak", latex, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+}
+
+#[test]
+fn determinant() {
+    let expr = "<math><mrow><mo>|</mo><mtable>
+            <mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr>
+            <mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr>
+        </mtable><mo>|</mo></mrow></math>";
+    let latex = "\\begin{vmatrix} a & b \\\\ c & d \\end{vmatrix}";
+    test("en", "ClearSpeak", expr, "the 2 by 2 determinant; row 1; eigh, b; row 2; c, d");
+    test_latex("en", "ClearSpeak", latex, "the 2 by 2 determinant; row 1; eigh, b; row 2; c, d");
+}
+
+#[test]
+fn cases() {
+    let expr = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>=</mo><mrow><mo>{</mo><mtable columnalign='left'>
+            <mtr><mtd><mi>x</mi></mtd><mtd><mrow><mi>x</mi><mo>≥</mo><mn>0</mn></mrow></mtd></mtr>
+            <mtr><mtd><mrow><mo>-</mo><mi>x</mi></mrow></mtd><mtd><mrow><mi>x</mi><mo>&lt;</mo><mn>0</mn></mrow></mtd></mtr>
+        </mtable></mrow></math>";
+    let latex = "|x| = \\begin{cases} x & x \\geq 0 \\\\ -x & x < 0 \\end{cases}";
+    test("en", "ClearSpeak", expr, "the absolute value of x; is equal to; 2 cases; case 1; x x is greater than or equal to 0; case 2; negative x x is less than 0");
+    test_latex("en", "ClearSpeak", latex, "the absolute value of x; is equal to; 2 cases; case 1; x x is greater than or equal to 0; case 2; negative x x is less than 0");
+}
+
+#[test]
+fn aligned() {
+    let expr = "<math><mtable columnalign='right left'>
+            <mtr><mtd><mn>2</mn><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mn>4</mn></mrow></mtd></mtr>
+            <mtr><mtd><mi>x</mi></mtd><mtd><mrow><mo>=</mo><mn>2</mn></mrow></mtd></mtr>
+        </mtable></math>";
+    let latex = "\\begin{aligned} 2x &= 4 \\\\ x &= 2 \\end{aligned}";
+    test("en", "SimpleSpeak", expr, "2 equations; equation 1; 2 x is equal to 4; equation 2; x is equal to 2");
+    test_latex("en", "SimpleSpeak", latex, "2 equations; equation 1; 2 x is equal to 4; equation 2; x is equal to 2");
+}
+
+#[test]
+fn chemistry_water() {
+    let expr = "<math><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    let latex = "\\ce{H2O}";
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Medium")], expr, "cap h, sub 2 cap o");
+    test_latex("en", "ClearSpeak", latex, "cap h, sub 2 cap o");
+}
+
+#[test]
+fn chemistry_reaction() {
+    let expr = "<math><mn>2</mn><msub><mi>H</mi><mn>2</mn></msub><mo>+</mo><msub><mi>O</mi><mn>2</mn></msub>
+            <mo>→</mo><mn>2</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    let latex = "\\ce{2H2 + O2 -> 2H2O}";
+    test("en", "SimpleSpeak", expr, "2 cap h, sub 2, plus cap o, sub 2; reacts to form, 2, cap h, sub 2 cap o");
+    test_latex("en", "SimpleSpeak", latex, "2 cap h, sub 2, plus cap o, sub 2; reacts to form, 2, cap h, sub 2 cap o");
+}
+
+#[test]
+fn chemistry_ion() {
+    let expr = "<math><mrow><msup>
+            <mrow><mo>[</mo><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>]</mo></mrow>
+            <mrow><mn>2</mn><mo>&#x2212;</mo></mrow>
+        </msup></mrow></math>";
+    let latex = "\\ce{[SO4]^2-}";
+    test("en", "ClearSpeak", expr, "open bracket, cap s, cap o, sub 4; close bracket super 2 minus");
+    test_latex("en", "ClearSpeak", latex, "open bracket, cap s, cap o, sub 4; close bracket super 2 minus");
+}
@@ -0,0 +1,156 @@
+/// Tests for OMML (Word equation) input: each OMML string should speak the same as the equivalent MathML
+use crate::common::*;
+
+#[test]
+fn fraction() {
+    let expr = "<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>";
+    let omml = r#"<m:oMath xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">
+            <m:f><m:num><m:r><m:t>1</m:t></m:r></m:num><m:den><m:r><m:t>2</m:t></m:r></m:den></m:f>
+        </m:oMath>"#;
+    test("en", "ClearSpeak", expr, "1 half");
+    test_omml("en", "ClearSpeak", omml, "1 half");
+}
+
+#[test]
+fn quadratic_formula() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mfrac>
+            <mrow><mo>-</mo><mi>b</mi><mo>±</mo><msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>-</mo><mn>4</mn><mi>a</mi><mi>c</mi></msqrt></mrow>
+            <mrow><mn>2</mn><mi>a</mi></mrow>
+        </mfrac></math>";
+    let omml = "<m:oMathPara><m:oMath>
+            <m:r><m:t>x=</m:t></m:r>
+            <m:f>
+                <m:num>
+                    <m:r><m:t>-b±</m:t></m:r>
+                    <m:rad><m:radPr><m:degHide m:val='1'/></m:radPr><m:deg/>
+                        <m:e><m:sSup><m:e><m:r><m:t>b</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup><m:r><m:t>-4ac</m:t></m:r></m:e>
+                    </m:rad>
+                </m:num>
+                <m:den><m:r><m:t>2a</m:t></m:r></m:den>
+            </m:f>
+        </m:oMath></m:oMathPara>";
+    test("en", "SimpleSpeak", expr, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+    test_omml("en", "SimpleSpeak", omml, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+}
+
+#[test]
+fn sum() {
+    let expr = "<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>
+            <msup><mi>i</mi><mn>2</mn></msup></math>";
+    let omml = "<m:oMath><m:nary><m:naryPr><m:chr m:val='∑'/><m:limLoc m:val='undOvr'/></m:naryPr>
+            <m:sub><m:r><m:t>i=1</m:t></m:r></m:sub>
+            <m:sup><m:r><m:t>n</m:t></m:r></m:sup>
+            <m:e><m:sSup><m:e><m:r><m:t>i</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup></m:e>
+        </m:nary></m:oMath>";
+    test("en", "SimpleSpeak", expr, "the sum from i is equal to 1, to n of; i squared");
+    test_omml("en", "SimpleSpeak", omml, "the sum from i is equal to 1, to n of; i squared");
+}
+
+#[test]
+fn integral() {
+    let expr = "<math><msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><mi>x</mi>
+            <mi mathvariant='normal'>d</mi><mi>x</mi></math>";
+    let omml = "<m:oMath><m:nary><m:naryPr><m:limLoc m:val='subSup'/></m:naryPr>
+            <m:sub><m:r><m:t>0</m:t></m:r></m:sub>
+            <m:sup><m:r><m:t>1</m:t></m:r></m:sup>
+            <m:e><m:r><m:t>x</m:t></m:r><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>d</m:t></m:r><m:r><m:t>x</m:t></m:r></m:e>
+        </m:nary></m:oMath>";
+    test("en", "SimpleSpeak", expr, "the integral from 0, to 1 of, x d x");
+    test_omml("en", "SimpleSpeak", omml, "the integral from 0, to 1 of, x d x");
+}
+
+#[test]
+fn trig_function() {
+    let expr = "<math><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi></math>";
+    let omml = "<m:oMath><m:func>
+            <m:fName><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>sin</m:t></m:r></m:fName>
+            <m:e><m:r><m:t>x</m:t></m:r></m:e>
+        </m:func></m:oMath>";
+    test("en", "ClearSpeak", expr, "sine x");
+    test_omml("en", "ClearSpeak", omml, "sine x");
+}
+
+#[test]
+fn limit() {
+    let expr = "<math><munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder>
+            <mfrac><mrow><mi>sin</mi><mi>x</mi></mrow><mi>x</mi></mfrac></math>";
+    let omml = "<m:oMath><m:func>
+            <m:fName><m:limLow>
+                <m:e><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>lim</m:t></m:r></m:e>
+                <m:lim><m:r><m:t>x→0</m:t></m:r></m:lim>
+            </m:limLow></m:fName>
+            <m:e><m:f>
+                <m:num><m:func><m:fName><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>sin</m:t></m:r></m:fName><m:e><m:r><m:t>x</m:t></m:r></m:e></m:func></m:num>
+                <m:den><m:r><m:t>x</m:t></m:r></m:den>
+            </m:f></m:e>
+        </m:func></m:oMath>";
+    test("en", "SimpleSpeak", expr, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+    test_omml("en", "SimpleSpeak", omml, "the limit as x approaches 0, of, fraction, sine of x, over x, end fraction");
+}
+
+#[test]
+fn absolute_value() {
+    let expr = "<math><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>&lt;</mo><mn>1</mn></math>";
+    let omml = "<m:oMath>
+            <m:d><m:dPr><m:begChr m:val='|'/><m:endChr m:val='|'/></m:dPr><m:e><m:r><m:t>x</m:t></m:r></m:e></m:d>
+            <m:r><m:t>&lt;1</m:t></m:r>
+        </m:oMath>";
+    test("en", "ClearSpeak", expr, "the absolute value of x; is less than 1");
+    test_omml("en", "ClearSpeak", omml, "the absolute value of x; is less than 1");
+}
+
+#[test]
+fn matrix() {
+    let expr = "<math><mrow><mo>(</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>)</mo></mrow></math>";
+    let omml = "<m:oMath><m:d><m:e><m:m>
+            <m:mr><m:e><m:r><m:t>1</m:t></m:r></m:e><m:e><m:r><m:t>2</m:t></m:r></m:e></m:mr>
+            <m:mr><m:e><m:r><m:t>3</m:t></m:r></m:e><m:e><m:r><m:t>4</m:t></m:r></m:e></m:mr>
+        </m:m></m:e></m:d></m:oMath>";
+    test("en", "SimpleSpeak", expr, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+    test_omml("en", "SimpleSpeak", omml, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+}
+
+#[test]
+fn accents() {
+    let expr = "<math><mover><mi>v</mi><mo>→</mo></mover><mo>=</mo><mover><mi>x</mi><mo>¯</mo></mover></math>";
+    let omml = "<m:oMath>
+            <m:acc><m:accPr><m:chr m:val='&#x20D7;'/></m:accPr><m:e><m:r><m:t>v</m:t></m:r></m:e></m:acc>
+            <m:r><m:t>=</m:t></m:r>
+            <m:bar><m:barPr><m:pos m:val='top'/></m:barPr><m:e><m:r><m:t>x</m:t></m:r></m:e></m:bar>
+        </m:oMath>";
+    test("en", "SimpleSpeak", expr, "vector v, is equal to x bar");
+    test_omml("en", "SimpleSpeak", omml, "vector v, is equal to x bar");
+}
+
+#[test]
+fn equation_array() {
+    let expr = "<math><mtable columnalign='left'>
+            <mtr><mtd><mn>2</mn><mi>x</mi><mo>=</mo><mn>4</mn></mtd></mtr>
+            <mtr><mtd><mi>x</mi><mo>=</mo><mn>2</mn></mtd></mtr>
+        </mtable></math>";
+    let omml = "<m:oMath><m:eqArr>
+            <m:e><m:r><m:t>2x&amp;=4</m:t></m:r></m:e>
+            <m:e><m:r><m:t>x&amp;=2</m:t></m:r></m:e>
+        </m:eqArr></m:oMath>";
+    test("en", "SimpleSpeak", expr, "2 equations; equation 1; 2 x is equal to 4; equation 2; x is equal to 2");
+    test_omml("en", "SimpleSpeak", omml, "2 equations; equation 1; 2 x is equal to 4; equation 2; x is equal to 2");
+}
+
+#[test]
+fn word_run_properties() {
+    // runs from Word include "w:rPr" with font information that should be ignored
+    let expr = "<math><mi mathvariant='double-struck'>R</mi></math>";
+    let omml = r#"<m:oMath xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math"
+                xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+            <m:r>
+                <w:rPr><w:rFonts w:ascii="Cambria Math" w:hAnsi="Cambria Math"/></w:rPr>
+                <m:rPr><m:scr m:val="double-struck"/><m:sty m:val="p"/></m:rPr>
+                <m:t>R</m:t>
+            </m:r>
+        </m:oMath>"#;
+    test("en", "SimpleSpeak", expr, "the real numbers");
+    test_omml("en", "SimpleSpeak", omml, "the real numbers");
+}
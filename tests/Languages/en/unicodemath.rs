@@ -0,0 +1,65 @@
+/// Tests for UnicodeMath input: each UnicodeMath string should speak the same as the equivalent MathML
+use crate::common::*;
+
+#[test]
+fn fraction_drops_parens() {
+    let expr = "<math><mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac></math>";
+    let unicodemath = "(a+b)/c";
+    test("en", "SimpleSpeak", expr, "fraction, eigh plus b, over c, end fraction");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "fraction, eigh plus b, over c, end fraction");
+}
+
+#[test]
+fn fraction_of_products() {
+    let expr = "<math><mfrac><mrow><mn>2</mn><mi>π</mi><mi>r</mi></mrow><mi>T</mi></mfrac></math>";
+    let unicodemath = "2πr/T";
+    test("en", "SimpleSpeak", expr, "fraction, 2 pi r, over cap t, end fraction");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "fraction, 2 pi r, over cap t, end fraction");
+}
+
+#[test]
+fn quadratic_formula() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mfrac>
+            <mrow><mo>-</mo><mi>b</mi><mo>±</mo><msqrt><msup><mi>b</mi><mn>2</mn></msup><mo>-</mo><mn>4</mn><mi>a</mi><mi>c</mi></msqrt></mrow>
+            <mrow><mn>2</mn><mi>a</mi></mrow>
+        </mfrac></math>";
+    let unicodemath = "x=(-b±√(b^2-4ac))/2a";
+    test("en", "SimpleSpeak", expr, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "x is equal to; fraction, negative b plus or minus; the square root of b squared minus 4 eigh c, end root; over, 2 eigh, end fraction");
+}
+
+#[test]
+fn sum() {
+    let expr = "<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover>
+            <msup><mi>i</mi><mn>2</mn></msup></math>";
+    let unicodemath = "∑_(i=1)^n▒i^2";
+    test("en", "SimpleSpeak", expr, "the sum from i is equal to 1, to n of; i squared");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "the sum from i is equal to 1, to n of; i squared");
+}
+
+#[test]
+fn subscript_run() {
+    let expr = "<math><msub><mi>a</mi><mrow><mi>i</mi><mi>j</mi></mrow></msub></math>";
+    let unicodemath = "a_ij";
+    test("en", "SimpleSpeak", expr, "eigh sub i j");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "eigh sub i j");
+}
+
+#[test]
+fn control_words() {
+    let expr = "<math><mi>α</mi><mo>≤</mo><mi>∞</mi></math>";
+    let unicodemath = "\\alpha \\le \\infty";
+    test("en", "SimpleSpeak", expr, "alpha is less than or equal to, infinity");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "alpha is less than or equal to, infinity");
+}
+
+#[test]
+fn matrix() {
+    let expr = "<math><mrow><mo>(</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>)</mo></mrow></math>";
+    let unicodemath = "(■(1&2@3&4))";
+    test("en", "SimpleSpeak", expr, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+    test_unicodemath("en", "SimpleSpeak", unicodemath, "the 2 by 2 matrix; row 1; 1, 2; row 2; 3, 4");
+}
@@ -3,4 +3,5 @@
 mod general;
 mod calculus;
 mod linear_algebra;
-mod tables;
\ No newline at end of file
+mod tables;
+mod export;
\ No newline at end of file
@@ -0,0 +1,87 @@
+/// Tests for exporting the inferred intent as MathML `intent` and `arg` attributes
+/// The exported MathML is fed back in to make sure the speech doesn't change
+use crate::common::*;
+
+#[test]
+fn binomial() {
+    let mathml = "<math><mrow><mo>(</mo><mfrac linethickness='0'><mn>7</mn><mn>3</mn></mfrac><mo>)</mo></mrow></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec!["binomial:infix($a1,$a2)"], "7 choose 3");
+}
+
+#[test]
+fn binomial_keeps_arg() {
+    let mathml = "<math><mrow><mo>(</mo><mfrac linethickness='0'><mi arg='n'>n</mi><mi>k</mi></mfrac><mo>)</mo></mrow></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec!["binomial:infix($n,$a1)"], "n choose k");
+}
+
+#[test]
+fn absolute_value() {
+    let mathml = "<math><mo>|</mo><mi>x</mi><mo>|</mo><mo>+</mo><mn>1</mn></math>";
+    test_intent_round_trip("SimpleSpeak", mathml, vec!["absolute-value:function($a1)"], "the absolute value of x, plus 1");
+}
+
+#[test]
+fn interval() {
+    let mathml = "<math><mo>[</mo><mi>a</mi><mo>,</mo><mi>b</mi><mo>)</mo></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec!["closed-open-interval($a1,$a2)"],
+                "the interval from eigh to b, including eigh but not including b");
+}
+
+#[test]
+fn nested_intents() {
+    let mathml = "<math><mo>[</mo><mo>|</mo><mi>a</mi><mo>|</mo><mo>,</mo><msup><mi>B</mi><mi>T</mi></msup><mo>]</mo></math>";
+    test_intent_round_trip("SimpleSpeak", mathml,
+                vec!["closed-interval($a1,$a2)", "absolute-value:function($a1)", "transpose:postfix($a1)"],
+                "the closed interval from the absolute value of eigh to cap b transpose");
+}
+
+#[test]
+fn determinant() {
+    let mathml = "<math><mo>|</mo><mtable>
+            <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>
+            <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+        </mtable><mo>|</mo></math>";
+    test_intent_round_trip("SimpleSpeak", mathml, vec!["determinant($a1,$a2)"],
+                "the 2 by 2 determinant; row 1; 1, 2; row 2; 3, 4");
+}
+
+#[test]
+fn author_intent_kept() {
+    let mathml = "<math><mrow intent='foo($x,$y)'>
+            <mi arg='x'>a</mi><mo>+</mo><mrow><mo>|</mo><mi arg='y'>b</mi><mo>|</mo></mrow>
+        </mrow></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec!["foo($x,$y)"], "foo of eigh comma, b");
+}
+
+#[test]
+fn author_data_attrs_kept() {
+    let mathml = "<math><mrow data-foo='bar'><mo>|</mo><mi data-source='eq-1'>x</mi><mo>|</mo></mrow><mo>+</mo><mn>1</mn></math>";
+    test_intent_round_trip("SimpleSpeak", mathml, vec!["absolute-value:function($a1)"], "the absolute value of x, plus 1");
+    assert!(get_mathml_with_intent().unwrap().contains("data-foo='bar'"));
+    assert!(get_mathml_with_intent().unwrap().contains("data-source='eq-1'"));
+}
+
+#[test]
+fn no_inferred_intent() {
+    let mathml = "<math><msup><mi>e</mi><mrow><mi>i</mi><mi>π</mi></mrow></msup><mo>=</mo><mo>-</mo><mn>1</mn></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec![], "e raised to the i pi power, is equal to negative 1");
+}
+
+#[test]
+fn chemistry() {
+    // the chemistry concepts are internal to MathCAT (they aren't MathML 4 concepts), so nothing is exported
+    let mathml = "<math><msub><mi mathvariant='normal'>H</mi><mn>2</mn></msub><mi mathvariant='normal'>O</mi></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec![], "cap h, sub 2 cap o");
+}
+
+#[test]
+fn internal_concepts_not_exported() {
+    let mathml = "<math><mo>-</mo><msup><mi>x</mi><mo>′</mo></msup><mo>+</mo><msup><mi>x</mi><mrow><mo>*</mo></mrow></msup></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec![], "negative x prime, plus x star");
+}
+
+#[test]
+fn only_core_properties_exported() {
+    let mathml = "<math><mo>-</mo><mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow></math>";
+    test_intent_round_trip("ClearSpeak", mathml, vec!["absolute-value:function($a1)"], "negative the absolute value of x");
+}
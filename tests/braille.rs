@@ -10,6 +10,7 @@ mod braille {
         mod SRE_Nemeth72;
         mod SRE_NemethBase;
         mod AataNemeth;
+        mod ueb_context;
     }
     mod UEB {
         mod iceb;
@@ -24,6 +25,10 @@ mod braille {
         mod vi;
     }
 
+    mod French {
+        mod french;
+    }
+
     mod LaTeX {
         mod augenbit;
         mod other;
@@ -32,6 +37,7 @@ mod braille {
     mod ASCIIMath {
         mod augenbit;
         mod other;
+        mod eight_dot;
     }
 }
 
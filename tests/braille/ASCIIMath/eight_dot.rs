@@ -0,0 +1,171 @@
+// Tests for 8-dot computer braille output of ASCIIMath (and highlighting without dots 7 & 8)
+use crate::common::*;
+
+#[test]
+fn nabcc_simple () {
+    let expr = r#"<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("EightDot_Table", "NABCC")], expr, "⠭⡘⠆⠬⠂");
+}
+
+#[test]
+fn nabcc_capital_and_relation () {
+    let expr = r#"<math><mi>A</mi><mo>=</mo><mn>20</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("EightDot_Table", "NABCC")], expr, "⡁⠀⠿⠀⠆⠴");
+}
+
+#[test]
+fn nabcc_fraction () {
+    let expr = r#"<math><mfrac><mi>a</mi><mi>b</mi></mfrac></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("EightDot_Table", "NABCC")], expr, "⠁⠌⠃");
+}
+
+#[test]
+fn highlight_is_a_range () {
+    let expr = r#"<math id='id-0'>
+        <mrow id='id-1'>
+            <mi id='id-2'>A</mi>
+            <mo id='id-3'>=</mo>
+            <mrow id='id-4'>
+                <mn id='id-5'>20</mn>
+                <mo id='id-6'>+</mo>
+                <mi id='id-7'>x</mi>
+            </mrow>
+        </mrow>
+    </math>"#;
+    set_rules_dir(abs_rules_dir_path()).unwrap();
+    set_preference("BrailleCode", "ASCIIMath").unwrap();
+    set_preference("EightDot_Table", "NABCC").unwrap();
+    set_preference("BrailleNavHighlight", "EndPoints").unwrap();
+    set_mathml(expr).unwrap();
+    // capital A uses dot 7 -- a highlight must not add or remove dots
+    assert_eq!(get_braille("id-5").unwrap(), "⡁⠀⠿⠀⠆⠴⠬⠭");
+    set_navigation_node("id-5", 0).unwrap();
+    assert_eq!(get_braille_position().unwrap(), (4, 6));
+    set_navigation_node("id-4", 0).unwrap();
+    assert_eq!(get_braille_position().unwrap(), (4, 8));
+
+    set_preference("BrailleNavHighlight", "FirstChar").unwrap();
+    set_navigation_node("id-4", 0).unwrap();
+    assert_eq!(get_braille_position().unwrap(), (4, 5));
+}
+
+#[test]
+fn navigation_and_position_lookup () {
+    let expr = r#"<math id='id-0'>
+        <mrow id='id-1'>
+            <mi id='id-2'>A</mi>
+            <mo id='id-3'>=</mo>
+            <mrow id='id-4'>
+                <mn id='id-5'>20</mn>
+                <mo id='id-6'>+</mo>
+                <mi id='id-7'>X</mi>
+            </mrow>
+        </mrow>
+    </math>"#;
+    set_rules_dir(abs_rules_dir_path()).unwrap();
+    set_preference("BrailleCode", "ASCIIMath").unwrap();
+    set_preference("EightDot_Table", "NABCC").unwrap();
+    set_preference("BrailleNavHighlight", "EndPoints").unwrap();
+    set_preference("NavMode", "Enhanced").unwrap();
+    set_mathml(expr).unwrap();
+    // the capitals use dot 7, which must not be mistaken for a highlight
+    assert_eq!(get_braille("").unwrap(), "⡁⠀⠿⠀⠆⠴⠬⡭");
+    assert_eq!(get_navigation_node_from_braille_position(0).unwrap(), ("id-2".to_string(), 0));
+    assert_eq!(get_navigation_node_from_braille_position(5).unwrap(), ("id-5".to_string(), 1));
+    assert_eq!(get_navigation_node_from_braille_position(6).unwrap(), ("id-6".to_string(), 0));
+    assert_eq!(get_navigation_node_from_braille_position(7).unwrap(), ("id-7".to_string(), 0));
+
+    set_navigation_node("id-2", 0).unwrap();
+    do_navigate_command("MoveNext").unwrap();
+    assert_eq!(get_navigation_mathml_id().unwrap().0, "id-3");
+    assert_eq!(get_braille_position().unwrap(), (1, 4));     // the spaces around "=" are part of the operator
+    do_navigate_command("MoveNext").unwrap();
+    assert_eq!(get_navigation_mathml_id().unwrap().0, "id-4");
+    assert_eq!(get_braille_position().unwrap(), (4, 8));
+    assert_eq!(get_braille("id-4").unwrap(), "⡁⠀⠿⠀⠆⠴⠬⡭");
+}
+
+#[test]
+fn six_dot_code_has_no_highlight_dots () {
+    let expr = r#"<math id='id-0'>
+        <mrow id='id-1'>
+            <mi id='id-2'>x</mi>
+            <mo id='id-3'>+</mo>
+            <mn id='id-4'>1</mn>
+        </mrow>
+    </math>"#;
+    set_rules_dir(abs_rules_dir_path()).unwrap();
+    set_preference("BrailleCode", "Nemeth").unwrap();
+    set_preference("EightDot_Table", "NABCC").unwrap();
+    set_preference("BrailleNavHighlight", "EndPoints").unwrap();
+    set_mathml(expr).unwrap();
+    assert_eq!(get_braille("id-3").unwrap(), "⠭⠬⠂");
+    set_navigation_node("id-3", 0).unwrap();
+    assert_eq!(get_braille_position().unwrap(), (1, 2));
+}
+
+#[test]
+fn swedish_table () {
+    let expr = r#"<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("EightDot_Table", "Swedish")], expr, "⠭⠘⠣⢖⠡");
+}
+
+#[test]
+fn swedish_table_from_language () {
+    let expr = r#"<math><mi>A</mi><mo>=</mo><mn>20</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("Language", "sv"), ("EightDot_Table", "Auto")], expr, "⡁⠀⠶⠀⠣⠼");
+}
+
+#[test]
+fn norwegian_table_from_language () {
+    let expr = r#"<math><mi>A</mi><mo>=</mo><mn>20</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("Language", "nb"), ("EightDot_Table", "Auto")], expr, "⡁⠀⠶⠀⠣⠼");
+}
+
+#[test]
+fn nabcc_table_from_other_language () {
+    let expr = r#"<math><mi>A</mi><mo>=</mo><mn>20</mn></math>"#;
+    test_braille_prefs("ASCIIMath", vec![("Language", "en"), ("EightDot_Table", "Auto")], expr, "⡁⠀⠿⠀⠆⠴");
+}
+
+#[test]
+fn swedish_code () {
+    // the capital and number indicators are folded into 8-dot cells; the decimal comma is part of the number
+    let expr = r#"<math><mi>A</mi><mo>+</mo><mn>12</mn><mo>=</mo><mn>9,99</mn></math>"#;
+    test_braille_prefs("Swedish", vec![("Language", "sv"), ("EightDot_Table", "None")], expr, "⠠⠁⠀⠲⠀⠼⠁⠃⠀⠶⠀⠼⠊⠂⠊⠊");
+    test_braille_prefs("Swedish", vec![("Language", "sv"), ("EightDot_Table", "Auto")], expr, "⡁⠀⠲⠀⠡⠣⠀⠶⠀⠪⠂⠪⠪");
+}
+
+#[test]
+fn ueb_with_norwegian_table () {
+    let expr = r#"<math><mi>A</mi><mo>+</mo><mn>12.5</mn></math>"#;
+    test_braille_prefs("UEB", vec![("EightDot_Table", "None")], expr, "⠠⠁⠐⠖⠼⠁⠃⠲⠑");
+    test_braille_prefs("UEB", vec![("EightDot_Table", "Norwegian")], expr, "⡁⠐⠖⠡⠣⠄⠱");
+}
+
+#[test]
+fn swedish_code_highlight_and_lookup () {
+    let expr = r#"<math id='id-0'>
+        <mrow id='id-1'>
+            <mi id='id-2'>A</mi>
+            <mo id='id-3'>+</mo>
+            <mn id='id-4'>12</mn>
+            <mo id='id-5'>=</mo>
+            <mi id='id-6'>x</mi>
+        </mrow>
+    </math>"#;
+    set_rules_dir(abs_rules_dir_path()).unwrap();
+    set_preference("Language", "sv").unwrap();
+    set_preference("BrailleCode", "Swedish").unwrap();
+    set_preference("EightDot_Table", "Auto").unwrap();
+    set_preference("BrailleNavHighlight", "EndPoints").unwrap();
+    set_preference("NavMode", "Enhanced").unwrap();
+    set_mathml(expr).unwrap();
+    // the range is in 8-dot cells (the number indicator isn't there)
+    assert_eq!(get_braille("id-4").unwrap(), "⡁⠀⠲⠀⠡⠣⠀⠶⠀⠭");
+    set_navigation_node("id-4", 0).unwrap();
+    assert_eq!(get_braille_position().unwrap(), (4, 6));
+    assert_eq!(get_navigation_node_from_braille_position(0).unwrap(), ("id-2".to_string(), 0));
+    assert_eq!(get_navigation_node_from_braille_position(5).unwrap(), ("id-4".to_string(), 1));
+    assert_eq!(get_navigation_node_from_braille_position(9).unwrap(), ("id-6".to_string(), 0));
+}
@@ -0,0 +1,147 @@
+// French braille tests for the "Notation mathématique braille" (2007)
+// Numbers use Antoine digits, blocks are "⠰...⠆", exponents "⠈" and indices "⠢"
+use crate::common::*;
+
+#[test]
+fn number() {
+    let expr = r#"<math><mn>1234</mn></math>"#;
+    test_braille("French", expr, "⠡⠣⠩⠹");
+}
+
+#[test]
+fn decimal_comma() {
+    let expr = r#"<math><mn>3,14</mn></math>"#;
+    test_braille("French", expr, "⠩⠂⠡⠹");
+}
+
+#[test]
+fn sum() {
+    let expr = r#"<math><mrow><mn>5</mn><mo>+</mo><mn>12</mn><mo>=</mo><mn>17</mn></mrow></math>"#;
+    test_braille("French", expr, "⠱⠖⠡⠣⠶⠡⠻");
+}
+
+#[test]
+fn linear_equation() {
+    let expr = r#"<math><mrow><mi>y</mi><mo>=</mo><mn>2</mn><mi>x</mi><mo>&#x2212;</mo><mn>3</mn></mrow></math>"#;
+    test_braille("French", expr, "⠽⠶⠣⠭⠤⠩");
+}
+
+#[test]
+fn capital() {
+    let expr = r#"<math><mi>A</mi><mo>=</mo><mi>b</mi></math>"#;
+    test_braille("French", expr, "⠨⠁⠶⠃");
+}
+
+#[test]
+fn capital_run() {
+    let expr = r#"<math><mi>AB</mi></math>"#;
+    test_braille("French", expr, "⠨⠨⠁⠃");
+}
+
+#[test]
+fn greek() {
+    let expr = r#"<math><mn>2</mn><mi>&#x03C0;</mi><mi>r</mi></math>"#;
+    test_braille("French", expr, "⠣⠘⠏⠗");
+}
+
+#[test]
+fn capital_greek() {
+    let expr = r#"<math><mi>&#x0394;</mi><mi>x</mi></math>"#;
+    test_braille("French", expr, "⠘⠨⠙⠭");
+}
+
+#[test]
+fn simple_fraction() {
+    let expr = r#"<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>"#;
+    test_braille("French", expr, "⠡⠌⠣");
+}
+
+#[test]
+fn fraction_with_blocks() {
+    let expr = r#"<math><mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mrow><mn>2</mn><mi>c</mi></mrow></mfrac></math>"#;
+    test_braille("French", expr, "⠰⠁⠖⠃⠆⠌⠰⠣⠉⠆");
+}
+
+#[test]
+fn squared() {
+    let expr = r#"<math><msup><mi>x</mi><mn>2</mn></msup></math>"#;
+    test_braille("French", expr, "⠭⠈⠣");
+}
+
+#[test]
+fn exponent_block() {
+    let expr = r#"<math><msup><mi>e</mi><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></msup></math>"#;
+    test_braille("French", expr, "⠑⠈⠰⠭⠖⠡⠆");
+}
+
+#[test]
+fn negative_exponent() {
+    let expr = r#"<math><msup><mn>10</mn><mrow><mo>-</mo><mn>3</mn></mrow></msup></math>"#;
+    test_braille("French", expr, "⠡⠼⠈⠤⠩");
+}
+
+#[test]
+fn subscript() {
+    let expr = r#"<math><msub><mi>u</mi><mi>n</mi></msub><mo>+</mo><msub><mi>u</mi><mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msub></math>"#;
+    test_braille("French", expr, "⠥⠢⠝⠖⠥⠢⠰⠝⠖⠡⠆");
+}
+
+#[test]
+fn subsup() {
+    let expr = r#"<math><msubsup><mi>x</mi><mn>1</mn><mn>2</mn></msubsup></math>"#;
+    test_braille("French", expr, "⠭⠢⠡⠈⠣");
+}
+
+#[test]
+fn square_root() {
+    let expr = r#"<math><msqrt><mn>2</mn></msqrt></math>"#;
+    test_braille("French", expr, "⠜⠣");
+}
+
+#[test]
+fn square_root_block() {
+    let expr = r#"<math><msqrt><msup><mi>a</mi><mn>2</mn></msup><mo>+</mo><msup><mi>b</mi><mn>2</mn></msup></msqrt></math>"#;
+    test_braille("French", expr, "⠜⠰⠁⠈⠣⠖⠃⠈⠣⠆");
+}
+
+#[test]
+fn cube_root() {
+    let expr = r#"<math><mroot><mi>x</mi><mn>3</mn></mroot></math>"#;
+    test_braille("French", expr, "⠈⠩⠜⠭");
+}
+
+#[test]
+fn parens() {
+    let expr = r#"<math><mn>2</mn><mrow><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo></mrow></math>"#;
+    test_braille("French", expr, "⠣⠠⠦⠭⠖⠡⠴⠄");
+}
+
+#[test]
+fn inequality() {
+    let expr = r#"<math><mi>x</mi><mo>&#x2264;</mo><mn>5</mn></math>"#;
+    test_braille("French", expr, "⠭⠐⠅⠶⠱");
+}
+
+#[test]
+fn function_name() {
+    let expr = r#"<math><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi></math>"#;
+    test_braille("French", expr, "⠎⠊⠝⠀⠭");
+}
+
+#[test]
+fn function_name_with_parens() {
+    let expr = r#"<math><mi>f</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></math>"#;
+    test_braille("French", expr, "⠋⠠⠦⠭⠴⠄");
+}
+
+#[test]
+fn set_of_reals() {
+    let expr = r#"<math><mi>x</mi><mo>&#x2208;</mo><mi>&#x211D;</mi></math>"#;
+    test_braille("French", expr, "⠭⠘⠡⠨⠨⠗");
+}
+
+#[test]
+fn spaces_around_operators_pref() {
+    let expr = r#"<math><mi>a</mi><mo>=</mo><mi>b</mi></math>"#;
+    test_braille_prefs("French", vec![("UseSpacesAroundAllOperators", "true")], expr, "⠁⠀⠶⠀⠃");
+}
@@ -153,3 +153,27 @@ fn no_omission_for_spaces_in_middle() {
     test_braille("Nemeth", expr, "⠹⠂⠌⠆⠼⠷⠏⠬⠟⠾⠀⠕⠗⠀⠹⠏⠬⠟⠌⠆⠼");
 }
 
+
+#[test]
+fn emphasis_color_transcribers_note() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mrow mathcolor='red'><mn>2</mn><mi>y</mi></mrow></math>";
+    test_braille_prefs("Nemeth", vec![("BrailleEmphasis", "Color")], expr, "⠭⠬⠈⠨⠣⠗⠑⠙⠈⠨⠜⠆⠽⠈⠨⠣⠑⠝⠙⠈⠨⠜");
+}
+
+#[test]
+fn emphasis_color_off_by_default() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mrow mathcolor='red'><mn>2</mn><mi>y</mi></mrow></math>";
+    test_braille("Nemeth", expr, "⠭⠬⠆⠽");
+}
+
+#[test]
+fn emphasis_background_typeform() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mi mathbackground='yellow'>y</mi></math>";
+    test_braille_prefs("Nemeth", vec![("BrailleEmphasis", "All"), ("BrailleEmphasisStyle", "Typeform")], expr, "⠭⠬⠘⠼⠆⠽");
+}
+
+#[test]
+fn emphasis_enclosure() {
+    let expr = "<math><menclose notation='box'><mi>x</mi><mo>+</mo><mn>1</mn></menclose></math>";
+    test_braille_prefs("Nemeth", vec![("BrailleEmphasis", "Enclosure")], expr, "⠈⠨⠣⠃⠕⠭⠈⠨⠜⠫⠗⠸⠫⠭⠬⠂⠻⠈⠨⠣⠑⠝⠙⠈⠨⠜");
+}
@@ -0,0 +1,64 @@
+// Nemeth within UEB contexts
+// Examples modeled on the BANA "Guidance for Transcription Using the Nemeth Code within UEB Contexts"
+use crate::common::*;
+
+#[test]
+fn letters_and_number() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mi>y</mi><mo>=</mo><mn>2</mn></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr, "⠸⠩⠀⠭⠬⠽⠀⠨⠅⠀⠼⠆⠀⠸⠱");
+}
+
+#[test]
+fn number_at_start() {
+    let expr = "<math><mn>1</mn><mo>+</mo><mn>2</mn></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr, "⠸⠩⠀⠼⠂⠬⠆⠀⠸⠱");
+}
+
+#[test]
+fn pure_nemeth_unchanged() {
+    let expr = "<math><mn>1</mn><mo>+</mo><mn>2</mn></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "false")], expr, "⠼⠂⠬⠆");
+}
+
+#[test]
+fn one_word_switch() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mn>3</mn><mtext> or </mtext><mi>x</mi><mo>=</mo><mn>4</mn></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠸⠩⠀⠭⠀⠨⠅⠀⠼⠒⠀⠸⠰⠀⠕⠗⠀⠭⠀⠨⠅⠀⠼⠲⠀⠸⠱");
+}
+
+#[test]
+fn several_words_terminate() {
+    let expr = "<math><mi>x</mi><mo>&gt;</mo><mn>0</mn><mtext> for all values of </mtext><mi>y</mi></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠸⠩⠀⠭⠀⠨⠂⠀⠼⠴⠀⠸⠱⠀⠋⠕⠗⠀⠁⠇⠇⠀⠧⠁⠇⠥⠑⠎⠀⠕⠋⠀⠸⠩⠀⠽⠀⠸⠱");
+}
+
+#[test]
+fn text_at_end() {
+    let expr = "<math><mn>5</mn><mo>+</mo><mn>3</mn><mtext> apples</mtext></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠸⠩⠀⠼⠢⠬⠒⠀⠸⠱⠀⠁⠏⠏⠇⠑⠎");
+}
+
+#[test]
+fn text_at_start() {
+    let expr = "<math><mtext>Solve </mtext><mn>2</mn><mi>x</mi><mo>=</mo><mn>6</mn></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠠⠎⠕⠇⠧⠑⠀⠸⠩⠀⠼⠆⠭⠀⠨⠅⠀⠼⠖⠀⠸⠱");
+}
+
+#[test]
+fn ueb_text_digits_and_capitals() {
+    // the text uses UEB forms: capital and capitalized word indicators, and the UEB number indicator with upper cell digits
+    let expr = "<math><mi>x</mi><mo>=</mo><mn>3</mn><mtext> in Room 12 of NASA </mtext><mi>y</mi></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠸⠩⠀⠭⠀⠨⠅⠀⠼⠒⠀⠸⠱⠀⠊⠝⠀⠠⠗⠕⠕⠍⠀⠼⠁⠃⠀⠕⠋⠀⠠⠠⠝⠁⠎⠁⠀⠸⠩⠀⠽⠀⠸⠱");
+}
+
+#[test]
+fn ueb_text_one_word_with_digit() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mn>3</mn><mtext> Step2 </mtext><mi>y</mi></math>";
+    test_braille_prefs("Nemeth", vec![("Nemeth_UEBContext", "true")], expr,
+        "⠸⠩⠀⠭⠀⠨⠅⠀⠼⠒⠀⠸⠰⠀⠠⠎⠞⠑⠏⠼⠃⠀⠽⠀⠸⠱");
+}
@@ -156,3 +156,21 @@ fn caps_bug_295() {
 ";
     test_braille("UEB", expr, "⠠⠠⠠⠏⠟⠐⠖⠟⠗⠀⠐⠶⠀⠗⠎⠐⠖⠟⠗⠠⠄");
 }
+
+#[test]
+fn emphasis_color_transcribers_note() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mrow mathcolor='red'><mn>2</mn><mi>y</mi></mrow></math>";
+    test_braille_prefs("UEB", vec![("BrailleEmphasis", "Color")], expr, "⠭⠐⠖⠈⠨⠣⠗⠑⠙⠈⠨⠜⠼⠃⠽⠈⠨⠣⠑⠝⠙⠈⠨⠜");
+}
+
+#[test]
+fn emphasis_color_typeform() {
+    let expr = "<math><mi>x</mi><mo>+</mo><mrow mathcolor='#00F'><mn>2</mn><mi>y</mi></mrow></math>";
+    test_braille_prefs("UEB", vec![("BrailleEmphasis", "Color Background"), ("BrailleEmphasisStyle", "Typeform")], expr, "⠭⠐⠖⠘⠼⠂⠼⠃⠽⠘⠼⠄");
+}
+
+#[test]
+fn emphasis_mstyle_color() {
+    let expr = "<math><mstyle mathcolor='red'><mi>x</mi></mstyle><mo>=</mo><mn>1</mn></math>";
+    test_braille_prefs("UEB", vec![("BrailleEmphasis", "Color"), ("BrailleEmphasisStyle", "Typeform")], expr, "⠘⠼⠆⠭⠀⠐⠶⠀⠼⠁");
+}
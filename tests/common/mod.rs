@@ -72,6 +72,45 @@ pub fn test(language: &str, style: &str, mathml: &str, speech: &str) {
     check_answer(mathml, speech, &format!("{}/{}", language, style));
 }
 
+// Speak 'input' after setting it with 'set_input' (e.g., 'set_latex') and compare the result to 'speech'
+// This uses default preferences -- the 'speech' should be the same as that for the equivalent MathML
+fn check_input_answer(set_input: fn(&str) -> libmathcat::errors::Result<String>, language: &str, style: &str, input: &str, speech: &str) {
+    set_default_speech_prefs();
+    set_preference("Language", language).unwrap();
+    set_preference("SpeechStyle", style).unwrap();
+    if let Err(e) = set_input(input) {
+        panic!("{}", errors_to_string(&e));
+    };
+    match get_spoken_text() {
+        Ok(result) => assert_eq!(speech, strip_spaces(&result), "\ntest of '{}' with {}/{} failed", input, language, style),
+        Err(e) => panic!("{}", errors_to_string(&e)),
+    };
+}
+
+// Compare the result of speaking the LaTeX input to the output 'speech'
+#[allow(dead_code)]     // used in testing
+pub fn test_latex(language: &str, style: &str, latex: &str, speech: &str) {
+    check_input_answer(|input| set_latex(input), language, style, latex, speech);
+}
+
+// Compare the result of speaking the AsciiMath input to the output 'speech'
+#[allow(dead_code)]     // used in testing
+pub fn test_asciimath(language: &str, style: &str, asciimath: &str, speech: &str) {
+    check_input_answer(|input| set_asciimath(input), language, style, asciimath, speech);
+}
+
+// Compare the result of speaking the UnicodeMath input to the output 'speech'
+#[allow(dead_code)]     // used in testing
+pub fn test_unicodemath(language: &str, style: &str, unicodemath: &str, speech: &str) {
+    check_input_answer(|input| set_unicodemath(input), language, style, unicodemath, speech);
+}
+
+// Compare the result of speaking the OMML (Word's equation XML) input to the output 'speech'
+#[allow(dead_code)]     // used in testing
+pub fn test_omml(language: &str, style: &str, omml: &str, speech: &str) {
+    check_input_answer(|input| set_omml(input), language, style, omml, speech);
+}
+
 // Compare the result of speaking the mathml input to the output 'speech'
 // This takes the speech style along with a vector of (pref_name, pref_value)
 #[allow(dead_code)]     // used in testing
@@ -118,7 +157,8 @@ pub fn test_braille(code: &str, mathml: &str, braille: &str) {
     match code {
         "Vietnam" => set_preference("Language", "vi").unwrap(),
         "CMU" => set_preference("Language", "es").unwrap(),
-        "UEB" | "Nemeth" | _ => set_preference("Language", "en").unwrap(),
+        "French" => set_preference("Language", "fr").unwrap(),
+        _ => set_preference("Language", "en").unwrap(),
     }
     if let Err(e) = set_mathml(mathml) {
         panic!("{}", errors_to_string(&e));
@@ -140,7 +180,8 @@ pub fn test_braille_prefs(code: &str, test_prefs: Vec<(&str, &str)>, mathml: &st
     match code {
         "Vietnam" => set_preference("Language", "vi").unwrap(),
         "CMU" => set_preference("Language", "es").unwrap(),
-        "UEB" | "Nemeth" | _ => set_preference("Language", "en").unwrap(),
+        "French" => set_preference("Language", "fr").unwrap(),
+        _ => set_preference("Language", "en").unwrap(),
     }
 
     set_preference("UseSpacesAroundAllOperators", "false").unwrap();         // makes testing simpler
@@ -220,6 +261,33 @@ pub fn test_intent(mathml: &str, target: &str, test_prefs: Vec<(&str, &str)>) {
     }
 }
 
+// Check the speech of 'mathml', then export it with 'get_mathml_with_intent' and check that:
+//   1. 'intents' are exactly the values of the 'intent' attrs in the exported MathML (in document order)
+//      and no internal attrs ('data-*' and added 'id's) are exported
+//   2. feeding the exported MathML back in results in the same speech
+#[allow(dead_code)]     // used in testing
+pub fn test_intent_round_trip(style: &str, mathml: &str, intents: Vec<&str>, speech: &str) {
+    set_default_speech_prefs();
+    set_preference("Language", "en").unwrap();
+    set_preference("SpeechStyle", style).unwrap();
+    check_answer(mathml, speech, &format!("en/{}", style));
+    let annotated_mathml = match get_mathml_with_intent() {
+        Ok(annotated_mathml) => annotated_mathml,
+        Err(e) => panic!("{}", errors_to_string(&e)),
+    };
+    let exported_intents: Vec<&str> = annotated_mathml.split("intent='").skip(1)
+                .map(|rest| &rest[..rest.find('\'').unwrap()])
+                .collect();
+    assert_eq!(exported_intents, intents, "\nexported MathML:\n{}", annotated_mathml);
+    // the 'data-*' and 'id' attrs must be the author's (MathCAT's internal attrs are removed)
+    static ATTR_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#" ((?:data-[\w-]+)|id)=['"]"#).unwrap());
+    for attr_name in ATTR_NAME.captures_iter(&annotated_mathml) {
+        assert!(mathml.contains(&format!(" {}=", &attr_name[1])),
+                "\ninternal attr '{}' found in exported MathML:\n{}", &attr_name[1], annotated_mathml);
+    }
+    check_answer(&annotated_mathml, speech, &format!("en/{} after round trip of\n{}", style, annotated_mathml));
+}
+
 /// This is a prototype function to test whether 'from_braille' (or whatever gets called) is cannonically the same as 'mathml'
 #[allow(dead_code)]     // used in testing
 #[allow(non_snake_case)]
@@ -235,7 +303,8 @@ pub fn test_from_braille(code: &str, mathml: &str, braille: &str) {
     match code {
         "Vietnam" => set_preference("Language", "vi").unwrap(),
         "CMU" => set_preference("Language", "es").unwrap(),
-        "UEB" | "Nemeth" | _ => set_preference("Language", "en").unwrap(),
+        "French" => set_preference("Language", "fr").unwrap(),
+        _ => set_preference("Language", "en").unwrap(),
     }
     if let Err(e) = set_mathml(mathml) {
         panic!("{}", errors_to_string(&e));